@@ -1,24 +1,88 @@
+use std::error::Error;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use derive_more::Display;
 
+use protocol::traits::executor::{
+    ExecutorBalanceProof, ExecutorFactory, ExecutorReadResp, ExecutorTrace, TrieDB,
+};
 use protocol::traits::{APIAdapter, Context, MemPool, Storage};
-use protocol::types::{Address, Balance, Epoch, Hash, Receipt, SignedTransaction};
-use protocol::ProtocolResult;
+use protocol::types::{
+    next_cycles_price, Address, AssetID, Balance, ContractAddress, Epoch, EpochHeader, Fee, Hash,
+    MerkleRoot, Receipt, SignedTransaction, UserAddress,
+};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-pub struct DefaultAPIAdapter<M, S> {
+pub struct DefaultAPIAdapter<EF, M, S, DB> {
     mempool: Arc<M>,
     storage: Arc<S>,
+    trie_db: Arc<DB>,
+    // Builds the executors with the registered native contracts.
+    executor_factory: Arc<EF>,
+    // The max cycles a query can consume, the cycles limit of an epoch on the
+    // queried state if it is not set.
+    query_cycles_limit: Option<u64>,
 }
 
-impl<M: MemPool, S: Storage> DefaultAPIAdapter<M, S> {
-    pub fn new(mempool: Arc<M>, storage: Arc<S>) -> Self {
-        Self { mempool, storage }
+impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> DefaultAPIAdapter<EF, M, S, DB> {
+    pub fn new(
+        mempool: Arc<M>,
+        storage: Arc<S>,
+        trie_db: Arc<DB>,
+        executor_factory: Arc<EF>,
+        query_cycles_limit: Option<u64>,
+    ) -> Self {
+        Self {
+            mempool,
+            storage,
+            trie_db,
+            executor_factory,
+            query_cycles_limit,
+        }
+    }
+
+    // The state of an old epoch is gone once the trie database prunes it.
+    fn ensure_state(&self, epoch_id: u64, state_root: &MerkleRoot) -> ProtocolResult<()> {
+        if *state_root == Hash::from_empty() {
+            return Ok(());
+        }
+
+        let exists = self
+            .trie_db
+            .contains(&state_root.as_bytes())
+            .map_err(|e| APIError::TrieDB(e.to_string()))?;
+        if !exists {
+            return Err(APIError::StatePruned { epoch_id }.into());
+        }
+        Ok(())
+    }
+
+    // The latest epoch header, the state after the epoch and the cycles used
+    // by it. The header itself carries the ones of the epoch before.
+    async fn latest_state(&self) -> ProtocolResult<(EpochHeader, MerkleRoot, u64)> {
+        let epoch = self.storage.get_latest_epoch().await?;
+        let receipts = self
+            .storage
+            .get_receipts(epoch.ordered_tx_hashes.clone())
+            .await?;
+
+        let state_root = match receipts.last() {
+            Some(receipt) => receipt.state_root.clone(),
+            None => epoch.header.state_root.clone(),
+        };
+        let cycles_used = receipts.iter().fold(0u64, |acc, receipt| {
+            acc.saturating_add(receipt.cycles_used.cycle)
+        });
+        Ok((epoch.header, state_root, cycles_used))
     }
 }
 
 #[async_trait]
-impl<M: MemPool, S: Storage> APIAdapter for DefaultAPIAdapter<M, S> {
+impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> APIAdapter
+    for DefaultAPIAdapter<EF, M, S, DB>
+{
     async fn insert_signed_txs(
         &self,
         ctx: Context,
@@ -46,4 +110,176 @@ impl<M: MemPool, S: Storage> APIAdapter for DefaultAPIAdapter<M, S> {
     async fn get_balance(&self, _ctx: Context, _address: &Address) -> ProtocolResult<Balance> {
         Ok(Balance::from(0u64))
     }
+
+    async fn get_balance_proof(
+        &self,
+        _ctx: Context,
+        epoch_id: Option<u64>,
+        address: Address,
+        asset_id: AssetID,
+    ) -> ProtocolResult<ExecutorBalanceProof> {
+        let epoch = match epoch_id {
+            Some(epoch_id) => self.storage.get_epoch_by_epoch_id(epoch_id).await?,
+            None => self.storage.get_latest_epoch().await?,
+        };
+        let header = epoch.header;
+        self.ensure_state(header.epoch_id, &header.state_root)?;
+
+        let executor = self.executor_factory.from_root(
+            header.chain_id,
+            header.state_root,
+            Arc::clone(&self.trie_db),
+            header.epoch_id,
+            0,
+            Address::User(header.proposer),
+            false,
+        )?;
+        executor.get_balance_proof(&address, &asset_id)
+    }
+
+    async fn query(
+        &self,
+        _ctx: Context,
+        epoch_id: Option<u64>,
+        caller: Address,
+        contract: ContractAddress,
+        method: String,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<ExecutorReadResp> {
+        let epoch = match epoch_id {
+            Some(epoch_id) => self.storage.get_epoch_by_epoch_id(epoch_id).await?,
+            None => self.storage.get_latest_epoch().await?,
+        };
+        let header = epoch.header;
+        self.ensure_state(header.epoch_id, &header.state_root)?;
+
+        // Nothing is paid by a query, so the cycles price does not matter.
+        let executor = self.executor_factory.from_root(
+            header.chain_id,
+            header.state_root,
+            Arc::clone(&self.trie_db),
+            header.epoch_id,
+            0,
+            Address::User(header.proposer),
+            false,
+        )?;
+        let cycle = match self.query_cycles_limit {
+            Some(cycle) => cycle,
+            None => {
+                executor
+                    .get_chain_params()?
+                    .ok_or(APIError::NoCyclesLimit)?
+                    .cycles_limit
+            }
+        };
+        let cycles_limit = Fee {
+            asset_id: Hash::from_empty(),
+            cycle,
+        };
+
+        executor.read(&caller, &cycles_limit, &contract, &method, &args)
+    }
+
+    async fn simulate(
+        &self,
+        _ctx: Context,
+        sender: UserAddress,
+        mut signed_tx: SignedTransaction,
+    ) -> ProtocolResult<Receipt> {
+        let (header, state_root, cycles_used) = self.latest_state().await?;
+
+        // The transaction would be packed into the next epoch, it is executed
+        // with the base fee and the cycles limit of that epoch. The chain
+        // without the governance contract keeps the price of the latest epoch
+        // and the cycles limit of the transaction.
+        let params = self
+            .executor_factory
+            .from_root(
+                header.chain_id.clone(),
+                state_root.clone(),
+                Arc::clone(&self.trie_db),
+                header.epoch_id + 1,
+                0,
+                Address::User(header.proposer.clone()),
+                false,
+            )?
+            .get_chain_params()?;
+        let cycles_price = match params {
+            Some(params) => {
+                signed_tx.raw.fee.cycle = params.cycles_limit;
+                next_cycles_price(header.cycles_price, cycles_used, params.cycles_limit)
+                    .max(params.cycles_price)
+            }
+            None => header.cycles_price,
+        };
+
+        let mut executor = self.executor_factory.from_root(
+            header.chain_id,
+            state_root,
+            Arc::clone(&self.trie_db),
+            header.epoch_id + 1,
+            cycles_price,
+            Address::User(header.proposer),
+            false,
+        )?;
+        executor.simulate(sender, signed_tx)
+    }
+
+    async fn trace_transaction(
+        &self,
+        _ctx: Context,
+        tx_hash: Hash,
+    ) -> ProtocolResult<ExecutorTrace> {
+        let receipt = self.storage.get_receipt(tx_hash.clone()).await?;
+        let epoch = self.storage.get_epoch_by_epoch_id(receipt.epoch_id).await?;
+        let parent = self
+            .storage
+            .get_epoch_by_epoch_id(receipt.epoch_id.saturating_sub(1))
+            .await?;
+
+        // The transactions before it in the epoch are executed again to
+        // restore the state it was executed on.
+        let mut hashes = epoch
+            .ordered_tx_hashes
+            .iter()
+            .take_while(|hash| **hash != tx_hash)
+            .cloned()
+            .collect::<Vec<_>>();
+        hashes.push(tx_hash);
+        let signed_txs = self.storage.get_transactions(hashes).await?;
+
+        let header = epoch.header;
+        self.ensure_state(parent.header.epoch_id, &parent.header.state_root)?;
+
+        let mut executor = self.executor_factory.from_root(
+            header.chain_id,
+            parent.header.state_root,
+            Arc::clone(&self.trie_db),
+            header.epoch_id,
+            header.cycles_price,
+            Address::User(header.proposer),
+            false,
+        )?;
+        executor.trace(signed_txs)
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum APIError {
+    #[display(fmt = "the state of epoch {} is pruned", epoch_id)]
+    StatePruned { epoch_id: u64 },
+
+    #[display(fmt = "no cycles limit for the query, the state has no chain parameters")]
+    NoCyclesLimit,
+
+    #[display(fmt = "trie db: {}", _0)]
+    TrieDB(String),
+}
+
+impl Error for APIError {}
+
+impl From<APIError> for ProtocolError {
+    fn from(err: APIError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::API, Box::new(err))
+    }
 }
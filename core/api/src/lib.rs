@@ -19,8 +19,9 @@ use protocol::traits::{APIAdapter, Context};
 
 use crate::config::GraphQLConfig;
 use crate::schema::{
-    Bytes, ContractType, Epoch, Hash, InputDeployAction, InputRawTransaction,
-    InputTransactionEncryption, InputTransferAction,
+    Address, AssetID, BalanceProof, Bytes, ContractType, Epoch, Hash, InputCallAction,
+    InputDeployAction, InputMultiSignature, InputRawTransaction, InputTransactionEncryption,
+    InputTransferAction, QueryResult, SimulationResult, TraceResult, Uint64,
 };
 
 pub async fn start_graphql<Adapter: APIAdapter + 'static>(cfg: GraphQLConfig, adapter: Adapter) {
@@ -56,6 +57,169 @@ impl Query {
             .map_err(FieldError::from)?;
         Ok(Epoch::from(epoch))
     }
+
+    #[graphql(
+        name = "getBalanceProof",
+        description = "Get the balance of an asset with its proof against the state root of an epoch."
+    )]
+    fn get_balance_proof(
+        state_ctx: &State,
+        epoch_id: Option<Uint64>,
+        address: Address,
+        asset_id: AssetID,
+    ) -> FieldResult<BalanceProof> {
+        let epoch_id = match epoch_id {
+            Some(epoch_id) => Some(hex_to_u64(&epoch_id.as_hex())?),
+            None => None,
+        };
+        let address =
+            protocol::types::Address::from_hex(&address.as_hex()).map_err(FieldError::from)?;
+        let asset_id =
+            protocol::types::Hash::from_hex(&asset_id.as_hex()).map_err(FieldError::from)?;
+
+        let proof = block_on(state_ctx.adapter.get_balance_proof(
+            Context::new(),
+            epoch_id,
+            address,
+            asset_id,
+        ))
+        .map_err(FieldError::from)?;
+        Ok(BalanceProof::from(proof))
+    }
+
+    #[graphql(
+        name = "queryContract",
+        description = "Call a contract on the state of an epoch without changing it."
+    )]
+    fn query_contract(
+        state_ctx: &State,
+        epoch_id: Option<Uint64>,
+        caller: Address,
+        contract: Address,
+        method: String,
+        args: Vec<Bytes>,
+    ) -> FieldResult<QueryResult> {
+        let epoch_id = match epoch_id {
+            Some(epoch_id) => Some(hex_to_u64(&epoch_id.as_hex())?),
+            None => None,
+        };
+        let caller =
+            protocol::types::Address::from_hex(&caller.as_hex()).map_err(FieldError::from)?;
+        let contract = protocol::types::ContractAddress::from_hex(&contract.as_hex())
+            .map_err(FieldError::from)?;
+        let args = args
+            .iter()
+            .map(|arg| hex_to_vec_u8(&arg.as_hex()).map(bytes::Bytes::from))
+            .collect::<FieldResult<Vec<_>>>()?;
+
+        let resp = block_on(state_ctx.adapter.query(
+            Context::new(),
+            epoch_id,
+            caller,
+            contract,
+            method,
+            args,
+        ))
+        .map_err(FieldError::from)?;
+        Ok(QueryResult::from(resp))
+    }
+
+    #[graphql(
+        name = "simulateTransferTransaction",
+        description = "Execute a transfer transaction in the next epoch without changing the state, the sender is given by inputSender or inputPubkey."
+    )]
+    fn simulate_transfer_transaction(
+        state_ctx: &State,
+        input_raw: InputRawTransaction,
+        input_action: InputTransferAction,
+        input_pubkey: Option<Bytes>,
+        input_sender: Option<Address>,
+    ) -> FieldResult<SimulationResult> {
+        let action = cover_transfer_action(&input_action)?;
+        simulate(state_ctx, &action, &input_raw, input_pubkey, input_sender)
+    }
+
+    #[graphql(
+        name = "simulateDeployTransaction",
+        description = "Execute a deployment transaction in the next epoch without changing the state, the sender is given by inputSender or inputPubkey."
+    )]
+    fn simulate_deploy_transaction(
+        state_ctx: &State,
+        input_raw: InputRawTransaction,
+        input_action: InputDeployAction,
+        input_pubkey: Option<Bytes>,
+        input_sender: Option<Address>,
+    ) -> FieldResult<SimulationResult> {
+        let action = cover_deploy_action(&input_action)?;
+        simulate(state_ctx, &action, &input_raw, input_pubkey, input_sender)
+    }
+
+    #[graphql(
+        name = "simulateCallTransaction",
+        description = "Execute a call transaction in the next epoch without changing the state, the sender is given by inputSender or inputPubkey."
+    )]
+    fn simulate_call_transaction(
+        state_ctx: &State,
+        input_raw: InputRawTransaction,
+        input_action: InputCallAction,
+        input_pubkey: Option<Bytes>,
+        input_sender: Option<Address>,
+    ) -> FieldResult<SimulationResult> {
+        let action = cover_call_action(&input_action)?;
+        simulate(state_ctx, &action, &input_raw, input_pubkey, input_sender)
+    }
+
+    #[graphql(
+        name = "traceTransaction",
+        description = "Execute a committed transaction again and record its invocations, state reads and writes and cycles."
+    )]
+    fn trace_transaction(state_ctx: &State, tx_hash: Hash) -> FieldResult<TraceResult> {
+        let tx_hash =
+            protocol::types::Hash::from_hex(&tx_hash.as_hex()).map_err(FieldError::from)?;
+
+        let trace = block_on(state_ctx.adapter.trace_transaction(Context::new(), tx_hash))
+            .map_err(FieldError::from)?;
+        Ok(TraceResult::from(trace))
+    }
+}
+
+// The transaction is not signed, it is sent by the given sender or the one
+// recovered from the public key.
+fn simulate(
+    state_ctx: &State,
+    action: &protocol::types::TransactionAction,
+    input_raw: &InputRawTransaction,
+    input_pubkey: Option<Bytes>,
+    input_sender: Option<Address>,
+) -> FieldResult<SimulationResult> {
+    let input_encryption = InputTransactionEncryption {
+        tx_hash:   Hash::from(protocol::types::Hash::from_empty()),
+        pubkey:    input_pubkey.unwrap_or_else(|| Bytes::from(bytes::Bytes::new())),
+        signature: Bytes::from(bytes::Bytes::new()),
+        multisig:  None,
+    };
+    let signed_tx = cover_to_signed_tx(action, input_raw, &input_encryption)?;
+
+    let sender = match input_sender {
+        Some(sender) => {
+            protocol::types::UserAddress::from_hex(&sender.as_hex()).map_err(FieldError::from)?
+        }
+        None if !signed_tx.pubkey.is_empty() => signed_tx.sender().map_err(FieldError::from)?,
+        None => {
+            return Err(FieldError::new(
+                "Either inputPubkey or inputSender is required",
+                juniper::Value::null(),
+            ))
+        }
+    };
+
+    let receipt = block_on(
+        state_ctx
+            .adapter
+            .simulate(Context::new(), sender, signed_tx),
+    )
+    .map_err(FieldError::from)?;
+    Ok(SimulationResult::from(receipt))
 }
 
 struct Mutation;
@@ -214,6 +378,7 @@ fn gen_input_tx_encryption(
         tx_hash:   tx_hash.clone(),
         pubkey:    Bytes::from(pubkey.to_bytes()),
         signature: Bytes::from(signature.to_bytes()),
+        multisig:  None,
     };
     Ok(input_encryption)
 }
@@ -221,8 +386,8 @@ fn gen_input_tx_encryption(
 // Convert from graphql type to protocol type
 // #####################
 
-const TRANSFER_TRANSACTION_FIELD_LENGTH: usize = 8;
-const DEPLOY_TRANSACTION_FIELD_LENGTH: usize = 7;
+const TRANSFER_TRANSACTION_FIELD_LENGTH: usize = 9;
+const DEPLOY_TRANSACTION_FIELD_LENGTH: usize = 9;
 fn calculate_hash_of_transfer(
     raw: &InputRawTransaction,
     action: &InputTransferAction,
@@ -234,6 +399,7 @@ fn calculate_hash_of_transfer(
     stream.append(&hex_to_vec_u8(&raw.fee_asset_id.as_hex())?);
     stream.append(&hex_to_vec_u8(&raw.nonce.as_hex())?);
     stream.append(&hex_to_vec_u8(&raw.timeout.as_hex())?);
+    stream.append(&hex_to_vec_u8(&raw.max_cycles_price.as_hex())?);
 
     stream.append(&hex_to_vec_u8(&action.carrying_amount.as_hex())?);
     stream.append(&hex_to_vec_u8(&action.carrying_asset_id.as_hex())?);
@@ -254,6 +420,7 @@ fn calculate_hash_of_deploy(
     stream.append(&hex_to_vec_u8(&raw.fee_asset_id.as_hex())?);
     stream.append(&hex_to_vec_u8(&raw.nonce.as_hex())?);
     stream.append(&hex_to_vec_u8(&raw.timeout.as_hex())?);
+    stream.append(&hex_to_vec_u8(&raw.max_cycles_price.as_hex())?);
 
     stream.append(&hex_to_vec_u8(&action.code.as_hex())?);
     let type_flag: u32 = match action.contract_type {
@@ -263,6 +430,20 @@ fn calculate_hash_of_deploy(
     };
     stream.append(&type_flag);
 
+    if let Some(asset) = &action.asset {
+        stream.begin_list(5);
+        stream.append(&asset.name.as_bytes());
+        stream.append(&asset.symbol.as_bytes());
+        stream.append(&hex_to_vec_u8(&asset.supply.as_hex())?);
+        stream.append(&hex_to_vec_u8(&asset.decimals.as_hex())?);
+        match &asset.manage_contract {
+            Some(address) => stream.append(&hex_to_vec_u8(&address.as_hex())?),
+            None => stream.append_empty_data(),
+        };
+    } else {
+        stream.append_empty_data();
+    }
+
     let hash = protocol::types::Hash::digest(bytes::Bytes::from(stream.out()));
     Ok(Hash::from(hash))
 }
@@ -273,17 +454,18 @@ fn cover_to_signed_tx(
     input_encryption: &InputTransactionEncryption,
 ) -> FieldResult<protocol::types::SignedTransaction> {
     let raw = protocol::types::RawTransaction {
-        chain_id: protocol::types::Hash::from_hex(&input_raw.chain_id.as_hex())
+        chain_id:         protocol::types::Hash::from_hex(&input_raw.chain_id.as_hex())
             .map_err(FieldError::from)?,
-        nonce:    protocol::types::Hash::from_hex(&input_raw.nonce.as_hex())
+        nonce:            protocol::types::Hash::from_hex(&input_raw.nonce.as_hex())
             .map_err(FieldError::from)?,
-        timeout:  hex_to_u64(&input_raw.timeout.as_hex())?,
-        fee:      protocol::types::Fee {
+        timeout:          hex_to_u64(&input_raw.timeout.as_hex())?,
+        fee:              protocol::types::Fee {
             asset_id: protocol::types::AssetID::from_hex(&input_raw.fee_asset_id.as_hex())
                 .map_err(FieldError::from)?,
             cycle:    hex_to_u64(&input_raw.fee_cycle.as_hex())?,
         },
-        action:   action.clone(),
+        max_cycles_price: hex_to_u64(&input_raw.max_cycles_price.as_hex())?,
+        action:           action.clone(),
     };
 
     let signed_tx = protocol::types::SignedTransaction {
@@ -292,11 +474,39 @@ fn cover_to_signed_tx(
             .map_err(FieldError::from)?,
         pubkey: bytes::Bytes::from(hex_to_vec_u8(&input_encryption.pubkey.as_hex())?),
         signature: bytes::Bytes::from(hex_to_vec_u8(&input_encryption.signature.as_hex())?),
+        multisig: match &input_encryption.multisig {
+            Some(multisig) => Some(cover_to_multisig(multisig)?),
+            None => None,
+        },
     };
 
     Ok(signed_tx)
 }
 
+fn cover_to_multisig(
+    input_multisig: &InputMultiSignature,
+) -> FieldResult<protocol::types::MultiSignature> {
+    let pubkeys = input_multisig
+        .pubkeys
+        .iter()
+        .map(|pubkey| hex_to_vec_u8(&pubkey.as_hex()).map(bytes::Bytes::from))
+        .collect::<FieldResult<Vec<_>>>()?;
+    let signatures = input_multisig
+        .signatures
+        .iter()
+        .map(|signature| hex_to_vec_u8(&signature.as_hex()).map(bytes::Bytes::from))
+        .collect::<FieldResult<Vec<_>>>()?;
+
+    let multisig = protocol::types::MultiSignature {
+        threshold: u8::try_from(hex_to_u64(&input_multisig.threshold.as_hex())?)
+            .map_err(FieldError::from)?,
+        pubkeys,
+        signatures,
+    };
+
+    Ok(multisig)
+}
+
 fn cover_transfer_action(
     input_action: &InputTransferAction,
 ) -> FieldResult<protocol::types::TransactionAction> {
@@ -324,9 +534,49 @@ fn cover_deploy_action(
         ContractType::Library => protocol::types::ContractType::Library,
     };
 
+    let asset = match &input_action.asset {
+        Some(asset) => Some(protocol::types::DeployAsset {
+            name:            asset.name.clone(),
+            symbol:          asset.symbol.clone(),
+            supply:          protocol::types::Balance::from_bytes_be(
+                hex_to_vec_u8(&asset.supply.as_hex())?.as_ref(),
+            ),
+            decimals:        hex_to_u64(&asset.decimals.as_hex())?,
+            manage_contract: match &asset.manage_contract {
+                Some(address) => Some(
+                    protocol::types::ContractAddress::from_hex(&address.as_hex())
+                        .map_err(FieldError::from)?,
+                ),
+                None => None,
+            },
+        }),
+        None => None,
+    };
+
     let action = protocol::types::TransactionAction::Deploy {
         code: bytes::Bytes::from(hex_to_vec_u8(&input_action.code.as_hex())?),
         contract_type,
+        asset,
+    };
+
+    Ok(action)
+}
+
+fn cover_call_action(
+    input_action: &InputCallAction,
+) -> FieldResult<protocol::types::TransactionAction> {
+    let args = input_action
+        .args
+        .iter()
+        .map(|arg| hex_to_vec_u8(&arg.as_hex()).map(bytes::Bytes::from))
+        .collect::<FieldResult<Vec<_>>>()?;
+
+    let action = protocol::types::TransactionAction::Call {
+        contract: protocol::types::ContractAddress::from_hex(&input_action.contract.as_hex())
+            .map_err(FieldError::from)?,
+        method: input_action.method.clone(),
+        args,
+        carrying_asset: None,
     };
 
     Ok(action)
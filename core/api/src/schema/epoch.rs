@@ -19,6 +19,7 @@ pub struct EpochHeader {
     pub state_root:   MerkleRoot,
     pub receipt_root: Vec<MerkleRoot>,
     pub cycles_used:  Uint64,
+    pub cycles_price: Uint64,
     pub proposer:     Address,
     // proof:             Proof,
     pub validator_version: Uint64,
@@ -45,6 +46,7 @@ impl From<protocol::types::EpochHeader> for EpochHeader {
                 .map(MerkleRoot::from)
                 .collect(),
             cycles_used:       Uint64::from(epoch_header.cycles_used),
+            cycles_price:      Uint64::from(epoch_header.cycles_price),
             proposer:          Address::from(protocol::types::Address::User(epoch_header.proposer)),
             validator_version: Uint64::from(epoch_header.validator_version),
         }
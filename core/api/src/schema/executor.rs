@@ -0,0 +1,182 @@
+use crate::schema::{Address, Balance, Bytes, Fee, MerkleRoot, Uint64};
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The result of a contract query")]
+pub struct QueryResult {
+    pub return_value: Bytes,
+    pub cycles_used:  Fee,
+}
+
+impl From<protocol::traits::executor::ExecutorReadResp> for QueryResult {
+    fn from(resp: protocol::traits::executor::ExecutorReadResp) -> Self {
+        QueryResult {
+            return_value: Bytes::from(resp.return_value),
+            cycles_used:  Fee::from(resp.cycles_used),
+        }
+    }
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The receipt a transaction would produce")]
+pub struct SimulationResult {
+    pub cycles_used: Fee,
+    pub success:     bool,
+    // The contract deployed or called by the transaction.
+    pub contract:     Option<Address>,
+    pub return_value: Option<Bytes>,
+    pub error:        Option<String>,
+}
+
+impl From<protocol::types::Receipt> for SimulationResult {
+    fn from(receipt: protocol::types::Receipt) -> Self {
+        use protocol::types::ReceiptResult;
+
+        let mut result = SimulationResult {
+            cycles_used:  Fee::from(receipt.cycles_used),
+            success:      true,
+            contract:     None,
+            return_value: None,
+            error:        None,
+        };
+
+        match receipt.result {
+            ReceiptResult::Deploy { contract, .. } => {
+                result.contract = Some(Address::from(protocol::types::Address::Contract(contract)));
+            }
+            ReceiptResult::Call {
+                contract,
+                return_value,
+                ..
+            } => {
+                result.contract = Some(Address::from(protocol::types::Address::Contract(contract)));
+                result.return_value = Some(Bytes::from(return_value));
+            }
+            ReceiptResult::Fail { system, user } => {
+                result.success = false;
+                result.error = Some(format!("{} {}", system, user).trim().to_owned());
+            }
+            _ => (),
+        }
+
+        result
+    }
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The balance of an account with its proof against a state root")]
+pub struct BalanceProof {
+    pub state_root:    MerkleRoot,
+    pub contract_root: MerkleRoot,
+    pub balance:       Balance,
+    // The proof of the account contract root in the world state trie.
+    pub state_proof: Vec<Bytes>,
+    // The proof of the account in the account contract trie.
+    pub account_proof: Vec<Bytes>,
+}
+
+impl From<protocol::traits::executor::ExecutorBalanceProof> for BalanceProof {
+    fn from(proof: protocol::traits::executor::ExecutorBalanceProof) -> Self {
+        BalanceProof {
+            state_root:    MerkleRoot::from(proof.state_root),
+            contract_root: MerkleRoot::from(proof.contract_root),
+            balance:       Balance::from(proof.balance),
+            state_proof:   proof.state_proof.into_iter().map(Bytes::from).collect(),
+            account_proof: proof.account_proof.into_iter().map(Bytes::from).collect(),
+        }
+    }
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(
+    description = "The receipt of a transaction with everything recorded during its execution"
+)]
+pub struct TraceResult {
+    pub receipt: SimulationResult,
+    pub events:  Vec<TraceEvent>,
+}
+
+impl From<protocol::traits::executor::ExecutorTrace> for TraceResult {
+    fn from(trace: protocol::traits::executor::ExecutorTrace) -> Self {
+        TraceResult {
+            receipt: SimulationResult::from(trace.receipt),
+            events:  trace.events.into_iter().map(TraceEvent::from).collect(),
+        }
+    }
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(
+    description = "An invocation, a state read, a state write or cycles charged, the fields of the other kinds are null"
+)]
+pub struct TraceEvent {
+    // One of "invoke", "read", "write" and "cycles".
+    pub kind:      String,
+    pub address:   Option<Address>,
+    pub method:    Option<String>,
+    pub args:      Option<Vec<Bytes>>,
+    pub key:       Option<Bytes>,
+    pub value:     Option<Bytes>,
+    pub old_value: Option<Bytes>,
+    pub action:    Option<String>,
+    pub cycles:    Option<Uint64>,
+}
+
+impl From<protocol::traits::executor::TraceEvent> for TraceEvent {
+    fn from(event: protocol::traits::executor::TraceEvent) -> Self {
+        use protocol::traits::executor::TraceEvent as Event;
+
+        let mut result = TraceEvent {
+            kind:      "".to_owned(),
+            address:   None,
+            method:    None,
+            args:      None,
+            key:       None,
+            value:     None,
+            old_value: None,
+            action:    None,
+            cycles:    None,
+        };
+
+        match event {
+            Event::Invoke {
+                address,
+                method,
+                args,
+            } => {
+                result.kind = "invoke".to_owned();
+                result.address = Some(Address::from(address));
+                result.method = Some(method);
+                result.args = Some(args.into_iter().map(Bytes::from).collect());
+            }
+            Event::StateRead {
+                address,
+                key,
+                value,
+            } => {
+                result.kind = "read".to_owned();
+                result.address = Some(Address::from(address));
+                result.key = Some(Bytes::from(key));
+                result.value = value.map(Bytes::from);
+            }
+            Event::StateWrite {
+                address,
+                key,
+                old_value,
+                new_value,
+            } => {
+                result.kind = "write".to_owned();
+                result.address = Some(Address::from(address));
+                result.key = Some(Bytes::from(key));
+                result.value = Some(Bytes::from(new_value));
+                result.old_value = old_value.map(Bytes::from);
+            }
+            Event::Cycles { action, cycles } => {
+                result.kind = "cycles".to_owned();
+                result.action = Some(action);
+                result.cycles = Some(Uint64::from(cycles));
+            }
+        }
+
+        result
+    }
+}
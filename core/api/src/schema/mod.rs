@@ -1,10 +1,12 @@
 mod epoch;
+mod executor;
 mod transaction;
 
 pub use epoch::{Epoch, EpochHeader};
+pub use executor::{BalanceProof, QueryResult, SimulationResult, TraceResult};
 pub use transaction::{
-    ContractType, InputDeployAction, InputRawTransaction, InputTransactionEncryption,
-    InputTransferAction,
+    ContractType, InputCallAction, InputDeployAction, InputMultiSignature, InputRawTransaction,
+    InputTransactionEncryption, InputTransferAction,
 };
 
 #[derive(GraphQLScalarValue, Clone)]
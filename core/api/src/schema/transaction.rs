@@ -22,6 +22,8 @@ pub struct InputRawTransaction {
     pub fee_asset_id: AssetID,
     pub nonce:        Hash,
     pub timeout:      Uint64,
+    // The highest cycles price to pay, it must not be lower than the base fee.
+    pub max_cycles_price: Uint64,
 }
 
 #[derive(GraphQLInputObject, Clone)]
@@ -30,6 +32,17 @@ pub struct InputTransactionEncryption {
     pub tx_hash:   Hash,
     pub pubkey:    Bytes,
     pub signature: Bytes,
+    // Signed by a multi-signature account, `pubkey` and `signature` are empty.
+    pub multisig: Option<InputMultiSignature>,
+}
+
+#[derive(GraphQLInputObject, Clone)]
+#[graphql(description = "input signatures of a multi-signature account.")]
+pub struct InputMultiSignature {
+    pub threshold: Uint64,
+    pub pubkeys:   Vec<Bytes>,
+    // Aligned with the pubkeys, an empty signature means the key does not sign.
+    pub signatures: Vec<Bytes>,
 }
 
 #[derive(GraphQLInputObject, Clone)]
@@ -45,4 +58,25 @@ pub struct InputTransferAction {
 pub struct InputDeployAction {
     pub code:          Bytes,
     pub contract_type: ContractType,
+    // Required by the asset contract.
+    pub asset: Option<InputDeployAsset>,
+}
+
+#[derive(GraphQLInputObject, Clone)]
+#[graphql(description = "input metadata of the deployed asset.")]
+pub struct InputDeployAsset {
+    pub name:     String,
+    pub symbol:   String,
+    pub supply:   Balance,
+    pub decimals: Uint64,
+    // Only the manage contract can mint and burn the asset.
+    pub manage_contract: Option<Address>,
+}
+
+#[derive(GraphQLInputObject, Clone)]
+#[graphql(description = "input call action.")]
+pub struct InputCallAction {
+    pub contract: Address,
+    pub method:   String,
+    pub args:     Vec<Bytes>,
 }
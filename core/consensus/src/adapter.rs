@@ -1,8 +1,10 @@
-use std::marker::PhantomData;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 
+use common_crypto::Secp256k1;
+use core_mempool::{verify_signature, verify_tx_hash};
 use protocol::traits::executor::{ExecutorExecResp, ExecutorFactory, TrieDB};
 use protocol::traits::{
     ConsensusAdapter, Context, CurrentConsensusStatus, Gossip, MemPool, MessageTarget,
@@ -11,6 +13,8 @@ use protocol::traits::{
 use protocol::types::{Address, Epoch, Hash, Proof, Receipt, SignedTransaction, Validator};
 use protocol::ProtocolResult;
 
+use crate::ConsensusError;
+
 pub struct OverlordConsensusAdapter<
     EF: ExecutorFactory<DB>,
     G: Gossip,
@@ -22,8 +26,10 @@ pub struct OverlordConsensusAdapter<
     mempool: Arc<M>,
     storage: Arc<S>,
     trie_db: Arc<DB>,
-
-    pin_ef: PhantomData<EF>,
+    // Builds the executors with the registered native contracts.
+    executor_factory: Arc<EF>,
+    // Record the state root after each transaction in its receipt.
+    intermediate_state_root: bool,
 }
 
 #[async_trait]
@@ -48,6 +54,42 @@ where
         self.mempool.ensure_order_txs(ctx, check_txs).await
     }
 
+    async fn verify_txs(
+        &self,
+        _ctx: Context,
+        node_info: NodeInfo,
+        status: CurrentConsensusStatus,
+        signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        let executor = self.executor_factory.from_root(
+            node_info.chain_id,
+            status.state_root,
+            Arc::clone(&self.trie_db),
+            status.epoch_id,
+            status.cycles_price,
+            Address::User(node_info.self_address),
+            false,
+        )?;
+
+        // The nonces used by the epoch are not recorded in the state yet.
+        let mut nonces = HashSet::new();
+        for signed_tx in signed_txs.iter() {
+            // The transactions synced from the proposer skip the checks of
+            // the mempool.
+            verify_tx_hash(signed_tx)?;
+            verify_signature::<Secp256k1>(signed_tx)?;
+            executor.check_tx(signed_tx)?;
+            if !nonces.insert((signed_tx.sender()?, signed_tx.raw.nonce.clone())) {
+                return Err(ConsensusError::ReusedNonce(
+                    signed_tx.raw.nonce.clone(),
+                    signed_tx.tx_hash.clone(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
     async fn sync_txs(&self, ctx: Context, txs: Vec<Hash>) -> ProtocolResult<()> {
         self.mempool.sync_propose_txs(ctx, txs).await
     }
@@ -90,15 +132,19 @@ where
         coinbase: Address,
         signed_txs: Vec<SignedTransaction>,
     ) -> ProtocolResult<ExecutorExecResp> {
-        let mut executor = EF::from_root(
+        let mut executor = self.executor_factory.from_root(
             node_info.chain_id,
             status.state_root,
             Arc::clone(&self.trie_db),
             status.epoch_id,
             status.cycles_price,
             coinbase,
+            self.intermediate_state_root,
         )?;
-        executor.exec(signed_txs)
+        let resp = executor.exec(signed_txs)?;
+
+        self.trie_db.record_root(&resp.state_root);
+        Ok(resp)
     }
 
     async fn flush_mempool(&self, ctx: Context, txs: Vec<Hash>) -> ProtocolResult<()> {
@@ -143,14 +189,21 @@ where
     S: Storage,
     DB: TrieDB,
 {
-    pub fn new(network: Arc<G>, mempool: Arc<M>, storage: Arc<S>, trie_db: Arc<DB>) -> Self {
+    pub fn new(
+        network: Arc<G>,
+        mempool: Arc<M>,
+        storage: Arc<S>,
+        trie_db: Arc<DB>,
+        executor_factory: Arc<EF>,
+        intermediate_state_root: bool,
+    ) -> Self {
         OverlordConsensusAdapter {
             network,
             mempool,
             storage,
             trie_db,
-
-            pin_ef: PhantomData,
+            executor_factory,
+            intermediate_state_root,
         }
     }
 }
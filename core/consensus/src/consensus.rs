@@ -9,12 +9,12 @@ use parking_lot::RwLock;
 use common_crypto::{PrivateKey, Secp256k1PrivateKey};
 
 use protocol::traits::{Consensus, ConsensusAdapter, CurrentConsensusStatus, NodeInfo};
-use protocol::types::{Epoch, Proof, SignedTransaction, Validator};
+use protocol::types::{Epoch, Proof, SignedTransaction};
 use protocol::ProtocolResult;
 
 use crate::engine::ConsensusEngine;
 use crate::fixed_types::{FixedPill, FixedSignedTxs};
-use crate::util::OverlordCrypto;
+use crate::util::{gen_duration_config, OverlordCrypto};
 use crate::{ConsensusError, MsgType};
 
 /// Provide consensus
@@ -94,11 +94,7 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
         overlord_handler
             .send_msg(
                 Context::new(),
-                OverlordMsg::RichStatus(gen_overlord_status(
-                    current_consensus_status.read().epoch_id,
-                    current_consensus_status.read().consensus_interval,
-                    current_consensus_status.read().validators.clone(),
-                )),
+                OverlordMsg::RichStatus(gen_overlord_status(&current_consensus_status.read())),
             )
             .unwrap();
 
@@ -123,9 +119,10 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
     }
 }
 
-fn gen_overlord_status(epoch_id: u64, interval: u64, validators: Vec<Validator>) -> Status {
-    let mut authority_list = validators
-        .into_iter()
+fn gen_overlord_status(status: &CurrentConsensusStatus) -> Status {
+    let mut authority_list = status
+        .validators
+        .iter()
         .map(|v| Node {
             address:        v.address.as_bytes(),
             propose_weight: v.propose_weight,
@@ -135,8 +132,9 @@ fn gen_overlord_status(epoch_id: u64, interval: u64, validators: Vec<Validator>)
 
     authority_list.sort();
     Status {
-        epoch_id,
-        interval: Some(interval),
+        epoch_id: status.epoch_id,
+        interval: Some(status.consensus_interval),
+        timer_config: Some(gen_duration_config(status)),
         authority_list,
     }
 }
@@ -14,13 +14,16 @@ use protocol::codec::ProtocolCodec;
 use protocol::traits::{
     ConsensusAdapter, Context, CurrentConsensusStatus, MessageTarget, NodeInfo,
 };
-use protocol::types::{Address, Epoch, EpochHeader, Hash, Pill, Proof, UserAddress, Validator};
+use protocol::types::{
+    next_cycles_price, Address, Epoch, EpochHeader, Hash, Pill, Proof, UserAddress, Validator,
+};
 use protocol::ProtocolError;
 
 use crate::fixed_types::{FixedPill, FixedSignedTxs};
 use crate::message::{
     END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE,
 };
+use crate::util::{gen_duration_config, next_validators};
 use crate::ConsensusError;
 
 /// validator is for create new epoch, and authority is for build overlord
@@ -66,9 +69,10 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
             state_root: current_consensus_status.state_root.clone(),
             receipt_root: current_consensus_status.receipt_root.clone(),
             cycles_used: current_consensus_status.cycles_used,
+            cycles_price: current_consensus_status.cycles_price,
             proposer: self.node_info.self_address.clone(),
             proof: current_consensus_status.proof.clone(),
-            validator_version: 0u64,
+            validator_version: current_consensus_status.validator_version,
             validators: current_consensus_status.validators.clone(),
         };
         let epoch = Epoch {
@@ -97,6 +101,25 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         hash: Bytes,
         epoch: FixedPill,
     ) -> Result<FixedSignedTxs, Box<dyn Error + Send>> {
+        let status = { self.current_consensus_status.read().clone() };
+        let header = &epoch.inner.epoch.header;
+        if header.cycles_price != status.cycles_price {
+            return Err(ProtocolError::from(ConsensusError::InvalidCyclesPrice(
+                header.cycles_price,
+                status.cycles_price,
+            ))
+            .into());
+        }
+        if header.validator_version != status.validator_version
+            || header.validators != status.validators
+        {
+            return Err(ProtocolError::from(ConsensusError::InvalidValidators(
+                header.validator_version,
+                status.validator_version,
+            ))
+            .into());
+        }
+
         let order_hashes = epoch.get_ordered_hashes();
         let exemption = {
             let set = self.exemption_hash.read();
@@ -114,7 +137,12 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
                 .await?;
         }
 
-        let inner = self.adapter.get_full_txs(ctx, order_hashes).await?;
+        let inner = self.adapter.get_full_txs(ctx.clone(), order_hashes).await?;
+        if !exemption {
+            self.adapter
+                .verify_txs(ctx, self.node_info.clone(), status, inner.clone())
+                .await?;
+        }
         Ok(FixedSignedTxs { inner })
     }
 
@@ -181,6 +209,10 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
             .await?;
 
         let prev_hash = Hash::digest(epoch.encode().await?);
+        let cycles_used = exec_resp
+            .all_cycles_used
+            .iter()
+            .fold(0u64, |acc, fee| acc.saturating_add(fee.cycle));
 
         // TODO: update current consensus status
         let current_consensus_status = {
@@ -189,6 +221,37 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
             current_consensus_status.prev_hash = prev_hash;
             current_consensus_status.proof = proof;
             current_consensus_status.state_root = exec_resp.state_root.clone();
+            current_consensus_status.logs_bloom = exec_resp.logs_bloom;
+            current_consensus_status.cycles_used = cycles_used;
+
+            // The parameters of the governance contract take effect from the
+            // next epoch, the base fee follows the new cycles limit as well.
+            let mut min_cycles_price = 1;
+            if let Some(params) = exec_resp.chain_params {
+                current_consensus_status.cycles_limit = params.cycles_limit;
+                current_consensus_status.consensus_interval = params.interval;
+                current_consensus_status.propose_ratio =
+                    (params.propose_numerator, params.propose_denominator);
+                current_consensus_status.prevote_ratio =
+                    (params.prevote_numerator, params.prevote_denominator);
+                current_consensus_status.precommit_ratio =
+                    (params.precommit_numerator, params.precommit_denominator);
+                min_cycles_price = params.cycles_price;
+            }
+            current_consensus_status.cycles_price = next_cycles_price(
+                current_consensus_status.cycles_price,
+                cycles_used,
+                current_consensus_status.cycles_limit,
+            )
+            .max(min_cycles_price);
+
+            let (validators, validator_version) = next_validators(
+                &current_consensus_status.validators,
+                current_consensus_status.validator_version,
+                exec_resp.validators,
+            );
+            current_consensus_status.validators = validators;
+            current_consensus_status.validator_version = validator_version;
 
             current_consensus_status.clone()
         };
@@ -196,6 +259,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         let status = Status {
             epoch_id:       epoch_id + 1,
             interval:       Some(current_consensus_status.consensus_interval),
+            timer_config:   Some(gen_duration_config(&current_consensus_status)),
             authority_list: covert_to_overlord_authority(&current_consensus_status.validators),
         };
         Ok(status)
@@ -57,7 +57,7 @@ impl Encodable for FixedPill {
             .map(|v| FixedValidator::from(v.to_owned()))
             .collect::<Vec<_>>();
 
-        s.begin_list(14)
+        s.begin_list(15)
             .append(&header.chain_id.as_hex())
             .append(&header.epoch_id)
             .append(&header.pre_hash.as_hex())
@@ -68,6 +68,7 @@ impl Encodable for FixedPill {
             .append(&header.state_root.as_hex())
             .append_list::<String, String>(&receipt_root)
             .append(&header.cycles_used)
+            .append(&header.cycles_price)
             .append(&header.proposer.as_hex())
             .append(&FixedProof::from(header.proof.clone()))
             .append(&header.validator_version)
@@ -183,6 +184,7 @@ mod test {
             nonce,
             timeout: random::<u64>(),
             fee,
+            max_cycles_price: random::<u64>(),
             action,
         };
 
@@ -194,6 +196,7 @@ mod test {
             tx_hash,
             pubkey: Bytes::from(gen_random_bytes(32)),
             signature: Bytes::from(gen_random_bytes(64)),
+            multisig: None,
         }
     }
 
@@ -7,6 +7,7 @@ pub mod fixed_types;
 pub mod message;
 
 pub use overlord::DurationConfig;
+pub use util::next_validators;
 
 use std::error::Error;
 
@@ -56,6 +57,18 @@ pub enum ConsensusError {
     #[display(fmt = "Consensus missed epoch header of {} epoch", _0)]
     MissingEpochHeader(u64),
 
+    /// The cycles price of the epoch is not the expected base fee.
+    #[display(fmt = "Invalid cycles price {}, expect {}", _0, _1)]
+    InvalidCyclesPrice(u64, u64),
+
+    /// The validators of the epoch are not the current ones.
+    #[display(fmt = "Invalid validator version {}, expect {}", _0, _1)]
+    InvalidValidators(u64, u64),
+
+    /// The nonce of the sender is used twice in the epoch.
+    #[display(fmt = "Reused nonce {:?} of transaction {:?}", _0, _1)]
+    ReusedNonce(Hash, Hash),
+
     /// This boxed error should be a `CryptoError`.
     #[display(fmt = "Crypto error {:?}", _0)]
     CryptoErr(Box<CryptoError>),
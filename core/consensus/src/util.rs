@@ -1,9 +1,10 @@
 use std::error::Error;
 
 use bytes::Bytes;
-use overlord::{types::AggregatedSignature, Crypto};
+use overlord::{types::AggregatedSignature, Crypto, DurationConfig};
 
-use protocol::types::{Hash, UserAddress};
+use protocol::traits::CurrentConsensusStatus;
+use protocol::types::{Hash, UserAddress, Validator};
 use protocol::ProtocolError;
 
 use common_crypto::{
@@ -73,3 +74,32 @@ impl OverlordCrypto {
         }
     }
 }
+
+/// The validators elected by the staking contract take effect once they
+/// differ from the current ones, and the validator version is increased. The
+/// current validators are kept if no one is elected, e.g. the validators of
+/// the genesis before anyone bonds.
+pub fn next_validators(
+    validators: &[Validator],
+    validator_version: u64,
+    elected: Vec<Validator>,
+) -> (Vec<Validator>, u64) {
+    if elected.is_empty() || elected.as_slice() == validators {
+        (validators.to_vec(), validator_version)
+    } else {
+        (elected, validator_version + 1)
+    }
+}
+
+/// The timeouts of the consensus steps in the current status, which may be
+/// changed by the governance contract.
+pub fn gen_duration_config(status: &CurrentConsensusStatus) -> DurationConfig {
+    DurationConfig::new(
+        status.propose_ratio.0,
+        status.propose_ratio.1,
+        status.prevote_ratio.0,
+        status.prevote_ratio.1,
+        status.precommit_ratio.0,
+        status.precommit_ratio.1,
+    )
+}
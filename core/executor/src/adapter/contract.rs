@@ -1,27 +1,83 @@
+use std::any::Any;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::mem;
 use std::rc::Rc;
 
 use bytes::Bytes;
 use derive_more::{Display, From};
 
 use protocol::traits::executor::contract::ContractStateAdapter;
-use protocol::traits::executor::{ContractSchema, ContractSer, TrieDB};
-use protocol::types::MerkleRoot;
+use protocol::traits::executor::{ContractSchema, ContractSer, RcTracer, TraceEvent, TrieDB};
+use protocol::types::{Address, MerkleRoot};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::trie::MPTTrie;
 
 pub type RcGeneralContractStateAdapter<DB> = Rc<RefCell<GeneralContractStateAdapter<DB>>>;
 
+// A removed key is cached as `None`.
+pub type CacheMap = HashMap<Bytes, Option<Box<dyn CacheValue>>>;
+
+/// A decoded value in the cache, it is only encoded when it is committed to
+/// the trie.
+pub trait CacheValue: Send {
+    fn as_any(&self) -> &dyn Any;
+
+    fn encode(&self) -> ProtocolResult<Bytes>;
+}
+
+impl<T: ContractSer + Send + 'static> CacheValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        ContractSer::encode(self)
+    }
+}
+
+// A cache with the encoded sizes of its keys and values, which are measured
+// once they are written.
+#[derive(Default)]
+struct SizedCache {
+    map:   CacheMap,
+    sizes: HashMap<Bytes, usize>,
+    bytes: usize,
+}
+
+impl SizedCache {
+    fn insert(&mut self, key: Bytes, value: Option<Box<dyn CacheValue>>, size: usize) {
+        if let Some(old_size) = self.sizes.insert(key.clone(), size) {
+            self.bytes -= old_size;
+        }
+        self.bytes += size;
+        self.map.insert(key, value);
+    }
+
+    fn extend(&mut self, other: SizedCache) {
+        let mut sizes = other.sizes;
+        for (key, value) in other.map {
+            let size = sizes.remove(&key).unwrap_or_default();
+            self.insert(key, value, size);
+        }
+    }
+}
+
 pub struct GeneralContractStateAdapter<DB: TrieDB> {
     trie: MPTTrie<DB>,
 
-    // TODO(@yejiayu): The value of HashMap should be changed to Box<dyn Any> to avoid multiple
-    // serializations.
-    cache_map: HashMap<Bytes, Bytes>,
-    stash_map: HashMap<Bytes, Bytes>,
+    cache_map: SizedCache,
+    // The caches of the outer checkpoints, the innermost one is the last.
+    checkpoints: Vec<SizedCache>,
+    stash_map:   CacheMap,
+    // The keys read from the trie, used to find the conflicts of the parallel
+    // execution.
+    read_set: RefCell<HashSet<Bytes>>,
+
+    // The reads and writes are recorded with the address of the contract.
+    tracer: Option<(Address, RcTracer)>,
 }
 
 impl<DB: TrieDB> GeneralContractStateAdapter<DB> {
@@ -29,12 +85,82 @@ impl<DB: TrieDB> GeneralContractStateAdapter<DB> {
         Self {
             trie,
 
-            cache_map: HashMap::new(),
+            cache_map: SizedCache::default(),
+            checkpoints: vec![],
             stash_map: HashMap::new(),
+            read_set: RefCell::new(HashSet::new()),
+
+            tracer: None,
+        }
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<(Address, RcTracer)>) {
+        self.tracer = tracer;
+    }
+
+    // Take the stashed changes, which can be moved to the adapter of another
+    // executor built from the same state root.
+    pub fn take_stash(&mut self) -> CacheMap {
+        mem::replace(&mut self.stash_map, HashMap::new())
+    }
+
+    // Take the keys read from the trie, the keys of the stash are the written
+    // ones.
+    pub fn take_reads(&mut self) -> HashSet<Bytes> {
+        mem::replace(self.read_set.get_mut(), HashSet::new())
+    }
+
+    pub fn extend_stash(&mut self, stash: CacheMap) {
+        self.stash_map.extend(stash);
+    }
+
+    // Nothing to commit if nothing is stashed, the root stays the same.
+    pub fn has_stash(&self) -> bool {
+        !self.stash_map.is_empty()
+    }
+
+    // The size of the keys and values cached in the checkpoints.
+    pub fn cached_bytes(&self) -> usize {
+        self.checkpoints
+            .iter()
+            .chain(Some(&self.cache_map))
+            .map(|cache| cache.bytes)
+            .sum()
+    }
+
+    // `Some(None)` if the key is removed in the cache.
+    fn get_cache(&self, key: &Bytes) -> Option<Option<&dyn CacheValue>> {
+        self.cache_map
+            .map
+            .get(key)
+            .or_else(|| {
+                self.checkpoints
+                    .iter()
+                    .rev()
+                    .find_map(|cache| cache.map.get(key))
+            })
+            .or_else(|| self.stash_map.get(key))
+            .map(|value| value.as_ref().map(|value| value.as_ref()))
+    }
+
+    fn get_bytes(&self, key: &Bytes) -> ProtocolResult<Option<Bytes>> {
+        match self.get_cache(key) {
+            Some(Some(value)) => Ok(Some(value.encode()?)),
+            Some(None) => Ok(None),
+            None => self.trie.get(key),
         }
     }
 }
 
+// The same key may be read by another schema, its value is converted by the
+// encoding.
+fn downcast<V: ContractSer + Clone + 'static>(value: &dyn CacheValue) -> ProtocolResult<V> {
+    match value.as_any().downcast_ref::<V>() {
+        Some(value) => Ok(value.clone()),
+        None => V::decode(value.encode()?),
+    }
+}
+
 impl<DB: TrieDB> ContractStateAdapter for GeneralContractStateAdapter<DB> {
     fn get<Schema: ContractSchema>(
         &self,
@@ -42,21 +168,31 @@ impl<DB: TrieDB> ContractStateAdapter for GeneralContractStateAdapter<DB> {
     ) -> ProtocolResult<Option<<Schema as ContractSchema>::Value>> {
         let encoded_key = key.encode()?;
 
-        if let Some(value_bytes) = self.cache_map.get(&encoded_key) {
-            let inst = <_>::decode(value_bytes.clone())?;
-            return Ok(Some(inst));
-        }
-
-        if let Some(value_bytes) = self.stash_map.get(&encoded_key) {
-            let inst = <_>::decode(value_bytes.clone())?;
-            return Ok(Some(inst));
-        }
+        let value = match self.get_cache(&encoded_key) {
+            Some(Some(value)) => Some(downcast(value)?),
+            Some(None) => None,
+            None => {
+                self.read_set.borrow_mut().insert(encoded_key.clone());
+                match self.trie.get(&encoded_key)? {
+                    Some(value_bytes) => Some(Schema::Value::decode(value_bytes)?),
+                    None => None,
+                }
+            }
+        };
 
-        if let Some(value_bytes) = self.trie.get(&encoded_key)? {
-            return Ok(Some(Schema::Value::decode(value_bytes)?));
+        if let Some((address, tracer)) = &self.tracer {
+            let value_bytes = match &value {
+                Some(value) => Some(ContractSer::encode(value)?),
+                None => None,
+            };
+            tracer.borrow_mut().push(TraceEvent::StateRead {
+                address: address.clone(),
+                key:     encoded_key,
+                value:   value_bytes,
+            });
         }
 
-        Ok(None)
+        Ok(value)
     }
 
     fn contains<Schema: ContractSchema>(
@@ -65,14 +201,11 @@ impl<DB: TrieDB> ContractStateAdapter for GeneralContractStateAdapter<DB> {
     ) -> ProtocolResult<bool> {
         let encoded_key = key.encode()?;
 
-        if self.cache_map.contains_key(&encoded_key) {
-            return Ok(true);
-        };
-
-        if self.stash_map.contains_key(&encoded_key) {
-            return Ok(true);
+        if let Some(value) = self.get_cache(&encoded_key) {
+            return Ok(value.is_some());
         };
 
+        self.read_set.borrow_mut().insert(encoded_key.clone());
         self.trie.contains(&encoded_key)
     }
 
@@ -81,26 +214,100 @@ impl<DB: TrieDB> ContractStateAdapter for GeneralContractStateAdapter<DB> {
         key: <Schema as ContractSchema>::Key,
         value: <Schema as ContractSchema>::Value,
     ) -> ProtocolResult<()> {
-        self.cache_map.insert(key.encode()?, value.encode()?);
+        let encoded_key = key.encode()?;
+        let encoded_value = ContractSer::encode(&value)?;
+        let size = encoded_key.len() + encoded_value.len();
+
+        if let Some((address, tracer)) = &self.tracer {
+            let old_value = self.get_bytes(&encoded_key)?;
+            tracer.borrow_mut().push(TraceEvent::StateWrite {
+                address: address.clone(),
+                key: encoded_key.clone(),
+                old_value,
+                new_value: encoded_value,
+            });
+        }
+
+        self.cache_map
+            .insert(encoded_key, Some(Box::new(value)), size);
+        Ok(())
+    }
+
+    fn remove_cache<Schema: ContractSchema>(
+        &mut self,
+        key: &<Schema as ContractSchema>::Key,
+    ) -> ProtocolResult<()> {
+        let encoded_key = key.encode()?;
+
+        // A removal is traced as an empty value.
+        if let Some((address, tracer)) = &self.tracer {
+            let old_value = self.get_bytes(&encoded_key)?;
+            tracer.borrow_mut().push(TraceEvent::StateWrite {
+                address: address.clone(),
+                key: encoded_key.clone(),
+                old_value,
+                new_value: Bytes::new(),
+            });
+        }
+
+        let size = encoded_key.len();
+        self.cache_map.insert(encoded_key, None, size);
+        Ok(())
+    }
+
+    fn push_checkpoint(&mut self) {
+        let cache = mem::replace(&mut self.cache_map, SizedCache::default());
+        self.checkpoints.push(cache);
+    }
+
+    fn commit_checkpoint(&mut self) -> ProtocolResult<()> {
+        let mut parent = self
+            .checkpoints
+            .pop()
+            .ok_or(GeneralContractStateAdapterError::NoCheckpoint)?;
+        parent.extend(mem::replace(&mut self.cache_map, SizedCache::default()));
+        self.cache_map = parent;
+        Ok(())
+    }
+
+    fn rollback_checkpoint(&mut self) -> ProtocolResult<()> {
+        self.cache_map = self
+            .checkpoints
+            .pop()
+            .ok_or(GeneralContractStateAdapterError::NoCheckpoint)?;
         Ok(())
     }
 
     fn revert_cache(&mut self) -> ProtocolResult<()> {
-        self.cache_map.clear();
+        self.cache_map = SizedCache::default();
+        self.checkpoints.clear();
         Ok(())
     }
 
     fn stash(&mut self) -> ProtocolResult<()> {
-        for (k, v) in self.cache_map.drain() {
-            self.stash_map.insert(k, v);
+        for cache in self.checkpoints.drain(..) {
+            self.stash_map.extend(cache.map);
         }
+        let cache = mem::replace(&mut self.cache_map, SizedCache::default());
+        self.stash_map.extend(cache.map);
 
         Ok(())
     }
 
+    fn revert_stash(&mut self) -> ProtocolResult<()> {
+        self.cache_map = SizedCache::default();
+        self.checkpoints.clear();
+        self.stash_map.clear();
+        Ok(())
+    }
+
     fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
+        // The values are only encoded here.
         for (key, value) in self.stash_map.drain() {
-            self.trie.insert(key, value)?;
+            match value {
+                Some(value) => self.trie.insert(key, value.encode()?)?,
+                None => self.trie.remove(&key)?,
+            }
         }
 
         let root = self.trie.commit()?;
@@ -110,7 +317,12 @@ impl<DB: TrieDB> ContractStateAdapter for GeneralContractStateAdapter<DB> {
 
 #[derive(Debug, Display, From)]
 pub enum GeneralContractStateAdapterError {
-    NotFound { key: String },
+    NotFound {
+        key: String,
+    },
+
+    #[display(fmt = "no checkpoint to commit or rollback")]
+    NoCheckpoint,
 }
 
 impl Error for GeneralContractStateAdapterError {}
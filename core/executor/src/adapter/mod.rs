@@ -1,5 +1,6 @@
 mod contract;
 
 pub use contract::{
-    GeneralContractStateAdapter, GeneralContractStateAdapterError, RcGeneralContractStateAdapter,
+    CacheMap, GeneralContractStateAdapter, GeneralContractStateAdapterError,
+    RcGeneralContractStateAdapter,
 };
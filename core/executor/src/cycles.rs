@@ -1,40 +1,85 @@
-use std::collections::HashMap;
 use std::error::Error;
 
 use derive_more::{Display, From};
-use lazy_static::lazy_static;
 
-use protocol::types::Fee;
+use protocol::traits::executor::{RcInvokeContext, TraceEvent};
+use protocol::types::{CyclesSchedule, Fee};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-const NATIVE_BASE_CYCLES: u64 = 10;
-
-lazy_static! {
-    static ref CYCLES_TABLE: HashMap<CyclesAction, u64> = {
-        let mut table = HashMap::new();
-        table.insert(CyclesAction::AccountTransfer, NATIVE_BASE_CYCLES * 21);
-        table.insert(CyclesAction::BankRegister, NATIVE_BASE_CYCLES * 210);
-        table
-    };
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CyclesAction {
+    // Charged for every transaction.
+    Base,
+    // The size of the encoded transaction.
+    PayloadBytes(usize),
+    // The size of the keys and values written to the state.
+    StateBytes(usize),
     AccountTransfer,
+    AccountApprove,
     BankRegister,
+    BankMint,
+    BankBurn,
+    // Bonding, unbonding and withdrawing are charged like a transfer.
+    StakingBond,
+    StakingUnbond,
+    StakingWithdraw,
+    // Proposing and voting are charged like a contract call.
+    GovernancePropose,
+    GovernanceVote,
+    ContractDeploy,
+    ContractCall,
+    // A contract calls a host function touching the state, the events or
+    // other contracts, with the bytes passed by it.
+    HostCall,
+    HostBytes(usize),
+}
+
+impl CyclesAction {
+    pub fn cycles(&self, schedule: &CyclesSchedule) -> u64 {
+        match self {
+            CyclesAction::Base => schedule.base,
+            CyclesAction::PayloadBytes(len) => schedule.payload_byte.saturating_mul(*len as u64),
+            CyclesAction::StateBytes(len) => schedule.state_byte.saturating_mul(*len as u64),
+            CyclesAction::AccountTransfer => schedule.transfer,
+            CyclesAction::AccountApprove => schedule.approve,
+            CyclesAction::BankRegister => schedule.register,
+            CyclesAction::BankMint => schedule.mint,
+            CyclesAction::BankBurn => schedule.burn,
+            CyclesAction::StakingBond
+            | CyclesAction::StakingUnbond
+            | CyclesAction::StakingWithdraw => schedule.transfer,
+            CyclesAction::GovernancePropose | CyclesAction::GovernanceVote => schedule.call,
+            CyclesAction::ContractDeploy => schedule.deploy,
+            CyclesAction::ContractCall => schedule.call,
+            CyclesAction::HostCall => schedule.host_call,
+            CyclesAction::HostBytes(len) => schedule.host_byte.saturating_mul(*len as u64),
+        }
+    }
+}
+
+/// Charge the cycles of an action by the schedule of the invoke context.
+pub fn consume_cycles(ictx: &RcInvokeContext, action: CyclesAction) -> ProtocolResult<()> {
+    let mut ictx = ictx.borrow_mut();
+    let cycles = action.cycles(&ictx.cycles_schedule);
+    let limit = ictx.cycles_limit.clone();
+    if let Some(tracer) = &ictx.tracer {
+        tracer.borrow_mut().push(TraceEvent::Cycles {
+            action: format!("{:?}", action),
+            cycles,
+        });
+    }
+
+    // `fee.cycle` counts cycles, the price is only applied when the fee is
+    // paid.
+    charge_cycles(cycles, &mut ictx.cycles_used, &limit)
 }
 
-pub fn consume_cycles(
-    action: CyclesAction,
-    cycles_price: u64,
-    fee: &mut Fee,
-    limit: &Fee,
-) -> ProtocolResult<()> {
-    let cycles_used = fee.cycle
-        + CYCLES_TABLE
-            .get(&action)
-            .unwrap_or_else(|| panic!("cycles action {:?} uninitialized", action));
-    let cycles_used = cycles_used * cycles_price;
+// Add `cycles` to the used cycles, fail if the limit is exceeded.
+pub fn charge_cycles(cycles: u64, fee: &mut Fee, limit: &Fee) -> ProtocolResult<()> {
+    let cycles_used = fee
+        .cycle
+        .checked_add(cycles)
+        .ok_or(CyclesError::OutOfCycles)?;
 
     if cycles_used > limit.cycle {
         return Err(CyclesError::OutOfCycles.into());
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 
 use bytes::Bytes;
@@ -6,8 +6,9 @@ use derive_more::{Display, From};
 
 use protocol::traits::executor::{ContractSchema, ContractSer};
 use protocol::types::{
-    Account, Address, ApprovedInfo, Asset, AssetID, AssetInfo, Balance, ContractAccount,
-    ContractAddress, Hash, MerkleRoot, UserAccount,
+    Account, Address, ApprovedInfo, Asset, AssetID, AssetInfo, Balance, ChainParams,
+    ContractAccount, ContractAddress, CyclesSchedule, Hash, MerkleRoot, UserAccount, UserAddress,
+    Validator,
 };
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
@@ -68,13 +69,14 @@ impl rlp::Encodable for FixedAsset {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
         let inner = &self.inner;
 
-        s.begin_list(6);
+        s.begin_list(7);
         s.append(&inner.id.as_bytes().to_vec());
         s.append(&inner.manage_contract.as_bytes().to_vec());
         s.append(&inner.name.as_bytes());
         s.append(&inner.storage_root.as_bytes().to_vec());
         s.append(&inner.supply.to_bytes_be());
         s.append(&inner.symbol.as_bytes());
+        s.append(&inner.decimals);
     }
 }
 
@@ -82,11 +84,11 @@ impl rlp::Encodable for FixedAsset {
 impl rlp::Decodable for FixedAsset {
     /// Decode a value from RLP bytes
     fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        if !r.is_list() && r.size() != 6 {
+        if !r.is_list() && r.size() != 7 {
             return Err(rlp::DecoderError::RlpInvalidLength);
         }
 
-        let mut values = Vec::with_capacity(6);
+        let mut values = Vec::with_capacity(7);
 
         for val in r {
             let data = val.data()?;
@@ -104,6 +106,7 @@ impl rlp::Decodable for FixedAsset {
         let supply = Balance::from_bytes_be(values[4]);
         let symbol = String::from_utf8(values[5].to_vec())
             .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let decimals = r.val_at(6)?;
 
         let asset = Asset {
             id,
@@ -112,6 +115,7 @@ impl rlp::Decodable for FixedAsset {
             storage_root,
             supply,
             symbol,
+            decimals,
         };
 
         Ok(FixedAsset { inner: asset })
@@ -402,9 +406,791 @@ impl rlp::Decodable for FixedContractAsset {
     }
 }
 
+/// The used nonces of an account, the key is `Hash(address + nonce)` and the
+/// value is the timeout of the transaction.
+pub struct FixedNonceSchema;
+impl ContractSchema for FixedNonceSchema {
+    type Key = FixedNonceKey;
+    type Value = FixedTimeout;
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedNonceKey {
+    inner: Hash,
+}
+
+impl FixedNonceKey {
+    pub fn new(address: &Address, nonce: &Hash) -> Self {
+        let inner = Hash::digest(Bytes::from([address.as_bytes(), nonce.as_bytes()].concat()));
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedNonceKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(self.inner.as_bytes())
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        let inner = Hash::from_bytes(bytes)?;
+        Ok(FixedNonceKey { inner })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FixedTimeout {
+    pub inner: u64,
+}
+
+impl FixedTimeout {
+    pub fn new(inner: u64) -> Self {
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedTimeout {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(self.inner.to_be_bytes().to_vec()))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.len() != 8 {
+            return Err(FixedTypesError::Decoder(rlp::DecoderError::RlpInvalidLength).into());
+        }
+
+        Ok(FixedTimeout {
+            inner: bytes_to_u64(bytes.as_ref()),
+        })
+    }
+}
+
+/// The recorded nonces expiring at an epoch, they are pruned by the first
+/// epoch after their timeout.
+pub struct FixedNonceIndexSchema;
+impl ContractSchema for FixedNonceIndexSchema {
+    type Key = FixedNonceIndexKey;
+    type Value = FixedNonceKeys;
+}
+
+const NONCE_INDEX_KEY: &[u8] = b"nonce_index";
+
+/// The key is `Hash("nonce_index" + timeout)`, which never collides with the
+/// other keys of the account contract.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedNonceIndexKey {
+    inner: Hash,
+}
+
+impl FixedNonceIndexKey {
+    pub fn new(timeout: u64) -> Self {
+        let inner = Hash::digest(Bytes::from(
+            [NONCE_INDEX_KEY, &timeout.to_be_bytes()].concat(),
+        ));
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedNonceIndexKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(self.inner.as_bytes())
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        let inner = Hash::from_bytes(bytes)?;
+        Ok(FixedNonceIndexKey { inner })
+    }
+}
+
+/// The keys are sorted, so the value does not depend on the order the
+/// transactions are executed in.
+#[derive(Clone, Debug, Default)]
+pub struct FixedNonceKeys {
+    pub inner: BTreeSet<FixedNonceKey>,
+}
+
+impl ContractSer for FixedNonceKeys {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedNonceKeys {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(self.inner.len());
+        for key in self.inner.iter() {
+            s.append(&key.inner.as_bytes().to_vec());
+        }
+    }
+}
+
+impl rlp::Decodable for FixedNonceKeys {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let mut inner = BTreeSet::new();
+        for item in r.iter() {
+            let key = Hash::from_bytes(Bytes::from(item.data()?))
+                .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+            inner.insert(FixedNonceKey { inner: key });
+        }
+
+        Ok(FixedNonceKeys { inner })
+    }
+}
+
+const CODE_KEY_PREFIX: u8 = 0;
+const STORAGE_KEY_PREFIX: u8 = 1;
+
+/// The code and the storage of a wasm contract share the same contract state,
+/// the keys are prefixed to keep them apart.
+pub struct FixedCodeSchema;
+impl ContractSchema for FixedCodeSchema {
+    type Key = FixedCodeKey;
+    type Value = FixedBytes;
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedCodeKey;
+
+impl ContractSer for FixedCodeKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from([CODE_KEY_PREFIX].to_vec()))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.as_ref() != [CODE_KEY_PREFIX] {
+            return Err(FixedTypesError::InvalidKeyPrefix.into());
+        }
+
+        Ok(FixedCodeKey)
+    }
+}
+
+pub struct FixedStorageSchema;
+impl ContractSchema for FixedStorageSchema {
+    type Key = FixedStorageKey;
+    type Value = FixedBytes;
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedStorageKey {
+    inner: Bytes,
+}
+
+impl FixedStorageKey {
+    pub fn new(inner: Bytes) -> Self {
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedStorageKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(
+            [
+                Bytes::from([STORAGE_KEY_PREFIX].to_vec()),
+                self.inner.clone(),
+            ]
+            .concat(),
+        ))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.first() != Some(&STORAGE_KEY_PREFIX) {
+            return Err(FixedTypesError::InvalidKeyPrefix.into());
+        }
+
+        Ok(FixedStorageKey {
+            inner: bytes.slice_from(1),
+        })
+    }
+}
+
+/// the `FixedBytes` is a wrapper type of raw bytes, it is stored as it is.
+#[derive(Clone, Debug)]
+pub struct FixedBytes {
+    pub inner: Bytes,
+}
+
+impl FixedBytes {
+    pub fn new(inner: Bytes) -> Self {
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedBytes {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(self.inner.clone())
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(FixedBytes { inner: bytes })
+    }
+}
+
+/// The cycles schedules of the chain, each one takes effect from its epoch
+/// id. There is a single key in the state of the system contract.
+pub struct FixedCyclesSchedulesSchema;
+impl ContractSchema for FixedCyclesSchedulesSchema {
+    type Key = FixedCyclesSchedulesKey;
+    type Value = FixedCyclesSchedules;
+}
+
+const CYCLES_SCHEDULES_KEY: &[u8] = b"cycles_schedules";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedCyclesSchedulesKey;
+
+impl ContractSer for FixedCyclesSchedulesKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(CYCLES_SCHEDULES_KEY))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.as_ref() != CYCLES_SCHEDULES_KEY {
+            return Err(FixedTypesError::InvalidKeyPrefix.into());
+        }
+        Ok(FixedCyclesSchedulesKey)
+    }
+}
+
+/// The schedules are sorted by their epoch ids.
+#[derive(Clone, Debug)]
+pub struct FixedCyclesSchedules {
+    pub inner: Vec<(u64, CyclesSchedule)>,
+}
+
+impl FixedCyclesSchedules {
+    pub fn new(inner: Vec<(u64, CyclesSchedule)>) -> Self {
+        Self { inner }
+    }
+
+    // The last schedule which takes effect at or before `epoch_id`.
+    pub fn active(&self, epoch_id: u64) -> Option<&CyclesSchedule> {
+        self.inner
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= epoch_id)
+            .map(|(_, schedule)| schedule)
+    }
+}
+
+impl ContractSer for FixedCyclesSchedules {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedCyclesSchedules {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(self.inner.len());
+        for (epoch_id, schedule) in self.inner.iter() {
+            s.begin_list(13);
+            s.append(epoch_id);
+            s.append(&schedule.base);
+            s.append(&schedule.payload_byte);
+            s.append(&schedule.state_byte);
+            s.append(&schedule.transfer);
+            s.append(&schedule.approve);
+            s.append(&schedule.register);
+            s.append(&schedule.mint);
+            s.append(&schedule.burn);
+            s.append(&schedule.deploy);
+            s.append(&schedule.call);
+            s.append(&schedule.host_call);
+            s.append(&schedule.host_byte);
+        }
+    }
+}
+
+impl rlp::Decodable for FixedCyclesSchedules {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let mut inner = vec![];
+        for item in r.iter() {
+            let schedule = CyclesSchedule {
+                base:         item.val_at(1)?,
+                payload_byte: item.val_at(2)?,
+                state_byte:   item.val_at(3)?,
+                transfer:     item.val_at(4)?,
+                approve:      item.val_at(5)?,
+                register:     item.val_at(6)?,
+                mint:         item.val_at(7)?,
+                burn:         item.val_at(8)?,
+                deploy:       item.val_at(9)?,
+                call:         item.val_at(10)?,
+                host_call:    item.val_at(11)?,
+                host_byte:    item.val_at(12)?,
+            };
+            inner.push((item.val_at(0)?, schedule));
+        }
+
+        Ok(FixedCyclesSchedules { inner })
+    }
+}
+
+/// The staking parameters of the genesis, they never change afterwards.
+pub struct FixedStakingSchema;
+impl ContractSchema for FixedStakingSchema {
+    type Key = FixedStakingKey;
+    type Value = FixedStaking;
+}
+
+const STAKING_KEY: &[u8] = b"staking";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedStakingKey;
+
+impl ContractSer for FixedStakingKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(STAKING_KEY))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.as_ref() != STAKING_KEY {
+            return Err(FixedTypesError::InvalidKeyPrefix.into());
+        }
+        Ok(FixedStakingKey)
+    }
+}
+
+/// Only the system token `asset_id` can be bonded.
+#[derive(Clone, Debug)]
+pub struct FixedStaking {
+    pub asset_id:         AssetID,
+    pub min_stake:        Balance,
+    pub max_validators:   u64,
+    pub unbonding_epochs: u64,
+}
+
+impl ContractSer for FixedStaking {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedStaking {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(4);
+        s.append(&self.asset_id.as_bytes().to_vec());
+        s.append(&self.min_stake.to_bytes_be());
+        s.append(&self.max_validators);
+        s.append(&self.unbonding_epochs);
+    }
+}
+
+impl rlp::Decodable for FixedStaking {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 4 {
+            return Err(rlp::DecoderError::RlpInvalidLength);
+        }
+
+        let asset_id = Hash::from_bytes(Bytes::from(r.at(0)?.data()?))
+            .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let min_stake = Balance::from_bytes_be(r.at(1)?.data()?);
+
+        Ok(FixedStaking {
+            asset_id,
+            min_stake,
+            max_validators: r.val_at(2)?,
+            unbonding_epochs: r.val_at(3)?,
+        })
+    }
+}
+
+/// The accounts with a bonded stake, the set only changes when an account
+/// starts or stops bonding.
+pub struct FixedStakersSchema;
+impl ContractSchema for FixedStakersSchema {
+    type Key = FixedStakersKey;
+    type Value = FixedStakers;
+}
+
+const STAKERS_KEY: &[u8] = b"stakers";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedStakersKey;
+
+impl ContractSer for FixedStakersKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(STAKERS_KEY))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.as_ref() != STAKERS_KEY {
+            return Err(FixedTypesError::InvalidKeyPrefix.into());
+        }
+        Ok(FixedStakersKey)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FixedStakers {
+    pub inner: BTreeSet<UserAddress>,
+}
+
+impl ContractSer for FixedStakers {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedStakers {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(self.inner.len());
+        for address in self.inner.iter() {
+            s.append(&address.as_bytes().to_vec());
+        }
+    }
+}
+
+impl rlp::Decodable for FixedStakers {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let mut inner = BTreeSet::new();
+        for item in r.iter() {
+            let address = UserAddress::from_bytes(Bytes::from(item.data()?))
+                .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+            inner.insert(address);
+        }
+
+        Ok(FixedStakers { inner })
+    }
+}
+
+/// The stake of an account, the key is `Hash("stake" + address)`.
+pub struct FixedStakeSchema;
+impl ContractSchema for FixedStakeSchema {
+    type Key = FixedStakeKey;
+    type Value = FixedStake;
+}
+
+const STAKE_KEY: &[u8] = b"stake";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedStakeKey {
+    inner: Hash,
+}
+
+impl FixedStakeKey {
+    pub fn new(address: &UserAddress) -> Self {
+        let inner = Hash::digest(Bytes::from(
+            [STAKE_KEY, address.as_bytes().as_ref()].concat(),
+        ));
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedStakeKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(self.inner.as_bytes())
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        let inner = Hash::from_bytes(bytes)?;
+        Ok(FixedStakeKey { inner })
+    }
+}
+
+/// The unbonded amounts are withdrawable from their release epochs, they are
+/// sorted by the release epoch.
+#[derive(Clone, Debug, Default)]
+pub struct FixedStake {
+    pub bonded:    Balance,
+    pub unbonding: Vec<(u64, Balance)>,
+}
+
+impl ContractSer for FixedStake {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedStake {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        s.append(&self.bonded.to_bytes_be());
+
+        s.begin_list(self.unbonding.len());
+        for (release_epoch_id, amount) in self.unbonding.iter() {
+            s.begin_list(2);
+            s.append(release_epoch_id);
+            s.append(&amount.to_bytes_be());
+        }
+    }
+}
+
+impl rlp::Decodable for FixedStake {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 2 {
+            return Err(rlp::DecoderError::RlpInvalidLength);
+        }
+
+        let bonded = Balance::from_bytes_be(r.at(0)?.data()?);
+        let mut unbonding = vec![];
+        for item in r.at(1)?.iter() {
+            let amount = Balance::from_bytes_be(item.at(1)?.data()?);
+            unbonding.push((item.val_at(0)?, amount));
+        }
+
+        Ok(FixedStake { bonded, unbonding })
+    }
+}
+
+/// The wrapper of the chain parameters, it is the argument of `propose` and
+/// the return value of `get_params` as well.
+#[derive(Clone, Debug)]
+pub struct FixedChainParams {
+    pub inner: ChainParams,
+}
+
+impl FixedChainParams {
+    pub fn new(inner: ChainParams) -> Self {
+        Self { inner }
+    }
+}
+
+impl ContractSer for FixedChainParams {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedChainParams {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let inner = &self.inner;
+
+        s.begin_list(9);
+        s.append(&inner.cycles_limit);
+        s.append(&inner.cycles_price);
+        s.append(&inner.interval);
+        s.append(&inner.propose_numerator);
+        s.append(&inner.propose_denominator);
+        s.append(&inner.prevote_numerator);
+        s.append(&inner.prevote_denominator);
+        s.append(&inner.precommit_numerator);
+        s.append(&inner.precommit_denominator);
+    }
+}
+
+impl rlp::Decodable for FixedChainParams {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 9 {
+            return Err(rlp::DecoderError::RlpInvalidLength);
+        }
+
+        let inner = ChainParams {
+            cycles_limit:          r.val_at(0)?,
+            cycles_price:          r.val_at(1)?,
+            interval:              r.val_at(2)?,
+            propose_numerator:     r.val_at(3)?,
+            propose_denominator:   r.val_at(4)?,
+            prevote_numerator:     r.val_at(5)?,
+            prevote_denominator:   r.val_at(6)?,
+            precommit_numerator:   r.val_at(7)?,
+            precommit_denominator: r.val_at(8)?,
+        };
+        Ok(FixedChainParams { inner })
+    }
+}
+
+/// The parameters of the chain and the proposals to change them. There is a
+/// single key in the state of the governance contract.
+pub struct FixedGovernanceSchema;
+impl ContractSchema for FixedGovernanceSchema {
+    type Key = FixedGovernanceKey;
+    type Value = FixedGovernance;
+}
+
+const GOVERNANCE_KEY: &[u8] = b"governance";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedGovernanceKey;
+
+impl ContractSer for FixedGovernanceKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(GOVERNANCE_KEY))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        if bytes.as_ref() != GOVERNANCE_KEY {
+            return Err(FixedTypesError::InvalidKeyPrefix.into());
+        }
+        Ok(FixedGovernanceKey)
+    }
+}
+
+/// `params` take effect from `activate_epoch_id` once the proposal is
+/// approved by the validators.
+#[derive(Clone, Debug)]
+pub struct FixedProposal {
+    pub proposer:          UserAddress,
+    pub activate_epoch_id: u64,
+    pub params:            ChainParams,
+    pub votes:             BTreeSet<UserAddress>,
+}
+
+impl ContractSer for FixedProposal {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedProposal {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(4);
+        s.append(&self.proposer.as_bytes().to_vec());
+        s.append(&self.activate_epoch_id);
+        s.append(&FixedChainParams::new(self.params.clone()));
+
+        s.begin_list(self.votes.len());
+        for address in self.votes.iter() {
+            s.append(&address.as_bytes().to_vec());
+        }
+    }
+}
+
+impl rlp::Decodable for FixedProposal {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 4 {
+            return Err(rlp::DecoderError::RlpInvalidLength);
+        }
+
+        let proposer = UserAddress::from_bytes(Bytes::from(r.at(0)?.data()?))
+            .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let activate_epoch_id = r.val_at(1)?;
+        let params = r.val_at::<FixedChainParams>(2)?.inner;
+
+        let mut votes = BTreeSet::new();
+        for item in r.at(3)?.iter() {
+            let address = UserAddress::from_bytes(Bytes::from(item.data()?))
+                .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+            votes.insert(address);
+        }
+
+        Ok(FixedProposal {
+            proposer,
+            activate_epoch_id,
+            params,
+            votes,
+        })
+    }
+}
+
+/// `params` are the parameters taking effect. Only the `validators` can
+/// propose and vote, they follow the validators of the consensus.
+#[derive(Clone, Debug)]
+pub struct FixedGovernance {
+    pub params:           ChainParams,
+    pub validators:       Vec<Validator>,
+    pub next_proposal_id: u64,
+    // The proposals waiting for votes.
+    pub proposals: BTreeMap<u64, FixedProposal>,
+    // The proposals waiting for their activate epoch, in the order they are
+    // approved.
+    pub approved: Vec<FixedProposal>,
+}
+
+impl ContractSer for FixedGovernance {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedTypesError::from)?)
+    }
+}
+
+impl rlp::Encodable for FixedGovernance {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5);
+        s.append(&FixedChainParams::new(self.params.clone()));
+
+        s.begin_list(self.validators.len());
+        for validator in self.validators.iter() {
+            s.begin_list(3);
+            s.append(&validator.address.as_bytes().to_vec());
+            s.append(&validator.propose_weight);
+            s.append(&validator.vote_weight);
+        }
+
+        s.append(&self.next_proposal_id);
+
+        s.begin_list(self.proposals.len());
+        for (id, proposal) in self.proposals.iter() {
+            s.begin_list(2);
+            s.append(id);
+            s.append(proposal);
+        }
+
+        s.append_list(&self.approved);
+    }
+}
+
+impl rlp::Decodable for FixedGovernance {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 5 {
+            return Err(rlp::DecoderError::RlpInvalidLength);
+        }
+
+        let params = r.val_at::<FixedChainParams>(0)?.inner;
+
+        let mut validators = vec![];
+        for item in r.at(1)?.iter() {
+            validators.push(Validator {
+                address:        UserAddress::from_bytes(Bytes::from(item.at(0)?.data()?))
+                    .map_err(|_| rlp::DecoderError::RlpInvalidLength)?,
+                propose_weight: item.val_at(1)?,
+                vote_weight:    item.val_at(2)?,
+            });
+        }
+
+        let next_proposal_id = r.val_at(2)?;
+
+        let mut proposals = BTreeMap::new();
+        for item in r.at(3)?.iter() {
+            proposals.insert(item.val_at(0)?, item.val_at(1)?);
+        }
+
+        Ok(FixedGovernance {
+            params,
+            validators,
+            next_proposal_id,
+            proposals,
+            approved: r.list_at(4)?,
+        })
+    }
+}
+
 #[derive(Debug, Display, From)]
 pub enum FixedTypesError {
     Decoder(rlp::DecoderError),
+
+    #[display(fmt = "invalid key prefix")]
+    InvalidKeyPrefix,
 }
 
 impl Error for FixedTypesError {}
@@ -1,14 +1,24 @@
+#![feature(test)]
+
 mod adapter;
 mod cycles;
 mod fixed_types;
 mod native_contract;
+mod parallel;
+mod proof;
+pub mod snapshot;
 #[cfg(test)]
 mod tests;
 pub mod trie;
+mod wasm;
+
+pub use adapter::{GeneralContractStateAdapter, RcGeneralContractStateAdapter};
+pub use proof::verify_balance_proof;
 
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
+use std::mem;
 use std::num::ParseIntError;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -16,52 +26,117 @@ use std::u64;
 
 use bytes::Bytes;
 use derive_more::{Display, From};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
-use protocol::traits::executor::contract::{AccountContract, BankContract, ContractStateAdapter};
+use protocol::codec::ProtocolCodecSync;
+use protocol::traits::executor::contract::{
+    AccountContract, BankContract, ContractStateAdapter, NativeContract, NativeDispatcher,
+    StakingContract,
+};
 use protocol::traits::executor::{
-    Executor, ExecutorExecResp, ExecutorFactory, InvokeContext, RcInvokeContext, TrieDB,
+    Dispatcher, Executor, ExecutorBalanceProof, ExecutorExecResp, ExecutorFactory,
+    ExecutorReadResp, ExecutorTrace, InvokeContext, RcInvokeContext, RcTracer, TraceEvent, TrieDB,
 };
 use protocol::types::{
-    Address, Balance, Bloom, ContractAddress, ContractType, Fee, Genesis, Hash, MerkleRoot,
-    Receipt, ReceiptResult, SignedTransaction, TransactionAction, UserAddress,
+    logs_bloom, Address, AssetID, Balance, CarryingAsset, ChainParams, ContractAddress,
+    ContractType, CyclesSchedule, DeployAsset, Fee, Genesis, Hash, MerkleRoot, Receipt,
+    ReceiptResult, SignedTransaction, TransactionAction, UserAddress, Validator,
 };
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-use crate::adapter::{GeneralContractStateAdapter, RcGeneralContractStateAdapter};
+use crate::adapter::CacheMap;
+use crate::cycles::{consume_cycles, CyclesAction};
+use crate::fixed_types::{
+    FixedBytes, FixedCodeKey, FixedCodeSchema, FixedCyclesSchedules, FixedCyclesSchedulesKey,
+    FixedCyclesSchedulesSchema,
+};
 use crate::native_contract::{
-    NativeAccountContract, NativeBankContract, ACCOUNT_CONTRACT_ADDRESS, BANK_CONTRACT_ADDRESS,
+    NativeAccountContract, NativeBankContract, NativeGovernanceContract, NativeStakingContract,
+    ACCOUNT_CONTRACT_ADDRESS, BANK_CONTRACT_ADDRESS, GOVERNANCE_CONTRACT_ADDRESS,
+    STAKING_CONTRACT_ADDRESS, SYSTEM_CONTRACT_ADDRESS,
 };
 use crate::trie::MPTTrie;
 
+// The max depth of nested contract calls.
+const MAX_CALL_DEPTH: usize = 64;
+
+/// Builds a registered native contract on its state, which is loaded from the
+/// state root of the executor.
+pub type NativeContractBuilder<DB> = Arc<
+    dyn Fn(
+            RcGeneralContractStateAdapter<DB>,
+        ) -> Box<dyn NativeContract<GeneralContractStateAdapter<DB>>>
+        + Send
+        + Sync,
+>;
+
+// The native contracts are shared by the executor and its registry.
+type RcNativeContract<DB> = Rc<RefCell<dyn NativeContract<GeneralContractStateAdapter<DB>>>>;
+
 pub struct TransactionExecutor<DB: TrieDB> {
     chain_id:     Hash,
     epoch_id:     u64,
     cycles_price: u64,
     coinbase:     Address,
+    // The cycles schedule taking effect at `epoch_id`.
+    cycles_schedule: CyclesSchedule,
+    // Commit the state after each transaction to get its state root.
+    intermediate_state_root: bool,
+    // Keep the fee of the last transaction instead of paying it to the
+    // coinbase, used by the parallel execution.
+    defer_fee:    bool,
+    deferred_fee: Option<(AssetID, Balance)>,
+    // The `(sender, nonce, timeout)` recorded by the executed transactions,
+    // they are indexed by the timeouts at the end of the epoch.
+    used_nonces: Vec<(Address, Hash, u64)>,
+    // Set while simulating a transaction, it is sent by this address instead
+    // of the one recovered from the public key, and its cycles are neither
+    // reserved nor paid.
+    simulated_sender: Option<UserAddress>,
 
-    trie:              MPTTrie<DB>,
-    account_contract:  NativeAccountContract<GeneralContractStateAdapter<DB>>,
-    bank_account:      NativeBankContract<GeneralContractStateAdapter<DB>>,
-    state_adapter_map: HashMap<Address, RcGeneralContractStateAdapter<DB>>,
+    db:                  Arc<DB>,
+    trie:                MPTTrie<DB>,
+    account_contract:    Rc<RefCell<NativeAccountContract<GeneralContractStateAdapter<DB>>>>,
+    bank_account:        Rc<RefCell<NativeBankContract<GeneralContractStateAdapter<DB>>>>,
+    staking_contract:    Rc<RefCell<NativeStakingContract<GeneralContractStateAdapter<DB>>>>,
+    governance_contract: Rc<RefCell<NativeGovernanceContract<GeneralContractStateAdapter<DB>>>>,
+    // Every native contract by its address, including the builtin ones above.
+    // A contract registered to the factory is built when it is first invoked,
+    // so the state of an untouched contract is never loaded.
+    native_contracts: RefCell<HashMap<Address, RcNativeContract<DB>>>,
+    native_builders:  BTreeMap<Address, NativeContractBuilder<DB>>,
+    // The state of wasm contracts is loaded on demand.
+    state_adapter_map: RefCell<HashMap<Address, RcGeneralContractStateAdapter<DB>>>,
+    call_depth:        Cell<usize>,
+    // The number of checkpoints opened on every contract state.
+    checkpoint_depth: Cell<usize>,
+    // Set when a transaction is traced, the contract states and the invoke
+    // context record into it.
+    tracer: Option<RcTracer>,
 }
 
-impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
+impl<DB: 'static + TrieDB> Executor for TransactionExecutor<DB> {
     fn create_genesis(&mut self, genesis: &Genesis) -> ProtocolResult<MerkleRoot> {
+        self.store_cycles_schedules(genesis)?;
+
         let ictx = InvokeContext {
-            chain_id:       self.chain_id.clone(),
-            cycles_price:   self.cycles_price,
-            epoch_id:       0,
-            coinbase:       self.coinbase.clone(),
-            caller:         self.coinbase.clone(),
-            cycles_used:    Fee {
+            chain_id:        self.chain_id.clone(),
+            cycles_price:    self.cycles_price,
+            epoch_id:        0,
+            coinbase:        self.coinbase.clone(),
+            caller:          self.coinbase.clone(),
+            cycles_used:     Fee {
                 asset_id: Hash::from_empty(),
                 cycle:    0,
             },
-            cycles_limit:   Fee {
+            cycles_limit:    Fee {
                 asset_id: Hash::from_empty(),
                 cycle:    999_999_999_999,
             },
-            carrying_asset: None,
+            carrying_asset:  None,
+            logs:            vec![],
+            cycles_schedule: self.cycles_schedule.clone(),
+            tracer:          None,
         };
         let ictx = Rc::new(RefCell::new(ictx));
 
@@ -72,29 +147,80 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
         );
         let token_contract_address = ContractAddress::from_code(code, 0, ContractType::Asset)?;
 
-        self.bank_account.register(
+        // The supply of the system token is allocated by the genesis.
+        let registered = self.bank_account.borrow_mut().register(
             Rc::clone(&ictx),
             &token_contract_address,
-            system_token.name.clone(),
-            system_token.symbol.clone(),
-            Balance::from(system_token.supply),
+            &DeployAsset {
+                name:            system_token.name.clone(),
+                symbol:          system_token.symbol.clone(),
+                supply:          Balance::from(system_token.supply),
+                decimals:        system_token.decimals,
+                manage_contract: None,
+            },
+        )?;
+
+        // The system token is bonded to become a validator.
+        self.staking_contract.borrow_mut().init(
+            registered.id.clone(),
+            Balance::from(genesis.staking.min_stake),
+            genesis.staking.max_validators,
+            genesis.staking.unbonding_epochs,
         )?;
 
+        // The parameters of the genesis are changed by the validators.
+        self.governance_contract
+            .borrow_mut()
+            .init(genesis.chain_params(), genesis.validators()?)?;
+
+        // The supply of the system token is exactly what the genesis
+        // allocates, so the total balance never exceeds the supply.
+        let mut allocated = Balance::from(0u64);
         for alloc in &genesis.state_alloc {
             let address = Address::from_hex(&alloc.address)?;
-            self.account_contract.create_account(&address)?;
+            self.account_contract
+                .borrow_mut()
+                .create_account(&address)?;
 
             for asset in &alloc.assets {
                 let asset_id = Hash::from_hex(&asset.asset_id)?;
                 let balance_byets =
                     hex::decode(asset.balance.clone()).map_err(TransactionExecutorError::from)?;
+                let balance = Balance::from_bytes_be(balance_byets.as_ref());
+                if asset_id == registered.id {
+                    allocated += balance.clone();
+                }
 
-                self.account_contract.add_balance(
-                    &asset_id,
-                    &address,
-                    Balance::from_bytes_be(balance_byets.as_ref()),
-                )?;
+                self.account_contract
+                    .borrow_mut()
+                    .add_balance(&asset_id, &address, balance)?;
+            }
+        }
+        if allocated != registered.supply {
+            return Err(TransactionExecutorError::GenesisSupplyMismatch {
+                supply: registered.supply,
+                allocated,
             }
+            .into());
+        }
+
+        // The validators of the genesis bond from their allocations, so the
+        // elected validators never start from a single bonded account.
+        let validator_stake = Balance::from(genesis.staking.validator_stake);
+        for validator in genesis.validators()? {
+            let address = Address::User(validator.address.clone());
+            let mut account_contract = self.account_contract.borrow_mut();
+            account_contract.sub_balance(&registered.id, &address, validator_stake.clone())?;
+            account_contract.add_balance(
+                &registered.id,
+                &STAKING_CONTRACT_ADDRESS,
+                validator_stake.clone(),
+            )?;
+            self.staking_contract.borrow_mut().bond(
+                Rc::clone(&ictx),
+                &validator.address,
+                validator_stake.clone(),
+            )?;
         }
 
         self.stash()?;
@@ -102,94 +228,611 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
     }
 
     fn exec(&mut self, signed_txs: Vec<SignedTransaction>) -> ProtocolResult<ExecutorExecResp> {
-        let mut receipts = Vec::with_capacity(signed_txs.len());
-
-        for signed_tx in signed_txs.into_iter() {
-            let tx_hash = signed_tx.tx_hash.clone();
-
-            let ictx = gen_invoke_ctx(
-                self.epoch_id,
-                self.cycles_price,
-                &self.chain_id,
-                &self.coinbase,
-                &signed_tx,
-            )?;
-
-            let res = match self.dispatch(Rc::clone(&ictx), signed_tx) {
-                Ok(res) => {
-                    self.stash()?;
-                    res
-                }
-                Err(e) => {
-                    self.revert()?;
-                    ReceiptResult::Fail {
-                        system: e.to_string(),
-                        user:   "".to_owned(),
+        let mut receipts = match self.exec_parallel(&signed_txs)? {
+            Some(receipts) => receipts,
+            None => {
+                let mut receipts = Vec::with_capacity(signed_txs.len());
+                for signed_tx in signed_txs.into_iter() {
+                    let mut receipt = self.exec_tx(signed_tx)?;
+                    // Committing the stash of every transaction leads to the
+                    // same final root as committing once, at the cost of
+                    // hashing the trie.
+                    if self.intermediate_state_root {
+                        receipt.state_root = self.commit()?;
                     }
+                    receipts.push(receipt);
                 }
-            };
+                receipts
+            }
+        };
 
-            self.account_contract.inc_nonce(Rc::clone(&ictx))?;
+        let epoch_logs_bloom = logs_bloom(receipts.iter().flat_map(|receipt| receipt.logs.iter()));
 
-            let receipt = Receipt {
-                state_root: Hash::from_empty(),
-                epoch_id: ictx.borrow().epoch_id,
-                cycles_used: ictx.borrow().cycles_used.clone(),
-                result: res,
-                tx_hash,
-            };
-            receipts.push(receipt);
-        }
-
-        //  Calculate the total fee and reward `coinbsae`
         let mut all_cycles_used: Vec<Fee> = vec![];
         for receipt in receipts.iter() {
             modify_all_cycles_used(&mut all_cycles_used, &receipt.cycles_used);
         }
-        for cycles_used in all_cycles_used.iter() {
-            self.account_contract.add_balance(
-                &cycles_used.asset_id,
-                &self.coinbase,
-                Balance::from(cycles_used.cycle),
-            )?;
+
+        // The nonces used in this epoch are kept until their timeouts, the
+        // ones expired by this epoch are pruned.
+        let used_nonces = mem::replace(&mut self.used_nonces, vec![]);
+        {
+            let mut account_contract = self.account_contract.borrow_mut();
+            account_contract.index_nonces(used_nonces)?;
+            account_contract.prune_nonces(self.epoch_id.saturating_sub(1))?;
         }
 
+        // The end of the epoch, the approved parameters taking effect from
+        // the next epoch are applied.
+        let validators = self.get_validators()?;
+        let chain_params = self
+            .governance_contract
+            .borrow_mut()
+            .advance(self.epoch_id + 1, validators.clone())?;
+        self.stash()?;
+
         // commit state
         let state_root = self.commit()?;
-        for receipt in receipts.iter_mut() {
-            receipt.state_root = state_root.clone();
+        if !self.intermediate_state_root {
+            for receipt in receipts.iter_mut() {
+                receipt.state_root = state_root.clone();
+            }
         }
 
         Ok(ExecutorExecResp {
             receipts,
             all_cycles_used,
-            logs_bloom: Bloom::default(),
+            logs_bloom: epoch_logs_bloom,
             state_root: state_root.clone(),
+            validators,
+            chain_params,
         })
     }
+
+    fn check_tx(&self, signed_tx: &SignedTransaction) -> ProtocolResult<()> {
+        let caller = Address::User(signed_tx.sender()?);
+        self.check_cycles_price(signed_tx)?;
+        self.check_nonce(&caller, signed_tx)
+    }
+
+    fn simulate(
+        &mut self,
+        sender: UserAddress,
+        signed_tx: SignedTransaction,
+    ) -> ProtocolResult<Receipt> {
+        self.simulated_sender = Some(sender);
+        let res = self.exec_tx(signed_tx);
+        self.simulated_sender = None;
+        self.discard()?;
+        res
+    }
+
+    fn trace(&mut self, mut signed_txs: Vec<SignedTransaction>) -> ProtocolResult<ExecutorTrace> {
+        let signed_tx = signed_txs
+            .pop()
+            .ok_or(TransactionExecutorError::NoTransaction)?;
+
+        let tracer = Rc::new(RefCell::new(vec![]));
+        let res = signed_txs
+            .into_iter()
+            .map(|signed_tx| self.exec_tx(signed_tx))
+            .collect::<ProtocolResult<Vec<_>>>()
+            .and_then(|_| {
+                self.set_tracer(Some(Rc::clone(&tracer)));
+                self.exec_tx(signed_tx)
+            });
+        self.set_tracer(None);
+        self.discard()?;
+
+        let receipt = res?;
+        let events = mem::replace(&mut *tracer.borrow_mut(), vec![]);
+        Ok(ExecutorTrace { receipt, events })
+    }
+
+    fn read(
+        &self,
+        caller: &Address,
+        cycles_limit: &Fee,
+        contract: &ContractAddress,
+        method: &str,
+        args: &[Bytes],
+    ) -> ProtocolResult<ExecutorReadResp> {
+        let ictx = InvokeContext {
+            chain_id:        self.chain_id.clone(),
+            cycles_used:     Fee {
+                asset_id: cycles_limit.asset_id.clone(),
+                cycle:    0,
+            },
+            cycles_limit:    cycles_limit.clone(),
+            cycles_price:    self.cycles_price,
+            epoch_id:        self.epoch_id,
+            caller:          caller.clone(),
+            carrying_asset:  None,
+            coinbase:        self.coinbase.clone(),
+            logs:            vec![],
+            cycles_schedule: self.cycles_schedule.clone(),
+            tracer:          None,
+        };
+        let ictx = Rc::new(RefCell::new(ictx));
+
+        let res = self.invoke(Rc::clone(&ictx), contract.clone(), method, args.to_vec());
+        // Drop everything written by the contract, so the executor can be
+        // reused by other queries.
+        self.revert()?;
+
+        let return_value = res?;
+        let cycles_used = ictx.borrow().cycles_used.clone();
+        Ok(ExecutorReadResp {
+            return_value,
+            cycles_used,
+        })
+    }
+
+    fn get_balance_proof(
+        &self,
+        address: &Address,
+        asset_id: &AssetID,
+    ) -> ProtocolResult<ExecutorBalanceProof> {
+        proof::prove_balance(&self.trie, Arc::clone(&self.db), address, asset_id)
+    }
+
+    fn get_validators(&self) -> ProtocolResult<Vec<Validator>> {
+        self.staking_contract.borrow().get_validators()
+    }
+
+    fn get_chain_params(&self) -> ProtocolResult<Option<ChainParams>> {
+        self.governance_contract.borrow().get_chain_params()
+    }
 }
 
-impl<DB: TrieDB> TransactionExecutor<DB> {
+impl<DB: 'static + TrieDB> TransactionExecutor<DB> {
+    // Execute groups of independent transfers concurrently, each group is
+    // executed by its own executor built from the current state root. A group
+    // reading or writing a key written by another group conflicts with it.
+    // The changes of the other groups are merged, and the transactions of the
+    // conflicting groups are executed again on the merged state in their
+    // order. They touch nothing of the merged groups, so the result is the
+    // same as the sequential execution. `None` is returned if the
+    // transactions have to be executed sequentially.
+    fn exec_parallel(
+        &mut self,
+        signed_txs: &[SignedTransaction],
+    ) -> ProtocolResult<Option<Vec<Receipt>>> {
+        if self.intermediate_state_root {
+            return Ok(None);
+        }
+        // Only transfers are partitioned, their accounts are known from the
+        // actions. Any other transaction, e.g. a call or a batch, may touch
+        // arbitrary accounts, so the whole epoch is executed sequentially.
+        let groups = match parallel::partition(signed_txs, &self.coinbase) {
+            Some(groups) => groups,
+            None => return Ok(None),
+        };
+        if groups.len() < 2 {
+            return Ok(None);
+        }
+
+        let chain_id = &self.chain_id;
+        let state_root = self.trie.root();
+        let db = &self.db;
+        let epoch_id = self.epoch_id;
+        let cycles_price = self.cycles_price;
+        let coinbase = &self.coinbase;
+        let native_builders = &self.native_builders;
+
+        let outputs = groups
+            .into_par_iter()
+            .map(|group| {
+                let mut executor = TransactionExecutor::new(
+                    chain_id.clone(),
+                    state_root.clone(),
+                    Arc::clone(db),
+                    epoch_id,
+                    cycles_price,
+                    coinbase.clone(),
+                    false,
+                )?;
+                executor.defer_fee = true;
+                executor.native_builders = native_builders.clone();
+
+                let mut receipts = Vec::with_capacity(group.len());
+                for index in group.into_iter() {
+                    let receipt = executor.exec_tx(signed_txs[index].clone())?;
+                    receipts.push((index, receipt, executor.deferred_fee.take()));
+                }
+
+                let mut stashes = vec![];
+                let mut reads = vec![];
+                for (address, state) in executor.state_adapter_map.borrow().iter() {
+                    let mut state = state.borrow_mut();
+                    stashes.push((address.clone(), state.take_stash()));
+                    reads.extend(
+                        state
+                            .take_reads()
+                            .into_iter()
+                            .map(|key| (address.clone(), key)),
+                    );
+                }
+                Ok(GroupOutput {
+                    receipts,
+                    stashes,
+                    reads: reads.into_iter().collect(),
+                    used_nonces: executor.used_nonces,
+                })
+            })
+            .collect::<ProtocolResult<Vec<_>>>()?;
+
+        let accesses = outputs
+            .iter()
+            .map(|output| (output.reads.clone(), output.writes()))
+            .collect::<Vec<_>>();
+        let conflicting = parallel::find_conflicts(&accesses);
+
+        let mut receipts = Vec::with_capacity(signed_txs.len());
+        let mut conflicts = vec![];
+        for (output, conflicting) in outputs.into_iter().zip(conflicting.into_iter()) {
+            if conflicting {
+                conflicts.extend(output.receipts.into_iter().map(|(index, ..)| index));
+                continue;
+            }
+
+            for (address, stash) in output.stashes.into_iter() {
+                self.get_or_create_state(&address)?
+                    .borrow_mut()
+                    .extend_stash(stash);
+            }
+            self.used_nonces.extend(output.used_nonces);
+            receipts.extend(output.receipts);
+        }
+
+        // Pay the fees to the coinbase, the balance of the coinbase is not
+        // touched by the groups.
+        for (_, _, fee) in receipts.iter() {
+            if let Some((asset_id, fee)) = fee {
+                self.account_contract.borrow_mut().add_balance(
+                    asset_id,
+                    &self.coinbase,
+                    fee.clone(),
+                )?;
+            }
+        }
+        self.stash()?;
+
+        conflicts.sort();
+        for index in conflicts.into_iter() {
+            let receipt = self.exec_tx(signed_txs[index].clone())?;
+            receipts.push((index, receipt, None));
+        }
+
+        receipts.sort_by_key(|(index, ..)| *index);
+        Ok(Some(
+            receipts
+                .into_iter()
+                .map(|(_, receipt, _)| receipt)
+                .collect(),
+        ))
+    }
+
+    // Execute a transaction and stash its changes, the receipt is not bound to
+    // a state root until the changes are committed. Every transaction gets a
+    // receipt, the one failing the price or nonce check changes nothing. The
+    // consensus rejects an epoch with such transactions anyway, see
+    // `Executor::check_tx`. The error is fatal.
+    fn exec_tx(&mut self, signed_tx: SignedTransaction) -> ProtocolResult<Receipt> {
+        let tx_hash = signed_tx.tx_hash.clone();
+        let nonce = signed_tx.raw.nonce.clone();
+        let timeout = signed_tx.raw.timeout;
+        let sender = match &self.simulated_sender {
+            Some(sender) => sender.clone(),
+            None => signed_tx.sender()?,
+        };
+
+        let ictx = self.gen_invoke_ctx(Address::User(sender), &signed_tx);
+
+        let caller = ictx.borrow().caller.clone();
+        let checked = self
+            .check_cycles_price(&signed_tx)
+            .and_then(|_| self.check_nonce(&caller, &signed_tx));
+        if let Err(e) = checked {
+            self.revert()?;
+            return Ok(fail_receipt(&ictx, tx_hash, e));
+        }
+
+        // The sender pays for the cycles limit before execution, the unused
+        // part is refunded afterwards. The balance may change within the
+        // epoch, so the consensus can't reject a sender unable to pay. The
+        // nonce is used up and the intrinsic cycles are charged instead.
+        let reserved = if self.simulated_sender.is_some() {
+            Ok(())
+        } else {
+            self.reserve_cycles(Rc::clone(&ictx))
+        };
+        if let Err(e) = reserved {
+            self.revert()?;
+            self.charge_intrinsic_cycles(Rc::clone(&ictx), &signed_tx)?;
+            self.use_nonce(Rc::clone(&ictx), caller, nonce, timeout)?;
+            self.stash()?;
+            return Ok(fail_receipt(&ictx, tx_hash, e));
+        }
+        self.stash()?;
+
+        let res = match self.dispatch(Rc::clone(&ictx), signed_tx) {
+            Ok(res) => {
+                self.stash()?;
+                res
+            }
+            Err(e) => {
+                self.revert()?;
+                // The events of a failed transaction are discarded along
+                // with its state changes.
+                ictx.borrow_mut().logs.clear();
+                ReceiptResult::Fail {
+                    system: e.to_string(),
+                    user:   "".to_owned(),
+                }
+            }
+        };
+
+        if self.simulated_sender.is_none() {
+            self.settle_cycles(Rc::clone(&ictx))?;
+        }
+        self.use_nonce(Rc::clone(&ictx), caller, nonce, timeout)?;
+        self.stash()?;
+
+        let receipt = Receipt {
+            state_root: Hash::from_empty(),
+            epoch_id: ictx.borrow().epoch_id,
+            cycles_used: ictx.borrow().cycles_used.clone(),
+            result: res,
+            logs: ictx.borrow().logs.clone(),
+            tx_hash,
+        };
+        Ok(receipt)
+    }
+
+    fn gen_invoke_ctx(&self, caller: Address, signed_tx: &SignedTransaction) -> RcInvokeContext {
+        let ctx = InvokeContext {
+            chain_id: self.chain_id.clone(),
+            cycles_used: Fee {
+                asset_id: signed_tx.raw.fee.asset_id.clone(),
+                cycle:    0,
+            },
+            cycles_limit: signed_tx.raw.fee.clone(),
+            caller,
+            coinbase: self.coinbase.clone(),
+            epoch_id: self.epoch_id,
+            cycles_price: self.cycles_price,
+            carrying_asset: carrying_asset_of(&signed_tx.raw.action),
+            logs: vec![],
+            cycles_schedule: self.cycles_schedule.clone(),
+            tracer: self.tracer.clone(),
+        };
+        Rc::new(RefCell::new(ctx))
+    }
+
     fn dispatch(
         &mut self,
         ictx: RcInvokeContext,
         signed_tx: SignedTransaction,
     ) -> ProtocolResult<ReceiptResult> {
-        let action = &signed_tx.raw.action;
+        // The intrinsic cycles of the transaction.
+        let payload_len = signed_tx.raw.encode_sync()?.len();
+        consume_cycles(&ictx, CyclesAction::Base)?;
+        consume_cycles(&ictx, CyclesAction::PayloadBytes(payload_len))?;
+
+        let res = match &signed_tx.raw.action {
+            TransactionAction::Batch { actions } => self.handle_batch(Rc::clone(&ictx), actions)?,
+            action => self.handle_action(Rc::clone(&ictx), action)?,
+        };
 
-        let res = match action {
+        // Only the changes of the transaction are cached at this point.
+        consume_cycles(&ictx, CyclesAction::StateBytes(self.cached_bytes()))?;
+
+        Ok(res)
+    }
+
+    fn handle_action(
+        &mut self,
+        ictx: RcInvokeContext,
+        action: &TransactionAction,
+    ) -> ProtocolResult<ReceiptResult> {
+        match action {
             TransactionAction::Transfer { receiver, .. } => {
                 let to = &Address::User(receiver.clone());
-                self.handle_transfer(Rc::clone(&ictx), &to)?
+                self.handle_transfer(ictx, &to)
             }
+            TransactionAction::Approve {
+                spender,
+                asset_id,
+                max,
+            } => self.handle_approve(ictx, spender, asset_id, max),
             TransactionAction::Deploy {
                 code,
                 contract_type,
-            } => self.handle_deploy(Rc::clone(&ictx), code, contract_type)?,
-            _ => panic!("Unsupported transaction"),
+                asset,
+            } => self.handle_deploy(ictx, code, contract_type, asset),
+            TransactionAction::Call {
+                contract,
+                method,
+                args,
+                ..
+            } => self.handle_call(ictx, contract, method, args),
+            TransactionAction::Batch { .. } => Err(TransactionExecutorError::InvalidBatch.into()),
+        }
+    }
+
+    // The actions are executed in order on the changes of the previous ones.
+    // Nothing is stashed until all of them succeed, so the failure of any
+    // action reverts the whole batch.
+    fn handle_batch(
+        &mut self,
+        ictx: RcInvokeContext,
+        actions: &[TransactionAction],
+    ) -> ProtocolResult<ReceiptResult> {
+        if actions.is_empty() {
+            return Err(TransactionExecutorError::InvalidBatch.into());
+        }
+
+        let mut results = Vec::with_capacity(actions.len());
+        for (index, action) in actions.iter().enumerate() {
+            ictx.borrow_mut().carrying_asset = carrying_asset_of(action);
+
+            let res = self
+                .handle_action(Rc::clone(&ictx), action)
+                .map_err(|error| TransactionExecutorError::BatchActionFailed { index, error })?;
+            results.push(res);
+        }
+
+        Ok(ReceiptResult::Batch { results })
+    }
+
+    // The sender is charged the base fee of the epoch, which must not exceed
+    // the price the sender is willing to pay.
+    fn check_cycles_price(&self, signed_tx: &SignedTransaction) -> ProtocolResult<()> {
+        let max_cycles_price = signed_tx.raw.max_cycles_price;
+        if max_cycles_price < self.cycles_price {
+            return Err(TransactionExecutorError::CyclesPriceTooLow {
+                max_cycles_price,
+                cycles_price: self.cycles_price,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    // A nonce can only be used once by the same sender, and the transaction
+    // must be executed before its timeout.
+    fn check_nonce(&self, caller: &Address, signed_tx: &SignedTransaction) -> ProtocolResult<()> {
+        let raw = &signed_tx.raw;
+        if raw.timeout < self.epoch_id {
+            return Err(TransactionExecutorError::Timeout {
+                timeout: raw.timeout,
+            }
+            .into());
+        }
+
+        if self
+            .account_contract
+            .borrow()
+            .contains_nonce(caller, &raw.nonce)?
+        {
+            return Err(TransactionExecutorError::ReusedNonce {
+                nonce: raw.nonce.clone(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    fn reserve_cycles(&mut self, ictx: RcInvokeContext) -> ProtocolResult<()> {
+        let caller = ictx.borrow().caller.clone();
+        let limit = ictx.borrow().cycles_limit.clone();
+        let required = Balance::from(limit.cycle) * Balance::from(ictx.borrow().cycles_price);
+
+        let balance = self
+            .account_contract
+            .borrow()
+            .get_balance(&limit.asset_id, &caller)
+            .unwrap_or_else(|_| Balance::from(0u64));
+        if balance < required {
+            return Err(TransactionExecutorError::InsufficientFee {
+                asset_id: limit.asset_id,
+                required,
+                balance,
+            }
+            .into());
+        }
+
+        self.account_contract
+            .borrow_mut()
+            .sub_balance(&limit.asset_id, &caller, required)
+    }
+
+    // Refund the unused cycles to the sender and pay the used ones to the
+    // `coinbase`.
+    fn settle_cycles(&mut self, ictx: RcInvokeContext) -> ProtocolResult<()> {
+        let caller = ictx.borrow().caller.clone();
+        let price = Balance::from(ictx.borrow().cycles_price);
+        let used = ictx.borrow().cycles_used.clone();
+        let limit = ictx.borrow().cycles_limit.clone();
+
+        let refund = Balance::from(limit.cycle.saturating_sub(used.cycle)) * price.clone();
+        let fee = Balance::from(used.cycle) * price;
+
+        self.account_contract
+            .borrow_mut()
+            .add_balance(&limit.asset_id, &caller, refund)?;
+        self.pay_fee(used.asset_id, fee)
+    }
+
+    // Charge the sender unable to reserve the cycles limit for the base and
+    // payload cycles, capped by the limit. The sender pays as much of it as
+    // the balance covers.
+    fn charge_intrinsic_cycles(
+        &mut self,
+        ictx: RcInvokeContext,
+        signed_tx: &SignedTransaction,
+    ) -> ProtocolResult<()> {
+        let payload_len = signed_tx.raw.encode_sync()?.len();
+        let caller = ictx.borrow().caller.clone();
+        let price = Balance::from(ictx.borrow().cycles_price);
+        let asset_id = ictx.borrow().cycles_limit.asset_id.clone();
+
+        let cycles = {
+            let ictx = ictx.borrow();
+            let schedule = &ictx.cycles_schedule;
+            CyclesAction::Base
+                .cycles(schedule)
+                .saturating_add(CyclesAction::PayloadBytes(payload_len).cycles(schedule))
+                .min(ictx.cycles_limit.cycle)
         };
+        ictx.borrow_mut().cycles_used.cycle = cycles;
 
-        Ok(res)
+        let balance = self
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &caller)
+            .unwrap_or_else(|_| Balance::from(0u64));
+        let fee = (Balance::from(cycles) * price).min(balance);
+        if fee == Balance::from(0u64) {
+            return Ok(());
+        }
+
+        self.account_contract
+            .borrow_mut()
+            .sub_balance(&asset_id, &caller, fee.clone())?;
+        self.pay_fee(asset_id, fee)
+    }
+
+    // Pay the fee to the `coinbase`, or keep it for the parallel execution to
+    // pay in the order of transactions.
+    fn pay_fee(&mut self, asset_id: AssetID, fee: Balance) -> ProtocolResult<()> {
+        if self.defer_fee {
+            self.deferred_fee = Some((asset_id, fee));
+            return Ok(());
+        }
+        self.account_contract
+            .borrow_mut()
+            .add_balance(&asset_id, &self.coinbase, fee)
+    }
+
+    // The nonce is used by the transaction once it is charged, whether it
+    // succeeds or not.
+    fn use_nonce(
+        &mut self,
+        ictx: RcInvokeContext,
+        caller: Address,
+        nonce: Hash,
+        timeout: u64,
+    ) -> ProtocolResult<()> {
+        self.account_contract.borrow_mut().inc_nonce(ictx)?;
+        self.account_contract
+            .borrow_mut()
+            .record_nonce(&caller, &nonce, timeout)?;
+        self.used_nonces.push((caller, nonce, timeout));
+        Ok(())
     }
 
     fn handle_transfer(
@@ -197,7 +840,7 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
         ictx: RcInvokeContext,
         to: &Address,
     ) -> ProtocolResult<ReceiptResult> {
-        let from = &ictx.borrow().caller;
+        let from = ictx.borrow().caller.clone();
         let carrying_asset = ictx
             .borrow()
             .carrying_asset
@@ -206,16 +849,24 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
 
         // check asset exists
         self.bank_account
+            .borrow()
             .get_asset(Rc::clone(&ictx), &carrying_asset.asset_id)?;
 
         let before_amount = self
             .account_contract
+            .borrow()
             .get_balance(&carrying_asset.asset_id, &from)?;
 
-        self.account_contract.transfer(Rc::clone(&ictx), &to)?;
+        trace_invoke(&ictx, &ACCOUNT_CONTRACT_ADDRESS, "transfer", &[
+            to.as_bytes()
+        ]);
+        self.account_contract
+            .borrow_mut()
+            .transfer(Rc::clone(&ictx), &to)?;
 
         let after_amount = self
             .account_contract
+            .borrow()
             .get_balance(&carrying_asset.asset_id, &from)?;
 
         Ok(ReceiptResult::Transfer {
@@ -226,52 +877,316 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
         })
     }
 
+    fn handle_approve(
+        &mut self,
+        ictx: RcInvokeContext,
+        spender: &ContractAddress,
+        asset_id: &AssetID,
+        max: &Balance,
+    ) -> ProtocolResult<ReceiptResult> {
+        // check asset exists
+        self.bank_account
+            .borrow()
+            .get_asset(Rc::clone(&ictx), asset_id)?;
+
+        trace_invoke(&ictx, &ACCOUNT_CONTRACT_ADDRESS, "approve", &[
+            spender.as_bytes(),
+            asset_id.as_bytes(),
+            Bytes::from(max.to_bytes_be()),
+        ]);
+        self.account_contract.borrow_mut().approve(
+            Rc::clone(&ictx),
+            spender,
+            asset_id,
+            max.clone(),
+        )?;
+
+        Ok(ReceiptResult::Approve {
+            spender:  spender.clone(),
+            asset_id: asset_id.clone(),
+            max:      max.clone(),
+        })
+    }
+
     fn handle_deploy(
         &mut self,
         ictx: RcInvokeContext,
         code: &Bytes,
         contract_type: &ContractType,
+        asset: &Option<DeployAsset>,
     ) -> ProtocolResult<ReceiptResult> {
+        // TODO(@yejiayu): Check account balance?
+        let nonce = self
+            .account_contract
+            .borrow()
+            .get_nonce(&ictx.borrow().caller)?;
+        let address = ContractAddress::from_code(code.clone(), nonce, contract_type.clone())?;
+        consume_cycles(&ictx, CyclesAction::ContractDeploy)?;
+
         match contract_type {
             ContractType::Asset => {
-                // TODO(@yejiayu): Check account balance?
-                let nonce = self.account_contract.get_nonce(&ictx.borrow().caller)?;
-                let address = ContractAddress::from_code(code.clone(), nonce, ContractType::Asset)?;
-
-                self.bank_account.register(
-                    Rc::clone(&ictx),
-                    &address,
-                    "Muta token".to_owned(),
-                    "MTT".to_owned(),
-                    Balance::from(21_000_000_000_000_000u64),
+                let asset = asset
+                    .as_ref()
+                    .ok_or(TransactionExecutorError::MissingAssetMetadata)?;
+                trace_invoke(&ictx, &BANK_CONTRACT_ADDRESS, "register", &[
+                    address.as_bytes()
+                ]);
+                let registered =
+                    self.bank_account
+                        .borrow_mut()
+                        .register(Rc::clone(&ictx), &address, asset)?;
+
+                // The initial supply is credited to the deployer.
+                let caller = ictx.borrow().caller.clone();
+                self.account_contract.borrow_mut().add_balance(
+                    &registered.id,
+                    &caller,
+                    registered.supply,
                 )?;
+            }
+            ContractType::App | ContractType::Library => {
+                let code = wasm::prepare(code)?;
 
-                Ok(ReceiptResult::Deploy {
-                    contract:      address,
-                    contract_type: ContractType::Asset,
-                })
+                let state = self.get_or_create_state(&Address::Contract(address.clone()))?;
+                if state.borrow().contains::<FixedCodeSchema>(&FixedCodeKey)? {
+                    return Err(TransactionExecutorError::ContractExists {
+                        address: Address::Contract(address),
+                    }
+                    .into());
+                }
+                state
+                    .borrow_mut()
+                    .insert_cache::<FixedCodeSchema>(FixedCodeKey, FixedBytes::new(code))?;
+            }
+            ContractType::Native => {
+                return Err(TransactionExecutorError::UnsupportedContractType {
+                    contract_type: contract_type.clone(),
+                }
+                .into())
             }
-            _ => panic!("Unsupported transaction"),
         }
+
+        Ok(ReceiptResult::Deploy {
+            contract:      address,
+            contract_type: contract_type.clone(),
+        })
+    }
+
+    fn handle_call(
+        &mut self,
+        ictx: RcInvokeContext,
+        contract: &ContractAddress,
+        method: &str,
+        args: &[Bytes],
+    ) -> ProtocolResult<ReceiptResult> {
+        consume_cycles(&ictx, CyclesAction::ContractCall)?;
+
+        // The asset carried by the call is paid to the contract before it is invoked.
+        let carrying_asset = ictx.borrow().carrying_asset.clone();
+        if let Some(carrying_asset) = carrying_asset {
+            self.bank_account
+                .borrow()
+                .get_asset(Rc::clone(&ictx), &carrying_asset.asset_id)?;
+
+            self.account_contract
+                .borrow_mut()
+                .transfer(Rc::clone(&ictx), &Address::Contract(contract.clone()))?;
+        }
+
+        let return_value =
+            self.invoke(Rc::clone(&ictx), contract.clone(), method, args.to_vec())?;
+
+        Ok(ReceiptResult::Call {
+            contract: contract.clone(),
+            return_value,
+            logs_bloom: Box::new(logs_bloom(ictx.borrow().logs.iter())),
+        })
+    }
+
+    fn invoke_wasm(
+        &self,
+        ictx: RcInvokeContext,
+        address: ContractAddress,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        let target = Address::Contract(address.clone());
+        let state = match self.get_state(&target)? {
+            Some(state) => state,
+            None => {
+                return Err(TransactionExecutorError::ContractNotFound { address: target }.into())
+            }
+        };
+        let code = state
+            .borrow()
+            .get::<FixedCodeSchema>(&FixedCodeKey)?
+            .ok_or_else(|| TransactionExecutorError::ContractNotFound { address: target })?;
+
+        let depth = self.call_depth.get();
+        if depth >= MAX_CALL_DEPTH {
+            return Err(TransactionExecutorError::CallDepthExceeded.into());
+        }
+
+        self.call_depth.set(depth + 1);
+        let res = wasm::execute(self, ictx, &address, &code.inner, method, args, state);
+        self.call_depth.set(depth);
+        res
+    }
+
+    // Avoid creating an empty state for the contract that does not exist.
+    fn get_state(
+        &self,
+        address: &Address,
+    ) -> ProtocolResult<Option<RcGeneralContractStateAdapter<DB>>> {
+        if let Some(state) = self.state_adapter_map.borrow().get(address) {
+            return Ok(Some(Rc::clone(state)));
+        }
+
+        if !self.trie.contains(&address.as_bytes())? {
+            return Ok(None);
+        }
+
+        self.get_or_create_state(address).map(Some)
+    }
+
+    fn get_or_create_state(
+        &self,
+        address: &Address,
+    ) -> ProtocolResult<RcGeneralContractStateAdapter<DB>> {
+        if let Some(state) = self.state_adapter_map.borrow().get(address) {
+            return Ok(Rc::clone(state));
+        }
+
+        // A state loaded inside a call joins the checkpoints opened before.
+        let state = gen_contract_state(&self.trie, address, Arc::clone(&self.db))?;
+        for _ in 0..self.checkpoint_depth.get() {
+            state.borrow_mut().push_checkpoint();
+        }
+        if let Some(tracer) = &self.tracer {
+            state
+                .borrow_mut()
+                .set_tracer(Some((address.clone(), Rc::clone(tracer))));
+        }
+        self.state_adapter_map
+            .borrow_mut()
+            .insert(address.clone(), Rc::clone(&state));
+        Ok(state)
+    }
+
+    // The cycles schedules of the genesis are sorted by their epoch ids, the
+    // default schedule is used until the first one takes effect.
+    fn store_cycles_schedules(&mut self, genesis: &Genesis) -> ProtocolResult<()> {
+        let mut schedules = genesis
+            .cycles_schedules
+            .iter()
+            .map(|genesis_schedule| (genesis_schedule.epoch_id, genesis_schedule.schedule.clone()))
+            .collect::<Vec<_>>();
+        schedules.sort_by_key(|(epoch_id, _)| *epoch_id);
+
+        self.get_or_create_state(&SYSTEM_CONTRACT_ADDRESS)?
+            .borrow_mut()
+            .insert_cache::<FixedCyclesSchedulesSchema>(
+                FixedCyclesSchedulesKey,
+                FixedCyclesSchedules::new(schedules),
+            )?;
+        self.cycles_schedule = self.load_cycles_schedule(self.epoch_id)?;
+        Ok(())
+    }
+
+    fn load_cycles_schedule(&self, epoch_id: u64) -> ProtocolResult<CyclesSchedule> {
+        let schedules = match self.get_state(&SYSTEM_CONTRACT_ADDRESS)? {
+            Some(state) => state
+                .borrow()
+                .get::<FixedCyclesSchedulesSchema>(&FixedCyclesSchedulesKey)?,
+            None => None,
+        };
+
+        Ok(schedules
+            .and_then(|schedules| schedules.active(epoch_id).cloned())
+            .unwrap_or_default())
+    }
+
+    // The size of the keys and values written since the last stash.
+    fn cached_bytes(&self) -> usize {
+        self.state_adapter_map
+            .borrow()
+            .values()
+            .map(|state| state.borrow().cached_bytes())
+            .sum()
+    }
+
+    // Run `f` in a new checkpoint of every contract state, its changes and
+    // events are dropped if it fails.
+    fn with_checkpoint<T>(
+        &self,
+        ictx: &RcInvokeContext,
+        f: impl FnOnce() -> ProtocolResult<T>,
+    ) -> ProtocolResult<T> {
+        let logs_len = ictx.borrow().logs.len();
+        for (_, state) in self.state_adapter_map.borrow().iter() {
+            state.borrow_mut().push_checkpoint();
+        }
+        self.checkpoint_depth.set(self.checkpoint_depth.get() + 1);
+
+        let res = f();
+
+        // `f` may load more states, they are in the map now.
+        self.checkpoint_depth.set(self.checkpoint_depth.get() - 1);
+        for (_, state) in self.state_adapter_map.borrow().iter() {
+            match res {
+                Ok(_) => state.borrow_mut().commit_checkpoint()?,
+                Err(_) => state.borrow_mut().rollback_checkpoint()?,
+            }
+        }
+        if res.is_err() {
+            ictx.borrow_mut().logs.truncate(logs_len);
+        }
+
+        res
+    }
+
+    fn set_tracer(&mut self, tracer: Option<RcTracer>) {
+        for (address, state) in self.state_adapter_map.borrow().iter() {
+            let tracer = tracer
+                .as_ref()
+                .map(|tracer| (address.clone(), Rc::clone(tracer)));
+            state.borrow_mut().set_tracer(tracer);
+        }
+        self.tracer = tracer;
     }
 
     fn stash(&mut self) -> ProtocolResult<()> {
-        for (_, state) in self.state_adapter_map.iter() {
+        for (_, state) in self.state_adapter_map.borrow().iter() {
             state.borrow_mut().stash()?;
         }
         Ok(())
     }
 
-    fn revert(&mut self) -> ProtocolResult<()> {
-        for (_, state) in self.state_adapter_map.iter() {
+    fn revert(&self) -> ProtocolResult<()> {
+        for (_, state) in self.state_adapter_map.borrow().iter() {
             state.borrow_mut().revert_cache()?;
         }
         Ok(())
     }
 
+    // Drop all changes since the last commit.
+    fn discard(&mut self) -> ProtocolResult<()> {
+        for (_, state) in self.state_adapter_map.borrow().iter() {
+            state.borrow_mut().revert_stash()?;
+        }
+        self.used_nonces.clear();
+        Ok(())
+    }
+
     fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
-        for (address, state) in self.state_adapter_map.iter() {
-            let root = state.borrow_mut().commit()?;
+        for (address, state) in self.state_adapter_map.borrow().iter() {
+            // An untouched state keeps its root, so a contract which is only
+            // loaded does not get an empty root in the world state.
+            let mut state = state.borrow_mut();
+            if !state.has_stash() {
+                continue;
+            }
+            let root = state.commit()?;
 
             self.trie.insert(address.as_bytes(), root.as_bytes())?;
         }
@@ -280,17 +1195,167 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
     }
 }
 
-pub struct TransactionExecutorFactory;
+impl<DB: 'static + TrieDB> Dispatcher for TransactionExecutor<DB> {
+    // Every call runs in its own checkpoint, so a failed inner call only
+    // reverts its own changes.
+    fn invoke(
+        &self,
+        ictx: RcInvokeContext,
+        address: ContractAddress,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        trace_invoke(&ictx, &Address::Contract(address.clone()), method, &args);
+        self.with_checkpoint(&ictx, || {
+            self.invoke_contract(Rc::clone(&ictx), address, method, args)
+        })
+    }
+}
+
+impl<DB: 'static + TrieDB> TransactionExecutor<DB> {
+    fn invoke_contract(
+        &self,
+        ictx: RcInvokeContext,
+        address: ContractAddress,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        let target = Address::Contract(address.clone());
 
-impl<DB: 'static + TrieDB> ExecutorFactory<DB> for TransactionExecutorFactory {
-    fn from_root(
+        if let Some(contract) = self.get_native_contract(&target)? {
+            // A native contract can not be invoked again before it returns,
+            // e.g. by a contract it calls.
+            let mut contract = contract
+                .try_borrow_mut()
+                .map_err(|_| TransactionExecutorError::ReentrantCall { address: target })?;
+            let dispatcher = ContractDispatcher {
+                executor: self,
+                contract: target,
+                caller:   ictx.borrow().caller.clone(),
+            };
+            return contract.invoke(&dispatcher, ictx, method, args);
+        }
+
+        match address.contract_type() {
+            ContractType::App | ContractType::Library => {
+                self.invoke_wasm(ictx, address, method, args)
+            }
+            _ => Err(TransactionExecutorError::ContractNotFound { address: target }.into()),
+        }
+    }
+
+    // A registered native contract is built on its state of the current root
+    // when it is first invoked, the state is committed with the others.
+    fn get_native_contract(
+        &self,
+        address: &Address,
+    ) -> ProtocolResult<Option<RcNativeContract<DB>>> {
+        if let Some(contract) = self.native_contracts.borrow().get(address) {
+            return Ok(Some(Rc::clone(contract)));
+        }
+
+        let builder = match self.native_builders.get(address) {
+            Some(builder) => builder,
+            None => return Ok(None),
+        };
+        let state = self.get_or_create_state(address)?;
+        let contract: RcNativeContract<DB> = Rc::new(RefCell::new(builder(state)));
+        self.native_contracts
+            .borrow_mut()
+            .insert(address.clone(), Rc::clone(&contract));
+        Ok(Some(contract))
+    }
+}
+
+// What a native contract sees of the executor while it is invoked. It moves
+// the balances of itself and its caller only, and only the bank creates or
+// destroys assets.
+struct ContractDispatcher<'a, DB: 'static + TrieDB> {
+    executor: &'a TransactionExecutor<DB>,
+    contract: Address,
+    caller:   Address,
+}
+
+impl<'a, DB: 'static + TrieDB> ContractDispatcher<'a, DB> {
+    fn ensure_bank(&self, address: &Address) -> ProtocolResult<()> {
+        if self.contract != *BANK_CONTRACT_ADDRESS {
+            return Err(TransactionExecutorError::BalanceNotAllowed {
+                contract: self.contract.clone(),
+                address:  address.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl<'a, DB: 'static + TrieDB> Dispatcher for ContractDispatcher<'a, DB> {
+    fn invoke(
+        &self,
+        ictx: RcInvokeContext,
+        address: ContractAddress,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        self.executor.invoke(ictx, address, method, args)
+    }
+}
+
+impl<'a, DB: 'static + TrieDB> NativeDispatcher for ContractDispatcher<'a, DB> {
+    fn get_balance(&self, id: &AssetID, address: &Address) -> ProtocolResult<Balance> {
+        self.executor
+            .account_contract
+            .borrow()
+            .get_balance(id, address)
+    }
+
+    fn transfer(
+        &self,
+        id: &AssetID,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> ProtocolResult<()> {
+        if *from != self.contract && *from != self.caller {
+            return Err(TransactionExecutorError::BalanceNotAllowed {
+                contract: self.contract.clone(),
+                address:  from.clone(),
+            }
+            .into());
+        }
+
+        let mut account_contract = self.executor.account_contract.borrow_mut();
+        account_contract.sub_balance(id, from, amount.clone())?;
+        account_contract.add_balance(id, to, amount)
+    }
+
+    fn mint(&self, id: &AssetID, to: &Address, amount: Balance) -> ProtocolResult<()> {
+        self.ensure_bank(to)?;
+        self.executor
+            .account_contract
+            .borrow_mut()
+            .add_balance(id, to, amount)
+    }
+
+    fn burn(&self, id: &AssetID, from: &Address, amount: Balance) -> ProtocolResult<()> {
+        self.ensure_bank(from)?;
+        self.executor
+            .account_contract
+            .borrow_mut()
+            .sub_balance(id, from, amount)
+    }
+}
+
+impl<DB: 'static + TrieDB> TransactionExecutor<DB> {
+    pub fn new(
         chain_id: Hash,
         state_root: MerkleRoot,
         db: Arc<DB>,
         epoch_id: u64,
         cycles_price: u64,
         coinbase: Address,
-    ) -> ProtocolResult<Box<dyn Executor>> {
+        intermediate_state_root: bool,
+    ) -> ProtocolResult<Self> {
         let trie = {
             if state_root == Hash::from_empty() {
                 MPTTrie::new(Arc::clone(&db))
@@ -304,7 +1369,9 @@ impl<DB: 'static + TrieDB> ExecutorFactory<DB> for TransactionExecutorFactory {
         // gen account contract
         let account_state_adapter =
             gen_contract_state(&trie, &ACCOUNT_CONTRACT_ADDRESS, Arc::clone(&db))?;
-        let account_contract = NativeAccountContract::new(Rc::clone(&account_state_adapter));
+        let account_contract = Rc::new(RefCell::new(NativeAccountContract::new(Rc::clone(
+            &account_state_adapter,
+        ))));
         state_adapter_map.insert(
             ACCOUNT_CONTRACT_ADDRESS.clone(),
             Rc::clone(&account_state_adapter),
@@ -313,24 +1380,185 @@ impl<DB: 'static + TrieDB> ExecutorFactory<DB> for TransactionExecutorFactory {
         // gen bank contract
         let bank_state_adapter =
             gen_contract_state(&trie, &BANK_CONTRACT_ADDRESS, Arc::clone(&db))?;
-        let bank_account =
-            NativeBankContract::new(chain_id.clone(), Rc::clone(&bank_state_adapter));
+        let bank_account = Rc::new(RefCell::new(NativeBankContract::new(
+            chain_id.clone(),
+            Rc::clone(&bank_state_adapter),
+        )));
         state_adapter_map.insert(
             BANK_CONTRACT_ADDRESS.clone(),
             Rc::clone(&bank_state_adapter),
         );
 
-        Ok(Box::new(TransactionExecutor {
+        // gen staking contract
+        let staking_state_adapter =
+            gen_contract_state(&trie, &STAKING_CONTRACT_ADDRESS, Arc::clone(&db))?;
+        let staking_contract = Rc::new(RefCell::new(NativeStakingContract::new(Rc::clone(
+            &staking_state_adapter,
+        ))));
+        state_adapter_map.insert(
+            STAKING_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&staking_state_adapter),
+        );
+
+        // gen governance contract
+        let governance_state_adapter =
+            gen_contract_state(&trie, &GOVERNANCE_CONTRACT_ADDRESS, Arc::clone(&db))?;
+        let governance_contract = Rc::new(RefCell::new(NativeGovernanceContract::new(Rc::clone(
+            &governance_state_adapter,
+        ))));
+        state_adapter_map.insert(
+            GOVERNANCE_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&governance_state_adapter),
+        );
+
+        // The builtin contracts are invoked through the registry as well.
+        let mut native_contracts: HashMap<Address, RcNativeContract<DB>> = HashMap::new();
+        native_contracts.insert(
+            ACCOUNT_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&account_contract) as RcNativeContract<DB>,
+        );
+        native_contracts.insert(
+            BANK_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&bank_account) as RcNativeContract<DB>,
+        );
+        native_contracts.insert(
+            STAKING_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&staking_contract) as RcNativeContract<DB>,
+        );
+        native_contracts.insert(
+            GOVERNANCE_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&governance_contract) as RcNativeContract<DB>,
+        );
+
+        let mut executor = TransactionExecutor {
             chain_id,
             epoch_id,
             cycles_price,
             coinbase,
+            cycles_schedule: CyclesSchedule::default(),
+            intermediate_state_root,
+            defer_fee: false,
+            deferred_fee: None,
+            used_nonces: vec![],
+            simulated_sender: None,
 
+            db,
             trie,
             account_contract,
             bank_account,
-            state_adapter_map,
-        }))
+            staking_contract,
+            governance_contract,
+            native_contracts: RefCell::new(native_contracts),
+            native_builders: BTreeMap::new(),
+            state_adapter_map: RefCell::new(state_adapter_map),
+            call_depth: Cell::new(0),
+            checkpoint_depth: Cell::new(0),
+            tracer: None,
+        };
+        executor.cycles_schedule = executor.load_cycles_schedule(epoch_id)?;
+
+        Ok(executor)
+    }
+}
+
+/// The factory builds the executors with the builtin native contracts and
+/// the ones registered to it.
+pub struct TransactionExecutorFactory<DB: TrieDB> {
+    native_contracts: BTreeMap<Address, NativeContractBuilder<DB>>,
+}
+
+impl<DB: TrieDB> TransactionExecutorFactory<DB> {
+    pub fn new() -> Self {
+        TransactionExecutorFactory {
+            native_contracts: BTreeMap::new(),
+        }
+    }
+
+    /// Register a native contract at `address`, which must be a native
+    /// contract address other than the builtin ones. Every executor built by
+    /// the factory has the contract.
+    pub fn register_native_contract<F>(
+        &mut self,
+        address: Address,
+        builder: F,
+    ) -> ProtocolResult<()>
+    where
+        F: Fn(
+                RcGeneralContractStateAdapter<DB>,
+            ) -> Box<dyn NativeContract<GeneralContractStateAdapter<DB>>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let is_native = match &address {
+            Address::Contract(contract) => contract.contract_type() == ContractType::Native,
+            Address::User(_) => false,
+        };
+        let is_builtin = [
+            &*ACCOUNT_CONTRACT_ADDRESS,
+            &*BANK_CONTRACT_ADDRESS,
+            &*SYSTEM_CONTRACT_ADDRESS,
+            &*STAKING_CONTRACT_ADDRESS,
+            &*GOVERNANCE_CONTRACT_ADDRESS,
+        ]
+        .contains(&&address);
+        if !is_native || is_builtin || self.native_contracts.contains_key(&address) {
+            return Err(TransactionExecutorError::InvalidNativeContract { address }.into());
+        }
+
+        self.native_contracts.insert(address, Arc::new(builder));
+        Ok(())
+    }
+}
+
+impl<DB: TrieDB> Default for TransactionExecutorFactory<DB> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<DB: 'static + TrieDB> ExecutorFactory<DB> for TransactionExecutorFactory<DB> {
+    fn from_root(
+        &self,
+        chain_id: Hash,
+        state_root: MerkleRoot,
+        db: Arc<DB>,
+        epoch_id: u64,
+        cycles_price: u64,
+        coinbase: Address,
+        intermediate_state_root: bool,
+    ) -> ProtocolResult<Box<dyn Executor>> {
+        let mut executor = TransactionExecutor::new(
+            chain_id,
+            state_root,
+            db,
+            epoch_id,
+            cycles_price,
+            coinbase,
+            intermediate_state_root,
+        )?;
+        executor.native_builders = self.native_contracts.clone();
+        Ok(Box::new(executor))
+    }
+}
+
+// The changes of a group of transactions executed in parallel, with the
+// receipts and the deferred fees of the transactions.
+struct GroupOutput {
+    receipts:    Vec<(usize, Receipt, Option<(AssetID, Balance)>)>,
+    stashes:     Vec<(Address, CacheMap)>,
+    reads:       HashSet<(Address, Bytes)>,
+    used_nonces: Vec<(Address, Hash, u64)>,
+}
+
+impl GroupOutput {
+    fn writes(&self) -> HashSet<(Address, Bytes)> {
+        self.stashes
+            .iter()
+            .flat_map(|(address, stash)| {
+                stash.keys().map(move |key| (address.clone(), key.clone()))
+            })
+            .collect()
     }
 }
 
@@ -368,39 +1596,143 @@ fn modify_all_cycles_used(all_cycles_used: &mut Vec<Fee>, cycles_used: &Fee) {
     all_cycles_used.push(new_fee);
 }
 
-fn gen_invoke_ctx(
-    epoch_id: u64,
-    cycles_price: u64,
-    chain_id: &Hash,
-    coinbase: &Address,
-    signed_tx: &SignedTransaction,
-) -> ProtocolResult<RcInvokeContext> {
-    let carrying_asset = match &signed_tx.raw.action {
+// The asset carried by an action, the actions of a batch carry their own.
+fn carrying_asset_of(action: &TransactionAction) -> Option<CarryingAsset> {
+    match action {
         TransactionAction::Transfer { carrying_asset, .. } => Some(carrying_asset.clone()),
         TransactionAction::Call { carrying_asset, .. } => carrying_asset.clone(),
         _ => None,
-    };
+    }
+}
 
-    let ctx = InvokeContext {
-        chain_id: chain_id.clone(),
-        cycles_used: Fee {
-            asset_id: signed_tx.raw.fee.asset_id.clone(),
-            cycle:    0,
+// Record the invocation of a contract if the transaction is traced.
+// The receipt of a transaction failing before it is dispatched.
+fn fail_receipt(ictx: &RcInvokeContext, tx_hash: Hash, err: ProtocolError) -> Receipt {
+    Receipt {
+        state_root: Hash::from_empty(),
+        epoch_id: ictx.borrow().epoch_id,
+        cycles_used: ictx.borrow().cycles_used.clone(),
+        result: ReceiptResult::Fail {
+            system: err.to_string(),
+            user:   "".to_owned(),
         },
-        cycles_limit: signed_tx.raw.fee.clone(),
-        caller: Address::User(UserAddress::from_pubkey_bytes(signed_tx.pubkey.clone())?),
-        coinbase: coinbase.clone(),
-        epoch_id,
-        cycles_price,
-        carrying_asset,
-    };
-    Ok(Rc::new(RefCell::new(ctx)))
+        logs: vec![],
+        tx_hash,
+    }
+}
+
+fn trace_invoke(ictx: &RcInvokeContext, address: &Address, method: &str, args: &[Bytes]) {
+    if let Some(tracer) = &ictx.borrow().tracer {
+        tracer.borrow_mut().push(TraceEvent::Invoke {
+            address: address.clone(),
+            method:  method.to_owned(),
+            args:    args.to_vec(),
+        });
+    }
 }
 
 #[derive(Debug, Display, From)]
 pub enum TransactionExecutorError {
     FromHex(hex::FromHexError),
     ParseInt(ParseIntError),
+
+    #[display(fmt = "contract {:?} not found", address)]
+    ContractNotFound {
+        address: Address,
+    },
+
+    #[display(fmt = "contract {:?} already exists", address)]
+    ContractExists {
+        address: Address,
+    },
+
+    #[display(fmt = "unsupported contract type {:?}", contract_type)]
+    UnsupportedContractType {
+        contract_type: ContractType,
+    },
+
+    #[display(fmt = "exceed the max call depth")]
+    CallDepthExceeded,
+
+    #[display(
+        fmt = "native contract {:?} is invoked again before it returns",
+        address
+    )]
+    ReentrantCall {
+        address: Address,
+    },
+
+    #[display(fmt = "{:?} can not be registered as a native contract", address)]
+    InvalidNativeContract {
+        address: Address,
+    },
+
+    #[display(
+        fmt = "native contract {:?} can not change the balance of {:?}",
+        contract,
+        address
+    )]
+    BalanceNotAllowed {
+        contract: Address,
+        address:  Address,
+    },
+
+    #[display(fmt = "missing the metadata of the asset")]
+    MissingAssetMetadata,
+
+    #[display(fmt = "a batch must have actions and can not be nested")]
+    InvalidBatch,
+
+    #[display(fmt = "action {} of the batch failed: {}", index, error)]
+    BatchActionFailed {
+        index: usize,
+        error: ProtocolError,
+    },
+
+    #[display(
+        fmt = "the supply {} of the system token differs from the allocated {}",
+        supply,
+        allocated
+    )]
+    GenesisSupplyMismatch {
+        supply:    Balance,
+        allocated: Balance,
+    },
+
+    #[display(fmt = "no transaction to trace")]
+    NoTransaction,
+
+    #[display(fmt = "transaction timeout {}", timeout)]
+    Timeout {
+        timeout: u64,
+    },
+
+    #[display(
+        fmt = "max cycles price {} is lower than the base fee {}",
+        max_cycles_price,
+        cycles_price
+    )]
+    CyclesPriceTooLow {
+        max_cycles_price: u64,
+        cycles_price:     u64,
+    },
+
+    #[display(fmt = "nonce {:?} has been used", nonce)]
+    ReusedNonce {
+        nonce: Hash,
+    },
+
+    #[display(
+        fmt = "insufficient fee of asset {:?}, required {}, balance {}",
+        asset_id,
+        required,
+        balance
+    )]
+    InsufficientFee {
+        asset_id: AssetID,
+        required: Balance,
+        balance:  Balance,
+    },
 }
 
 impl Error for TransactionExecutorError {}
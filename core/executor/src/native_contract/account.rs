@@ -3,17 +3,25 @@ use std::collections::BTreeMap;
 use std::error::Error;
 use std::rc::Rc;
 
+use bytes::Bytes;
 use derive_more::{Display, From};
 
-use protocol::traits::executor::contract::{AccountContract, ContractStateAdapter};
+use protocol::traits::executor::contract::{
+    AccountContract, ContractStateAdapter, NativeContract, NativeDispatcher,
+};
 use protocol::traits::executor::RcInvokeContext;
 use protocol::types::{
-    Account, Address, AssetID, AssetInfo, Balance, ContractAccount, Hash, UserAccount,
+    Account, Address, ApprovedInfo, AssetID, AssetInfo, Balance, ContractAccount, ContractAddress,
+    Hash, UserAccount,
 };
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::cycles::{consume_cycles, CyclesAction};
-use crate::fixed_types::{FixedAccount, FixedAccountSchema, FixedAddress};
+use crate::fixed_types::{
+    FixedAccount, FixedAccountSchema, FixedAddress, FixedNonceIndexKey, FixedNonceIndexSchema,
+    FixedNonceKey, FixedNonceSchema, FixedTimeout,
+};
+use crate::native_contract::{emit_event, ACCOUNT_CONTRACT_ADDRESS};
 
 pub struct NativeAccountContract<StateAdapter: ContractStateAdapter> {
     state_adapter: Rc<RefCell<StateAdapter>>,
@@ -25,6 +33,80 @@ impl<StateAdapter: ContractStateAdapter> NativeAccountContract<StateAdapter> {
     }
 }
 
+impl<StateAdapter: ContractStateAdapter> NativeContract<StateAdapter>
+    for NativeAccountContract<StateAdapter>
+{
+    // Entry of the `Call` transaction, the arguments and the return value are
+    // encoded as bytes.
+    //
+    // get_balance(asset_id, address) -> balance(big endian)
+    // get_nonce(address) -> nonce(u64, big endian)
+    // get_allowance(asset_id, owner, spender) -> remaining allowance(big endian)
+    // transfer_from(from, to, asset_id, amount(big endian)) -> empty
+    fn invoke(
+        &mut self,
+        _dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        match method {
+            "get_balance" => {
+                let (id, address) = match args.as_slice() {
+                    [id, address] => (
+                        AssetID::from_bytes(id.clone())?,
+                        Address::from_bytes(address.clone())?,
+                    ),
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let balance = self.get_balance(&id, &address)?;
+                Ok(Bytes::from(balance.to_bytes_be()))
+            }
+            "get_nonce" => {
+                let address = match args.as_slice() {
+                    [address] => Address::from_bytes(address.clone())?,
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let nonce = self.get_nonce(&address)?;
+                Ok(Bytes::from(nonce.to_be_bytes().to_vec()))
+            }
+            "get_allowance" => {
+                let (id, owner, spender) = match args.as_slice() {
+                    [id, owner, spender] => (
+                        AssetID::from_bytes(id.clone())?,
+                        Address::from_bytes(owner.clone())?,
+                        ContractAddress::from_bytes(spender.clone())?,
+                    ),
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let info = self.get_allowance(&id, &owner, &spender)?;
+                Ok(Bytes::from((info.max - info.used).to_bytes_be()))
+            }
+            "transfer_from" => {
+                let (from, to, id, amount) = match args.as_slice() {
+                    [from, to, id, amount] => (
+                        Address::from_bytes(from.clone())?,
+                        Address::from_bytes(to.clone())?,
+                        AssetID::from_bytes(id.clone())?,
+                        Balance::from_bytes_be(amount.as_ref()),
+                    ),
+                    _ => return Err(invalid_args(method)),
+                };
+
+                self.transfer_from(ictx, &from, &to, &id, amount)?;
+                Ok(Bytes::new())
+            }
+            _ => Err(NativeAccountContractError::MethodNotFound {
+                method: method.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
 impl<StateAdapter: ContractStateAdapter> AccountContract<StateAdapter>
     for NativeAccountContract<StateAdapter>
 {
@@ -42,17 +124,169 @@ impl<StateAdapter: ContractStateAdapter> AccountContract<StateAdapter>
         )?;
         self.add_balance(&carrying_asset.asset_id, to, carrying_asset.amount.clone())?;
 
-        let mut fee = ictx.borrow().cycles_used.clone();
-        consume_cycles(
-            CyclesAction::AccountTransfer,
-            ictx.borrow().cycles_price,
-            &mut fee,
-            &ictx.borrow().cycles_limit,
+        let from = ictx.borrow().caller.clone();
+        emit_transfer(
+            &ictx,
+            &from,
+            to,
+            &carrying_asset.asset_id,
+            &carrying_asset.amount,
         )?;
-        ictx.borrow_mut().cycles_used = fee;
+
+        consume_cycles(&ictx, CyclesAction::AccountTransfer)?;
         Ok(())
     }
 
+    fn approve(
+        &mut self,
+        ictx: RcInvokeContext,
+        spender: &ContractAddress,
+        id: &AssetID,
+        max: Balance,
+    ) -> ProtocolResult<()> {
+        let caller = ictx.borrow().caller.clone();
+
+        let mut user = match self.find_or_create(&caller)? {
+            Account::User(user) => user,
+            Account::Contract(_) => return Err(NativeAccountContractError::InvalidAddress.into()),
+        };
+
+        user.assets
+            .entry(id.clone())
+            .or_insert_with(|| AssetInfo {
+                balance:  Balance::from(0u64),
+                approved: BTreeMap::new(),
+            })
+            .approved
+            .insert(spender.clone(), ApprovedInfo {
+                max:  max.clone(),
+                used: Balance::from(0u64),
+            });
+
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedAccountSchema>(
+                FixedAddress::new(caller),
+                FixedAccount::new(Account::User(user)),
+            )?;
+
+        emit_event(&ictx, &ACCOUNT_CONTRACT_ADDRESS, "Approve", id, vec![
+            caller.as_bytes(),
+            spender.as_bytes(),
+            Bytes::from(max.to_bytes_be()),
+        ])?;
+
+        consume_cycles(&ictx, CyclesAction::AccountApprove)?;
+        Ok(())
+    }
+
+    fn transfer_from(
+        &mut self,
+        ictx: RcInvokeContext,
+        from: &Address,
+        to: &Address,
+        id: &AssetID,
+        amount: Balance,
+    ) -> ProtocolResult<()> {
+        let spender = match &ictx.borrow().caller {
+            Address::Contract(contract) => contract.clone(),
+            Address::User(_) => return Err(NativeAccountContractError::InvalidAddress.into()),
+        };
+
+        let mut user = match self.get_account(from)? {
+            Account::User(user) => user,
+            Account::Contract(_) => return Err(NativeAccountContractError::InvalidAddress.into()),
+        };
+
+        let info = user
+            .assets
+            .get_mut(id)
+            .ok_or(NativeAccountContractError::InsufficientBalance)?;
+        if info.balance < amount {
+            return Err(NativeAccountContractError::InsufficientBalance.into());
+        }
+
+        let approved = info.approved.get_mut(&spender).ok_or_else(|| {
+            NativeAccountContractError::NotApproved {
+                spender: spender.clone(),
+            }
+        })?;
+        let used = approved.used.clone() + amount.clone();
+        if used > approved.max {
+            return Err(NativeAccountContractError::ExceedAllowance.into());
+        }
+
+        approved.used = used;
+        info.balance -= amount.clone();
+
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedAccountSchema>(
+                FixedAddress::new(from.clone()),
+                FixedAccount::new(Account::User(user)),
+            )?;
+        self.add_balance(id, to, amount.clone())?;
+        emit_transfer(&ictx, from, to, id, &amount)?;
+
+        consume_cycles(&ictx, CyclesAction::AccountTransfer)?;
+        Ok(())
+    }
+
+    fn record_nonce(
+        &mut self,
+        address: &Address,
+        nonce: &Hash,
+        timeout: u64,
+    ) -> ProtocolResult<()> {
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedNonceSchema>(
+                FixedNonceKey::new(address, nonce),
+                FixedTimeout::new(timeout),
+            )
+    }
+
+    fn contains_nonce(&self, address: &Address, nonce: &Hash) -> ProtocolResult<bool> {
+        self.state_adapter
+            .borrow()
+            .contains::<FixedNonceSchema>(&FixedNonceKey::new(address, nonce))
+    }
+
+    fn index_nonces(&mut self, nonces: Vec<(Address, Hash, u64)>) -> ProtocolResult<()> {
+        let mut timeouts: BTreeMap<u64, Vec<FixedNonceKey>> = BTreeMap::new();
+        for (address, nonce, timeout) in nonces.iter() {
+            timeouts
+                .entry(*timeout)
+                .or_default()
+                .push(FixedNonceKey::new(address, nonce));
+        }
+
+        let mut state_adapter = self.state_adapter.borrow_mut();
+        for (timeout, keys) in timeouts.into_iter() {
+            let index_key = FixedNonceIndexKey::new(timeout);
+            let mut index = state_adapter
+                .get::<FixedNonceIndexSchema>(&index_key)?
+                .unwrap_or_default();
+            index.inner.extend(keys);
+            state_adapter.insert_cache::<FixedNonceIndexSchema>(index_key, index)?;
+        }
+        Ok(())
+    }
+
+    fn prune_nonces(&mut self, timeout: u64) -> ProtocolResult<()> {
+        let index_key = FixedNonceIndexKey::new(timeout);
+        let mut state_adapter = self.state_adapter.borrow_mut();
+        let index = match state_adapter.get::<FixedNonceIndexSchema>(&index_key)? {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        for key in index.inner.iter() {
+            state_adapter.remove_cache::<FixedNonceSchema>(key)?;
+        }
+        state_adapter.remove_cache::<FixedNonceIndexSchema>(&index_key)
+    }
+
     fn create_account(&mut self, address: &Address) -> ProtocolResult<Account> {
         self.find_or_create(address)
     }
@@ -114,8 +348,9 @@ impl<StateAdapter: ContractStateAdapter> AccountContract<StateAdapter>
     }
 
     fn inc_nonce(&mut self, ictx: RcInvokeContext) -> ProtocolResult<()> {
+        // A sender unable to pay any fee may have no account yet.
         let caller = &ictx.borrow().caller;
-        let account = self.get_account(caller)?;
+        let account = self.find_or_create(caller)?;
 
         let modified_account = match account {
             Account::User(user) => Account::User(UserAccount {
@@ -172,6 +407,26 @@ impl<StateAdapter: ContractStateAdapter> AccountContract<StateAdapter>
             Account::Contract(contract) => Ok(contract.nonce),
         }
     }
+
+    fn get_allowance(
+        &self,
+        id: &AssetID,
+        owner: &Address,
+        spender: &ContractAddress,
+    ) -> ProtocolResult<ApprovedInfo> {
+        let approved = match self.get_account(owner)? {
+            Account::User(user) => user
+                .assets
+                .get(id)
+                .and_then(|info| info.approved.get(spender).cloned()),
+            Account::Contract(_) => None,
+        };
+
+        Ok(approved.unwrap_or_else(|| ApprovedInfo {
+            max:  Balance::from(0u64),
+            used: Balance::from(0u64),
+        }))
+    }
 }
 
 impl<StateAdapter: ContractStateAdapter> NativeAccountContract<StateAdapter> {
@@ -277,12 +532,16 @@ impl<StateAdapter: ContractStateAdapter> NativeAccountContract<StateAdapter> {
         account: &mut ContractAccount,
         amount: Balance,
     ) -> ProtocolResult<()> {
-        account
-            .assets
-            .entry(id.clone())
-            .and_modify(|balance| *balance -= amount.clone())
-            .or_insert(amount);
-        Ok(())
+        if let Some(balance) = account.assets.get_mut(id) {
+            if *balance < amount {
+                return Err(NativeAccountContractError::InsufficientBalance.into());
+            }
+
+            *balance -= amount;
+            return Ok(());
+        }
+
+        Err(NativeAccountContractError::InsufficientBalance.into())
     }
 }
 
@@ -297,6 +556,18 @@ pub enum NativeAccountContractError {
     #[display(fmt = "invalid address")]
     InvalidAddress,
 
+    #[display(fmt = "contract {:?} is not approved", spender)]
+    NotApproved { spender: ContractAddress },
+
+    #[display(fmt = "exceed the approved allowance")]
+    ExceedAllowance,
+
+    #[display(fmt = "method {} not found", method)]
+    MethodNotFound { method: String },
+
+    #[display(fmt = "invalid args of method {}", method)]
+    InvalidArgs { method: String },
+
     #[display(fmt = "fixed codec {:?}", _0)]
     FixedCodec(rlp::DecoderError),
 }
@@ -308,3 +579,24 @@ impl From<NativeAccountContractError> for ProtocolError {
         ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
     }
 }
+
+fn invalid_args(method: &str) -> ProtocolError {
+    NativeAccountContractError::InvalidArgs {
+        method: method.to_owned(),
+    }
+    .into()
+}
+
+fn emit_transfer(
+    ictx: &RcInvokeContext,
+    from: &Address,
+    to: &Address,
+    id: &AssetID,
+    amount: &Balance,
+) -> ProtocolResult<()> {
+    emit_event(ictx, &ACCOUNT_CONTRACT_ADDRESS, "Transfer", id, vec![
+        from.as_bytes(),
+        to.as_bytes(),
+        Bytes::from(amount.to_bytes_be()),
+    ])
+}
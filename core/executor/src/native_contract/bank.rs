@@ -5,20 +5,26 @@ use std::rc::Rc;
 use bytes::Bytes;
 use derive_more::{Display, From};
 
-use protocol::traits::executor::contract::{BankContract, ContractStateAdapter};
-use protocol::traits::executor::RcInvokeContext;
-use protocol::types::{Asset, AssetID, Balance, ContractAddress, ContractType, Hash};
+use protocol::traits::executor::contract::{
+    BankContract, ContractStateAdapter, NativeContract, NativeDispatcher,
+};
+use protocol::traits::executor::{ContractSer, RcInvokeContext};
+use protocol::types::{
+    Address, Asset, AssetID, Balance, ContractAddress, ContractType, DeployAsset, Hash,
+};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::cycles::{consume_cycles, CyclesAction};
 use crate::fixed_types::{FixedAsset, FixedAssetID, FixedAssetSchema};
+use crate::native_contract::{emit_event, BANK_CONTRACT_ADDRESS};
 
 /// Bank is the registration and query center for asset.
 ///
-/// It only does two things
+/// It does three things
 /// 1. Responsible for generating a unique ID for the asset and writing the
 /// asset's information to the chain.
 /// 2. Query the basic information of the asset by asset id.
+/// 3. Keep the supply of the asset when it is minted or burned.
 pub struct NativeBankContract<StateAdapter: ContractStateAdapter> {
     chain_id: Hash,
 
@@ -34,6 +40,69 @@ impl<StateAdapter: ContractStateAdapter> NativeBankContract<StateAdapter> {
     }
 }
 
+impl<StateAdapter: ContractStateAdapter> NativeContract<StateAdapter>
+    for NativeBankContract<StateAdapter>
+{
+    // Entry of the `Call` transaction, the arguments and the return value are
+    // encoded as bytes. Minting and burning change the balances kept by the
+    // account contract as well.
+    //
+    // get_asset(asset_id) -> asset(rlp)
+    // mint(asset_id, to, amount(big endian))
+    // burn(asset_id, amount(big endian)), burned from the balance of the caller
+    fn invoke(
+        &mut self,
+        dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        match method {
+            "get_asset" => {
+                let id = match args.as_slice() {
+                    [id] => AssetID::from_bytes(id.clone())?,
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let asset = self.get_asset(ictx, &id)?;
+                FixedAsset::new(asset).encode()
+            }
+            "mint" => {
+                let (id, to, amount) = match args.as_slice() {
+                    [id, to, amount] => (
+                        AssetID::from_bytes(id.clone())?,
+                        Address::from_bytes(to.clone())?,
+                        Balance::from_bytes_be(amount.as_ref()),
+                    ),
+                    _ => return Err(invalid_args(method)),
+                };
+
+                self.mint(ictx, &id, amount.clone())?;
+                dispatcher.mint(&id, &to, amount)?;
+                Ok(Bytes::new())
+            }
+            "burn" => {
+                let (id, amount) = match args.as_slice() {
+                    [id, amount] => (
+                        AssetID::from_bytes(id.clone())?,
+                        Balance::from_bytes_be(amount.as_ref()),
+                    ),
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let caller = ictx.borrow().caller.clone();
+                dispatcher.burn(&id, &caller, amount.clone())?;
+                self.burn(ictx, &id, amount)?;
+                Ok(Bytes::new())
+            }
+            _ => Err(NativeBankContractError::MethodNotFound {
+                method: method.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
 impl<StateAdapter: ContractStateAdapter> BankContract<StateAdapter>
     for NativeBankContract<StateAdapter>
 {
@@ -46,9 +115,7 @@ impl<StateAdapter: ContractStateAdapter> BankContract<StateAdapter>
         &mut self,
         ictx: RcInvokeContext,
         address: &ContractAddress,
-        name: String,
-        symbol: String,
-        supply: Balance,
+        asset: &DeployAsset,
     ) -> ProtocolResult<Asset> {
         if address.contract_type() != ContractType::Asset {
             return Err(NativeBankContractError::InvalidAddress.into());
@@ -67,31 +134,28 @@ impl<StateAdapter: ContractStateAdapter> BankContract<StateAdapter>
             return Err(NativeBankContractError::AssetExists { id: asset_id }.into());
         }
 
+        // The supply of an asset without a manage contract is fixed, since
+        // no one can call as the asset contract.
         let asset = Asset {
-            name,
-            symbol,
-            supply,
-
-            id: asset_id.clone(),
-            manage_contract: address.clone(),
-            storage_root: Hash::from_empty(),
+            name:            asset.name.clone(),
+            symbol:          asset.symbol.clone(),
+            supply:          asset.supply.clone(),
+            decimals:        asset.decimals,
+            id:              asset_id.clone(),
+            manage_contract: asset
+                .manage_contract
+                .clone()
+                .unwrap_or_else(|| address.clone()),
+            storage_root:    Hash::from_empty(),
         };
+        self.save_asset(&asset)?;
 
-        self.state_adapter
-            .borrow_mut()
-            .insert_cache::<FixedAssetSchema>(
-                FixedAssetID::new(asset_id.clone()),
-                FixedAsset::new(asset.clone()),
-            )?;
-
-        let mut fee = ictx.borrow().cycles_used.clone();
-        consume_cycles(
-            CyclesAction::BankRegister,
-            ictx.borrow().cycles_price,
-            &mut fee,
-            &ictx.borrow().cycles_limit,
-        )?;
-        ictx.borrow_mut().cycles_used = fee;
+        consume_cycles(&ictx, CyclesAction::BankRegister)?;
+
+        emit_event(&ictx, &BANK_CONTRACT_ADDRESS, "Register", &asset_id, vec![
+            address.as_bytes(),
+            Bytes::from(asset.supply.to_bytes_be()),
+        ])?;
         Ok(asset)
     }
 
@@ -103,6 +167,71 @@ impl<StateAdapter: ContractStateAdapter> BankContract<StateAdapter>
             .ok_or(NativeBankContractError::NotFound { id: id.clone() })?;
         Ok(fixed_asset.inner)
     }
+
+    fn mint(
+        &mut self,
+        ictx: RcInvokeContext,
+        id: &AssetID,
+        amount: Balance,
+    ) -> ProtocolResult<Asset> {
+        let mut asset = self.get_managed_asset(Rc::clone(&ictx), id)?;
+        asset.supply += amount.clone();
+        self.save_asset(&asset)?;
+
+        consume_cycles(&ictx, CyclesAction::BankMint)?;
+
+        emit_event(&ictx, &BANK_CONTRACT_ADDRESS, "Mint", id, vec![
+            Bytes::from(amount.to_bytes_be()),
+        ])?;
+        Ok(asset)
+    }
+
+    fn burn(
+        &mut self,
+        ictx: RcInvokeContext,
+        id: &AssetID,
+        amount: Balance,
+    ) -> ProtocolResult<Asset> {
+        let mut asset = self.get_managed_asset(Rc::clone(&ictx), id)?;
+        if asset.supply < amount {
+            return Err(NativeBankContractError::InsufficientSupply {
+                supply: asset.supply,
+                amount,
+            }
+            .into());
+        }
+        asset.supply -= amount.clone();
+        self.save_asset(&asset)?;
+
+        consume_cycles(&ictx, CyclesAction::BankBurn)?;
+
+        emit_event(&ictx, &BANK_CONTRACT_ADDRESS, "Burn", id, vec![
+            Bytes::from(amount.to_bytes_be()),
+        ])?;
+        Ok(asset)
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeBankContract<StateAdapter> {
+    // Only the manage contract can change the supply of an asset.
+    fn get_managed_asset(&self, ictx: RcInvokeContext, id: &AssetID) -> ProtocolResult<Asset> {
+        let asset = self.get_asset(Rc::clone(&ictx), id)?;
+
+        let caller = ictx.borrow().caller.clone();
+        if caller != Address::Contract(asset.manage_contract.clone()) {
+            return Err(NativeBankContractError::Forbidden { caller }.into());
+        }
+        Ok(asset)
+    }
+
+    fn save_asset(&mut self, asset: &Asset) -> ProtocolResult<()> {
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedAssetSchema>(
+                FixedAssetID::new(asset.id.clone()),
+                FixedAsset::new(asset.clone()),
+            )
+    }
 }
 
 #[derive(Debug, Display, From)]
@@ -116,6 +245,18 @@ pub enum NativeBankContractError {
     #[display(fmt = "invalid address")]
     InvalidAddress,
 
+    #[display(fmt = "{:?} is not the manage contract of the asset", caller)]
+    Forbidden { caller: Address },
+
+    #[display(fmt = "burn {} exceeds the supply {}", amount, supply)]
+    InsufficientSupply { supply: Balance, amount: Balance },
+
+    #[display(fmt = "method {} not found", method)]
+    MethodNotFound { method: String },
+
+    #[display(fmt = "invalid args of method {}", method)]
+    InvalidArgs { method: String },
+
     #[display(fmt = "fixed codec {:?}", _0)]
     FixedCodec(rlp::DecoderError),
 }
@@ -127,3 +268,10 @@ impl From<NativeBankContractError> for ProtocolError {
         ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
     }
 }
+
+fn invalid_args(method: &str) -> ProtocolError {
+    NativeBankContractError::InvalidArgs {
+        method: method.to_owned(),
+    }
+    .into()
+}
@@ -0,0 +1,380 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+
+use protocol::traits::executor::contract::{
+    ContractStateAdapter, GovernanceContract, NativeContract, NativeDispatcher,
+};
+use protocol::traits::executor::{ContractSer, RcInvokeContext};
+use protocol::types::{Address, ChainParams, UserAddress, Validator};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::cycles::{consume_cycles, CyclesAction};
+use crate::fixed_types::{
+    FixedChainParams, FixedGovernance, FixedGovernanceKey, FixedGovernanceSchema, FixedProposal,
+};
+
+/// Governance keeps the parameters every node of the chain must agree on.
+///
+/// The validators propose new parameters with the epoch they take effect
+/// from, a proposal is approved once the validators voting for it hold more
+/// than 2/3 of the vote weight. The approved parameters are applied at the
+/// end of the epoch before their activate epoch.
+pub struct NativeGovernanceContract<StateAdapter: ContractStateAdapter> {
+    state_adapter: Rc<RefCell<StateAdapter>>,
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeGovernanceContract<StateAdapter> {
+    pub fn new(state_adapter: Rc<RefCell<StateAdapter>>) -> Self {
+        Self { state_adapter }
+    }
+
+    // Called by the genesis with the parameters and the validators of the
+    // genesis.
+    pub fn init(&mut self, params: ChainParams, validators: Vec<Validator>) -> ProtocolResult<()> {
+        self.save_governance(FixedGovernance {
+            params,
+            validators,
+            next_proposal_id: 0,
+            proposals: BTreeMap::new(),
+            approved: vec![],
+        })
+    }
+
+    // Called by the executor at the end of every epoch, return the parameters
+    // taking effect from `next_epoch_id`. The validators follow the elected
+    // ones the same as the consensus does, they are kept if no one is
+    // elected. `None` is returned if the chain has no governance contract.
+    pub fn advance(
+        &mut self,
+        next_epoch_id: u64,
+        elected: Vec<Validator>,
+    ) -> ProtocolResult<Option<ChainParams>> {
+        let mut governance = match self
+            .state_adapter
+            .borrow()
+            .get::<FixedGovernanceSchema>(&FixedGovernanceKey)?
+        {
+            Some(governance) => governance,
+            None => return Ok(None),
+        };
+        let mut changed = false;
+
+        if !elected.is_empty() && elected != governance.validators {
+            governance.validators = elected;
+            changed = true;
+        }
+
+        // The later activate epoch wins, the later approved one wins if they
+        // take effect from the same epoch.
+        let (mut activated, approved): (Vec<_>, Vec<_>) = governance
+            .approved
+            .into_iter()
+            .partition(|proposal| proposal.activate_epoch_id <= next_epoch_id);
+        governance.approved = approved;
+        activated.sort_by_key(|proposal| proposal.activate_epoch_id);
+        for proposal in activated.into_iter() {
+            governance.params = proposal.params;
+            changed = true;
+        }
+
+        // The proposals can not take effect in time once their activate epoch
+        // comes, they are dropped.
+        let proposals_len = governance.proposals.len();
+        governance.proposals = governance
+            .proposals
+            .into_iter()
+            .filter(|(_, proposal)| proposal.activate_epoch_id > next_epoch_id)
+            .collect();
+        changed |= governance.proposals.len() != proposals_len;
+
+        let params = governance.params.clone();
+        if changed {
+            self.save_governance(governance)?;
+        }
+        Ok(Some(params))
+    }
+
+    // The parameters on the current state, `None` if the chain has no
+    // governance contract.
+    pub fn get_chain_params(&self) -> ProtocolResult<Option<ChainParams>> {
+        let governance = self
+            .state_adapter
+            .borrow()
+            .get::<FixedGovernanceSchema>(&FixedGovernanceKey)?;
+        Ok(governance.map(|governance| governance.params))
+    }
+
+    fn get_governance(&self) -> ProtocolResult<FixedGovernance> {
+        let governance = self
+            .state_adapter
+            .borrow()
+            .get::<FixedGovernanceSchema>(&FixedGovernanceKey)?
+            .ok_or(NativeGovernanceContractError::Uninitialized)?;
+        Ok(governance)
+    }
+
+    fn save_governance(&mut self, governance: FixedGovernance) -> ProtocolResult<()> {
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedGovernanceSchema>(FixedGovernanceKey, governance)
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeContract<StateAdapter>
+    for NativeGovernanceContract<StateAdapter>
+{
+    // Entry of the `Call` transaction, the arguments and the return value are
+    // encoded as bytes. The epoch id and the proposal id are big endian.
+    //
+    // propose(activate_epoch_id, params(rlp)) -> proposal_id
+    // vote(proposal_id)
+    // get_params() -> params(rlp)
+    // get_proposal(proposal_id) -> proposal(rlp)
+    fn invoke(
+        &mut self,
+        _dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        match method {
+            "propose" => {
+                let (activate_epoch_id, params) = match args.as_slice() {
+                    [activate_epoch_id, params] => (
+                        parse_u64(method, activate_epoch_id)?,
+                        FixedChainParams::decode(params.clone())?.inner,
+                    ),
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let proposal_id = self.propose(ictx, activate_epoch_id, params)?;
+                Ok(Bytes::from(proposal_id.to_be_bytes().to_vec()))
+            }
+            "vote" => {
+                let proposal_id = match args.as_slice() {
+                    [proposal_id] => parse_u64(method, proposal_id)?,
+                    _ => return Err(invalid_args(method)),
+                };
+
+                self.vote(ictx, proposal_id)?;
+                Ok(Bytes::new())
+            }
+            "get_params" => {
+                if !args.is_empty() {
+                    return Err(invalid_args(method));
+                }
+
+                FixedChainParams::new(self.get_params()?).encode()
+            }
+            "get_proposal" => {
+                let proposal_id = match args.as_slice() {
+                    [proposal_id] => parse_u64(method, proposal_id)?,
+                    _ => return Err(invalid_args(method)),
+                };
+
+                self.get_governance()?
+                    .proposals
+                    .get(&proposal_id)
+                    .ok_or(NativeGovernanceContractError::ProposalNotFound { proposal_id })?
+                    .encode()
+            }
+            _ => Err(NativeGovernanceContractError::MethodNotFound {
+                method: method.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> GovernanceContract<StateAdapter>
+    for NativeGovernanceContract<StateAdapter>
+{
+    fn propose(
+        &mut self,
+        ictx: RcInvokeContext,
+        activate_epoch_id: u64,
+        params: ChainParams,
+    ) -> ProtocolResult<u64> {
+        let mut governance = self.get_governance()?;
+        let proposer = caller_validator(&ictx, &governance.validators)?;
+
+        let epoch_id = ictx.borrow().epoch_id;
+        if activate_epoch_id <= epoch_id {
+            return Err(NativeGovernanceContractError::InvalidActivateEpoch {
+                activate_epoch_id,
+                epoch_id,
+            }
+            .into());
+        }
+        check_params(&params)?;
+
+        let proposal_id = governance.next_proposal_id;
+        governance.next_proposal_id += 1;
+
+        let mut votes = BTreeSet::new();
+        votes.insert(proposer.clone());
+        governance.proposals.insert(proposal_id, FixedProposal {
+            proposer,
+            activate_epoch_id,
+            params,
+            votes,
+        });
+        try_approve(&mut governance, proposal_id);
+        self.save_governance(governance)?;
+
+        consume_cycles(&ictx, CyclesAction::GovernancePropose)?;
+        Ok(proposal_id)
+    }
+
+    fn vote(&mut self, ictx: RcInvokeContext, proposal_id: u64) -> ProtocolResult<()> {
+        let mut governance = self.get_governance()?;
+        let voter = caller_validator(&ictx, &governance.validators)?;
+
+        let proposal = governance
+            .proposals
+            .get_mut(&proposal_id)
+            .ok_or(NativeGovernanceContractError::ProposalNotFound { proposal_id })?;
+        if !proposal.votes.insert(voter) {
+            return Err(NativeGovernanceContractError::AlreadyVoted { proposal_id }.into());
+        }
+        try_approve(&mut governance, proposal_id);
+        self.save_governance(governance)?;
+
+        consume_cycles(&ictx, CyclesAction::GovernanceVote)
+    }
+
+    fn get_params(&self) -> ProtocolResult<ChainParams> {
+        Ok(self.get_governance()?.params)
+    }
+}
+
+// Only the validators can propose and vote.
+fn caller_validator(
+    ictx: &RcInvokeContext,
+    validators: &[Validator],
+) -> ProtocolResult<UserAddress> {
+    match &ictx.borrow().caller {
+        Address::User(address)
+            if validators
+                .iter()
+                .any(|validator| validator.address == *address) =>
+        {
+            Ok(address.clone())
+        }
+        caller => Err(NativeGovernanceContractError::NotValidator {
+            caller: caller.clone(),
+        }
+        .into()),
+    }
+}
+
+// Move the proposal to the approved ones if the validators voting for it hold
+// more than 2/3 of the vote weight. The votes of the accounts which are no
+// longer validators are not counted.
+fn try_approve(governance: &mut FixedGovernance, proposal_id: u64) {
+    let votes = match governance.proposals.get(&proposal_id) {
+        Some(proposal) => &proposal.votes,
+        None => return,
+    };
+
+    let (voted, total) =
+        governance
+            .validators
+            .iter()
+            .fold((0u64, 0u64), |(voted, total), validator| {
+                let weight = u64::from(validator.vote_weight);
+                if votes.contains(&validator.address) {
+                    (voted + weight, total + weight)
+                } else {
+                    (voted, total + weight)
+                }
+            });
+
+    if voted * 3 > total * 2 {
+        if let Some(proposal) = governance.proposals.remove(&proposal_id) {
+            governance.approved.push(proposal);
+        }
+    }
+}
+
+// The cycles limit, the price and the interval can not be zero, nor can the
+// denominators of the consensus timeouts.
+fn check_params(params: &ChainParams) -> ProtocolResult<()> {
+    if params.cycles_limit == 0
+        || params.cycles_price == 0
+        || params.interval == 0
+        || params.propose_denominator == 0
+        || params.prevote_denominator == 0
+        || params.precommit_denominator == 0
+    {
+        return Err(NativeGovernanceContractError::InvalidParams {
+            params: params.clone(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn parse_u64(method: &str, bytes: &Bytes) -> ProtocolResult<u64> {
+    if bytes.len() != 8 {
+        return Err(invalid_args(method));
+    }
+
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(bytes);
+    Ok(u64::from_be_bytes(buf))
+}
+
+#[derive(Debug, Display, From)]
+pub enum NativeGovernanceContractError {
+    #[display(fmt = "governance is not initialized by the genesis")]
+    Uninitialized,
+
+    #[display(fmt = "{:?} is not a validator", caller)]
+    NotValidator { caller: Address },
+
+    #[display(
+        fmt = "activate epoch {} must be after the current epoch {}",
+        activate_epoch_id,
+        epoch_id
+    )]
+    InvalidActivateEpoch {
+        activate_epoch_id: u64,
+        epoch_id:          u64,
+    },
+
+    #[display(fmt = "invalid chain params {:?}", params)]
+    InvalidParams { params: ChainParams },
+
+    #[display(fmt = "proposal {} not found", proposal_id)]
+    ProposalNotFound { proposal_id: u64 },
+
+    #[display(fmt = "proposal {} has been voted", proposal_id)]
+    AlreadyVoted { proposal_id: u64 },
+
+    #[display(fmt = "method {} not found", method)]
+    MethodNotFound { method: String },
+
+    #[display(fmt = "invalid args of method {}", method)]
+    InvalidArgs { method: String },
+}
+
+impl Error for NativeGovernanceContractError {}
+
+impl From<NativeGovernanceContractError> for ProtocolError {
+    fn from(err: NativeGovernanceContractError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
+
+fn invalid_args(method: &str) -> ProtocolError {
+    NativeGovernanceContractError::InvalidArgs {
+        method: method.to_owned(),
+    }
+    .into()
+}
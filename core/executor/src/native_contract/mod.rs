@@ -1,9 +1,14 @@
 mod account;
 mod bank;
+mod governance;
+mod staking;
 
+use bytes::Bytes;
 use lazy_static::lazy_static;
 
-use protocol::types::Address;
+use protocol::traits::executor::RcInvokeContext;
+use protocol::types::{Address, AssetID, ContractAddress, Hash, Log};
+use protocol::ProtocolResult;
 
 lazy_static! {
     pub static ref ACCOUNT_CONTRACT_ADDRESS: Address = Address::from_hex(
@@ -14,7 +19,45 @@ lazy_static! {
         "0x230000000000000000000000000000000000000002"
     )
     .expect("0x230000000000000000000000000000000000000001 is not a legal native contract address.");
+    // The state of the chain itself, e.g. the cycles schedules.
+    pub static ref SYSTEM_CONTRACT_ADDRESS: Address = Address::from_hex(
+        "0x230000000000000000000000000000000000000003"
+    )
+    .expect("0x230000000000000000000000000000000000000003 is not a legal native contract address.");
+    pub static ref STAKING_CONTRACT_ADDRESS: Address = Address::from_hex(
+        "0x230000000000000000000000000000000000000004"
+    )
+    .expect("0x230000000000000000000000000000000000000004 is not a legal native contract address.");
+    pub static ref GOVERNANCE_CONTRACT_ADDRESS: Address = Address::from_hex(
+        "0x230000000000000000000000000000000000000005"
+    )
+    .expect("0x230000000000000000000000000000000000000005 is not a legal native contract address.");
 }
 
 pub use account::{NativeAccountContract, NativeAccountContractError};
 pub use bank::{NativeBankContract, NativeBankContractError};
+pub use governance::{NativeGovernanceContract, NativeGovernanceContractError};
+pub use staking::{NativeStakingContract, NativeStakingContractError};
+
+// Emit an event of a native contract. The event is indexed by the hash of its
+// name and the asset id, the fields are encoded as a rlp list of bytes.
+fn emit_event(
+    ictx: &RcInvokeContext,
+    contract: &Address,
+    name: &str,
+    asset_id: &AssetID,
+    fields: Vec<Bytes>,
+) -> ProtocolResult<()> {
+    let fields = fields
+        .iter()
+        .map(|field| field.to_vec())
+        .collect::<Vec<_>>();
+
+    let log = Log {
+        address: ContractAddress::from_bytes(contract.as_bytes())?,
+        topics:  vec![Hash::digest(Bytes::from(name)), asset_id.clone()],
+        data:    Bytes::from(rlp::encode_list::<Vec<u8>, _>(&fields)),
+    };
+    ictx.borrow_mut().logs.push(log);
+    Ok(())
+}
@@ -0,0 +1,433 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+
+use protocol::traits::executor::contract::{
+    ContractStateAdapter, NativeContract, NativeDispatcher, StakingContract,
+};
+use protocol::traits::executor::RcInvokeContext;
+use protocol::types::{Address, AssetID, Balance, UserAddress, Validator};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::cycles::{consume_cycles, CyclesAction};
+use crate::fixed_types::{
+    FixedStake, FixedStakeKey, FixedStakeSchema, FixedStakers, FixedStakersKey, FixedStakersSchema,
+    FixedStaking, FixedStakingKey, FixedStakingSchema,
+};
+use crate::native_contract::{emit_event, STAKING_CONTRACT_ADDRESS};
+
+/// Staking keeps the system token bonded by the user accounts.
+///
+/// The accounts which bond at least `min_stake` are the candidates, the top
+/// `max_validators` of them by stake are the validators. Their weights are
+/// proportional to their stake, the largest stake gets the max weight. The
+/// unbonded stake stays in the contract until its release epoch, so a
+/// validator can not withdraw before it leaves the validators.
+pub struct NativeStakingContract<StateAdapter: ContractStateAdapter> {
+    state_adapter: Rc<RefCell<StateAdapter>>,
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeStakingContract<StateAdapter> {
+    pub fn new(state_adapter: Rc<RefCell<StateAdapter>>) -> Self {
+        Self { state_adapter }
+    }
+
+    // Called by the genesis, the staking parameters never change afterwards.
+    pub fn init(
+        &mut self,
+        asset_id: AssetID,
+        min_stake: Balance,
+        max_validators: u64,
+        unbonding_epochs: u64,
+    ) -> ProtocolResult<()> {
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedStakingSchema>(FixedStakingKey, FixedStaking {
+                asset_id,
+                min_stake,
+                max_validators,
+                unbonding_epochs,
+            })
+    }
+
+    // The asset which can be bonded, it is the system token.
+    fn asset_id(&self) -> ProtocolResult<AssetID> {
+        Ok(self.get_staking()?.asset_id)
+    }
+
+    fn get_staking(&self) -> ProtocolResult<FixedStaking> {
+        let staking = self
+            .state_adapter
+            .borrow()
+            .get::<FixedStakingSchema>(&FixedStakingKey)?
+            .ok_or(NativeStakingContractError::Uninitialized)?;
+        Ok(staking)
+    }
+
+    fn get_stakers(&self) -> ProtocolResult<FixedStakers> {
+        Ok(self
+            .state_adapter
+            .borrow()
+            .get::<FixedStakersSchema>(&FixedStakersKey)?
+            .unwrap_or_default())
+    }
+
+    fn get_fixed_stake(&self, address: &UserAddress) -> ProtocolResult<FixedStake> {
+        Ok(self
+            .state_adapter
+            .borrow()
+            .get::<FixedStakeSchema>(&FixedStakeKey::new(address))?
+            .unwrap_or_default())
+    }
+
+    // The stakers only change when the bonded stake becomes zero or non-zero,
+    // the stake of an address is removed once nothing is left.
+    fn save_fixed_stake(
+        &mut self,
+        address: &UserAddress,
+        bonded_before: &Balance,
+        stake: FixedStake,
+    ) -> ProtocolResult<()> {
+        let zero = Balance::from(0u64);
+        if (*bonded_before == zero) != (stake.bonded == zero) {
+            let mut stakers = self.get_stakers()?;
+            if stake.bonded == zero {
+                stakers.inner.remove(address);
+            } else {
+                stakers.inner.insert(address.clone());
+            }
+            self.state_adapter
+                .borrow_mut()
+                .insert_cache::<FixedStakersSchema>(FixedStakersKey, stakers)?;
+        }
+
+        let key = FixedStakeKey::new(address);
+        let mut state_adapter = self.state_adapter.borrow_mut();
+        if stake.bonded == zero && stake.unbonding.is_empty() {
+            state_adapter.remove_cache::<FixedStakeSchema>(&key)
+        } else {
+            state_adapter.insert_cache::<FixedStakeSchema>(key, stake)
+        }
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeContract<StateAdapter>
+    for NativeStakingContract<StateAdapter>
+{
+    // Entry of the `Call` transaction, the arguments and the return value are
+    // encoded as bytes. Bonding and withdrawing move the system token between
+    // the caller and the staking contract, only a user account can be a
+    // validator.
+    //
+    // bond(amount(big endian))
+    // unbond(amount(big endian))
+    // withdraw() -> amount(big endian)
+    // get_stake(address) -> stake(big endian)
+    // get_validators() -> [[address, propose_weight, vote_weight]](rlp)
+    fn invoke(
+        &mut self,
+        dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        match method {
+            "bond" | "unbond" | "withdraw" => {
+                let amount = match (method, args.as_slice()) {
+                    ("withdraw", []) => None,
+                    ("bond", [amount]) | ("unbond", [amount]) => {
+                        Some(Balance::from_bytes_be(amount.as_ref()))
+                    }
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let caller = ictx.borrow().caller.clone();
+                let address = match &caller {
+                    Address::User(address) => address.clone(),
+                    Address::Contract(_) => {
+                        return Err(NativeStakingContractError::NotUserCaller { caller }.into())
+                    }
+                };
+
+                let asset_id = self.asset_id()?;
+                match (method, amount) {
+                    ("bond", Some(amount)) => {
+                        dispatcher.transfer(
+                            &asset_id,
+                            &caller,
+                            &STAKING_CONTRACT_ADDRESS,
+                            amount.clone(),
+                        )?;
+                        self.bond(ictx, &address, amount)?;
+                        Ok(Bytes::new())
+                    }
+                    ("unbond", Some(amount)) => {
+                        self.unbond(ictx, &address, amount)?;
+                        Ok(Bytes::new())
+                    }
+                    _ => {
+                        let amount = self.withdraw(ictx, &address)?;
+                        dispatcher.transfer(
+                            &asset_id,
+                            &STAKING_CONTRACT_ADDRESS,
+                            &caller,
+                            amount.clone(),
+                        )?;
+                        Ok(Bytes::from(amount.to_bytes_be()))
+                    }
+                }
+            }
+            "get_stake" => {
+                let address = match args.as_slice() {
+                    [address] => UserAddress::from_bytes(address.clone())?,
+                    _ => return Err(invalid_args(method)),
+                };
+
+                let stake = self.get_stake(&address)?;
+                Ok(Bytes::from(stake.to_bytes_be()))
+            }
+            "get_validators" => {
+                if !args.is_empty() {
+                    return Err(invalid_args(method));
+                }
+
+                let mut s = rlp::RlpStream::new();
+                let validators = self.get_validators()?;
+                s.begin_list(validators.len());
+                for validator in validators.iter() {
+                    s.begin_list(3);
+                    s.append(&validator.address.as_bytes().to_vec());
+                    s.append(&validator.propose_weight);
+                    s.append(&validator.vote_weight);
+                }
+                Ok(Bytes::from(s.out()))
+            }
+            _ => Err(NativeStakingContractError::MethodNotFound {
+                method: method.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> StakingContract<StateAdapter>
+    for NativeStakingContract<StateAdapter>
+{
+    fn bond(
+        &mut self,
+        ictx: RcInvokeContext,
+        address: &UserAddress,
+        amount: Balance,
+    ) -> ProtocolResult<()> {
+        let staking = self.get_staking()?;
+        let mut stake = self.get_fixed_stake(address)?;
+        let bonded_before = stake.bonded.clone();
+        stake.bonded += amount.clone();
+        // Every stake is at least the minimum, so the stakes can not be
+        // flooded by dust bonds.
+        if stake.bonded < staking.min_stake {
+            return Err(NativeStakingContractError::BelowMinStake {
+                stake:     stake.bonded,
+                min_stake: staking.min_stake,
+            }
+            .into());
+        }
+        self.save_fixed_stake(address, &bonded_before, stake)?;
+
+        consume_cycles(&ictx, CyclesAction::StakingBond)?;
+
+        emit_event(
+            &ictx,
+            &STAKING_CONTRACT_ADDRESS,
+            "Bond",
+            &staking.asset_id,
+            vec![address.as_bytes(), Bytes::from(amount.to_bytes_be())],
+        )
+    }
+
+    fn unbond(
+        &mut self,
+        ictx: RcInvokeContext,
+        address: &UserAddress,
+        amount: Balance,
+    ) -> ProtocolResult<()> {
+        let staking = self.get_staking()?;
+        let mut stake = self.get_fixed_stake(address)?;
+        if stake.bonded < amount {
+            return Err(NativeStakingContractError::InsufficientStake {
+                stake: stake.bonded,
+                amount,
+            }
+            .into());
+        }
+
+        // Either the whole stake is unbonded, or at least the minimum stays.
+        let bonded_before = stake.bonded.clone();
+        stake.bonded -= amount.clone();
+        if stake.bonded != Balance::from(0u64) && stake.bonded < staking.min_stake {
+            return Err(NativeStakingContractError::BelowMinStake {
+                stake:     stake.bonded,
+                min_stake: staking.min_stake,
+            }
+            .into());
+        }
+
+        // The validators elected at the end of this epoch take effect from
+        // the next one, the unbonded stake is released after that.
+        let release_epoch_id = ictx
+            .borrow()
+            .epoch_id
+            .saturating_add(1)
+            .saturating_add(staking.unbonding_epochs);
+        stake.unbonding.push((release_epoch_id, amount.clone()));
+        self.save_fixed_stake(address, &bonded_before, stake)?;
+
+        consume_cycles(&ictx, CyclesAction::StakingUnbond)?;
+
+        emit_event(
+            &ictx,
+            &STAKING_CONTRACT_ADDRESS,
+            "Unbond",
+            &staking.asset_id,
+            vec![address.as_bytes(), Bytes::from(amount.to_bytes_be())],
+        )
+    }
+
+    fn withdraw(
+        &mut self,
+        ictx: RcInvokeContext,
+        address: &UserAddress,
+    ) -> ProtocolResult<Balance> {
+        let asset_id = self.asset_id()?;
+        let mut stake = self.get_fixed_stake(address)?;
+        let epoch_id = ictx.borrow().epoch_id;
+
+        let (released, unbonding): (Vec<_>, Vec<_>) = stake
+            .unbonding
+            .into_iter()
+            .partition(|(release_epoch_id, _)| *release_epoch_id <= epoch_id);
+        stake.unbonding = unbonding;
+        let amount = released
+            .into_iter()
+            .fold(Balance::from(0u64), |acc, (_, amount)| acc + amount);
+        if amount == Balance::from(0u64) {
+            return Err(NativeStakingContractError::NothingReleased.into());
+        }
+        let bonded = stake.bonded.clone();
+        self.save_fixed_stake(address, &bonded, stake)?;
+
+        consume_cycles(&ictx, CyclesAction::StakingWithdraw)?;
+
+        emit_event(
+            &ictx,
+            &STAKING_CONTRACT_ADDRESS,
+            "Withdraw",
+            &asset_id,
+            vec![address.as_bytes(), Bytes::from(amount.to_bytes_be())],
+        )?;
+        Ok(amount)
+    }
+
+    fn get_stake(&self, address: &UserAddress) -> ProtocolResult<Balance> {
+        Ok(self.get_fixed_stake(address)?.bonded)
+    }
+
+    fn get_validators(&self) -> ProtocolResult<Vec<Validator>> {
+        // No one can bond on a chain without the staking contract.
+        let staking = self
+            .state_adapter
+            .borrow()
+            .get::<FixedStakingSchema>(&FixedStakingKey)?;
+        let FixedStaking {
+            min_stake,
+            max_validators,
+            ..
+        } = match staking {
+            Some(staking) => staking,
+            None => return Ok(vec![]),
+        };
+
+        let mut candidates = vec![];
+        for address in self.get_stakers()?.inner.into_iter() {
+            let stake = self.get_fixed_stake(&address)?.bonded;
+            if stake >= min_stake && stake > Balance::from(0u64) {
+                candidates.push((address, stake));
+            }
+        }
+
+        // Sorted by stake, the ties are broken by address.
+        candidates.sort_by(|(a, a_stake), (b, b_stake)| b_stake.cmp(a_stake).then(a.cmp(b)));
+        candidates.truncate(max_validators as usize);
+
+        let max_stake = match candidates.first() {
+            Some((_, stake)) => stake.clone(),
+            None => return Ok(vec![]),
+        };
+
+        let validators = candidates
+            .into_iter()
+            .map(|(address, stake)| {
+                let weight = stake_weight(&stake, &max_stake);
+                Validator {
+                    address,
+                    propose_weight: weight,
+                    vote_weight: weight,
+                }
+            })
+            .collect();
+        Ok(validators)
+    }
+}
+
+// Scale the stake to the weight in `1..=u8::MAX` against the largest stake.
+fn stake_weight(stake: &Balance, max_stake: &Balance) -> u8 {
+    let weight = stake * Balance::from(u8::max_value()) / max_stake;
+    // The weight is at most `u8::MAX`, it is a single byte.
+    let weight = weight.to_bytes_be().last().cloned().unwrap_or(0);
+    weight.max(1)
+}
+
+#[derive(Debug, Display, From)]
+pub enum NativeStakingContractError {
+    #[display(fmt = "staking is not initialized by the genesis")]
+    Uninitialized,
+
+    #[display(fmt = "unbond {} exceeds the stake {}", amount, stake)]
+    InsufficientStake { stake: Balance, amount: Balance },
+
+    #[display(fmt = "stake {} is below the minimum {}", stake, min_stake)]
+    BelowMinStake {
+        stake:     Balance,
+        min_stake: Balance,
+    },
+
+    #[display(fmt = "no unbonded stake is released")]
+    NothingReleased,
+
+    #[display(fmt = "{:?} is not a user account", caller)]
+    NotUserCaller { caller: Address },
+
+    #[display(fmt = "method {} not found", method)]
+    MethodNotFound { method: String },
+
+    #[display(fmt = "invalid args of method {}", method)]
+    InvalidArgs { method: String },
+}
+
+impl Error for NativeStakingContractError {}
+
+impl From<NativeStakingContractError> for ProtocolError {
+    fn from(err: NativeStakingContractError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
+
+fn invalid_args(method: &str) -> ProtocolError {
+    NativeStakingContractError::InvalidArgs {
+        method: method.to_owned(),
+    }
+    .into()
+}
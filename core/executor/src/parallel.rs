@@ -0,0 +1,110 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use protocol::types::{Address, SignedTransaction, TransactionAction};
+
+/// Partition the transactions into groups which touch disjoint accounts, the
+/// groups can be executed concurrently and the transactions in a group keep
+/// their order.
+///
+/// Only transfers are partitioned, because the accounts touched by other
+/// transactions are unknown before execution. `None` is returned if the
+/// transactions have to be executed sequentially.
+pub fn partition(signed_txs: &[SignedTransaction], coinbase: &Address) -> Option<Vec<Vec<usize>>> {
+    let mut set = DisjointSet::new(signed_txs.len());
+    let mut owners: HashMap<Address, usize> = HashMap::new();
+
+    for (index, signed_tx) in signed_txs.iter().enumerate() {
+        let receiver = match &signed_tx.raw.action {
+            TransactionAction::Transfer { receiver, .. } => Address::User(receiver.clone()),
+            _ => return None,
+        };
+        let sender = Address::User(signed_tx.sender().ok()?);
+
+        // Every transaction pays fee to the coinbase, its balance is settled
+        // after all groups are executed.
+        if sender == *coinbase || receiver == *coinbase {
+            return None;
+        }
+
+        for address in vec![sender, receiver] {
+            match owners.get(&address) {
+                Some(owner) => set.union(*owner, index),
+                None => {
+                    owners.insert(address, index);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut group_index: HashMap<usize, usize> = HashMap::new();
+    for index in 0..signed_txs.len() {
+        let root = set.find(index);
+        let group = *group_index.entry(root).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[group].push(index);
+    }
+
+    Some(groups)
+}
+
+/// Find the groups conflicting with the others, given the keys read and the
+/// keys written by every group. A group conflicts if it reads or writes a key
+/// written by another group, or writes a key read by another group.
+pub fn find_conflicts<K: Hash + Eq>(accesses: &[(HashSet<K>, HashSet<K>)]) -> Vec<bool> {
+    let mut readers: HashMap<&K, usize> = HashMap::new();
+    let mut writers: HashMap<&K, usize> = HashMap::new();
+    for (reads, writes) in accesses.iter() {
+        for key in reads.iter() {
+            *readers.entry(key).or_insert(0) += 1;
+        }
+        for key in writes.iter() {
+            *writers.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    accesses
+        .iter()
+        .map(|(reads, writes)| {
+            let written_by_others = |key: &K| writers[key] > writes.contains(key) as usize;
+            let read_by_others = |key: &K| readers[key] > reads.contains(key) as usize;
+
+            reads
+                .iter()
+                .any(|key| writers.contains_key(key) && written_by_others(key))
+                || writes.iter().any(|key| {
+                    written_by_others(key) || (readers.contains_key(key) && read_by_others(key))
+                })
+        })
+        .collect()
+}
+
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b] = a;
+        }
+    }
+}
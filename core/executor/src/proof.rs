@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use derive_more::{Display, From};
+
+use protocol::traits::executor::{ContractSer, ExecutorBalanceProof, TrieDB};
+use protocol::types::{Account, Address, AssetID, Balance, MerkleRoot};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::fixed_types::{FixedAccount, FixedAddress};
+use crate::native_contract::ACCOUNT_CONTRACT_ADDRESS;
+use crate::trie::{self, MPTTrie};
+
+/// Prove the balance of an account on the committed state of `trie`.
+pub fn prove_balance<DB: TrieDB>(
+    trie: &MPTTrie<DB>,
+    db: Arc<DB>,
+    address: &Address,
+    asset_id: &AssetID,
+) -> ProtocolResult<ExecutorBalanceProof> {
+    let contract_key = ACCOUNT_CONTRACT_ADDRESS.as_bytes();
+    let contract_root = trie
+        .get(&contract_key)?
+        .ok_or(BalanceProofError::AccountContractNotFound)?;
+    let contract_root = MerkleRoot::from_bytes(contract_root)?;
+    let state_proof = trie.get_proof(&contract_key)?;
+
+    let account_key = FixedAddress::new(address.clone()).encode()?;
+    let contract_trie = MPTTrie::from(contract_root.clone(), db)?;
+    let balance = match contract_trie.get(&account_key)? {
+        Some(value) => balance_of(&FixedAccount::decode(value)?.inner, asset_id),
+        None => Balance::from(0u64),
+    };
+    let account_proof = contract_trie.get_proof(&account_key)?;
+
+    Ok(ExecutorBalanceProof {
+        state_root: trie.root(),
+        contract_root,
+        balance,
+        state_proof,
+        account_proof,
+    })
+}
+
+/// Verify a balance proof against a trusted state root, the proved balance is
+/// returned. The `state_root` of the proof is ignored, so the proof can not be
+/// forged by a node.
+pub fn verify_balance_proof(
+    state_root: &MerkleRoot,
+    address: &Address,
+    asset_id: &AssetID,
+    proof: &ExecutorBalanceProof,
+) -> ProtocolResult<Balance> {
+    let contract_root = trie::verify_proof(
+        state_root,
+        &ACCOUNT_CONTRACT_ADDRESS.as_bytes(),
+        proof.state_proof.clone(),
+    )?
+    .ok_or(BalanceProofError::InvalidProof)?;
+    if MerkleRoot::from_bytes(contract_root)? != proof.contract_root {
+        return Err(BalanceProofError::InvalidProof.into());
+    }
+
+    let account_key = FixedAddress::new(address.clone()).encode()?;
+    let balance = match trie::verify_proof(
+        &proof.contract_root,
+        &account_key,
+        proof.account_proof.clone(),
+    )? {
+        Some(value) => balance_of(&FixedAccount::decode(value)?.inner, asset_id),
+        None => Balance::from(0u64),
+    };
+    if balance != proof.balance {
+        return Err(BalanceProofError::InvalidProof.into());
+    }
+
+    Ok(balance)
+}
+
+fn balance_of(account: &Account, asset_id: &AssetID) -> Balance {
+    let balance = match account {
+        Account::User(user) => user.assets.get(asset_id).map(|info| info.balance.clone()),
+        Account::Contract(contract) => contract.assets.get(asset_id).cloned(),
+    };
+
+    balance.unwrap_or_else(|| Balance::from(0u64))
+}
+
+#[derive(Debug, Display, From)]
+pub enum BalanceProofError {
+    #[display(fmt = "account contract not found in the state")]
+    AccountContractNotFound,
+
+    #[display(fmt = "invalid balance proof")]
+    InvalidProof,
+}
+
+impl Error for BalanceProofError {}
+
+impl From<BalanceProofError> for ProtocolError {
+    fn from(err: BalanceProofError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
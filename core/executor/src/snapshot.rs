@@ -0,0 +1,218 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+
+use protocol::traits::executor::TrieDB;
+use protocol::types::{Address, Epoch, MerkleRoot, Receipt};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::trie::MPTTrie;
+
+/// The complete world state at a state root, it contains the raw entries in
+/// the trie of every contract, including the accounts of the account contract,
+/// the assets of the bank contract and the storage of the wasm contracts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateSnapshot {
+    pub state_root: MerkleRoot,
+    pub contracts:  Vec<ContractSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractSnapshot {
+    pub address: Address,
+    pub entries: Vec<(Bytes, Bytes)>,
+}
+
+impl StateSnapshot {
+    pub fn encode(&self) -> Bytes {
+        Bytes::from(rlp::encode(self))
+    }
+
+    pub fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(SnapshotError::from)?)
+    }
+}
+
+/// Dump the world state at `state_root`.
+pub fn export_snapshot<DB: TrieDB>(
+    db: Arc<DB>,
+    state_root: &MerkleRoot,
+) -> ProtocolResult<StateSnapshot> {
+    let world = MPTTrie::from(state_root.clone(), Arc::clone(&db))?;
+
+    let mut contracts = vec![];
+    for (address, contract_root) in world.iter() {
+        let contract_root = MerkleRoot::from_bytes(contract_root)?;
+        let trie = MPTTrie::from(contract_root, Arc::clone(&db))?;
+
+        contracts.push(ContractSnapshot {
+            address: Address::from_bytes(address)?,
+            entries: trie.iter().collect(),
+        });
+    }
+
+    Ok(StateSnapshot {
+        state_root: state_root.clone(),
+        contracts,
+    })
+}
+
+/// Rebuild the world state of a snapshot in `db`, the state root is verified
+/// after all entries are written. The database should be discarded on error,
+/// the nodes already written are not removed.
+pub fn import_snapshot<DB: TrieDB>(
+    db: Arc<DB>,
+    snapshot: &StateSnapshot,
+) -> ProtocolResult<MerkleRoot> {
+    let mut world = MPTTrie::new(Arc::clone(&db));
+
+    for contract in snapshot.contracts.iter() {
+        let mut trie = MPTTrie::new(Arc::clone(&db));
+        for (key, value) in contract.entries.iter() {
+            trie.insert(key.clone(), value.clone())?;
+        }
+
+        let contract_root = trie.commit()?;
+        world.insert(contract.address.as_bytes(), contract_root.as_bytes())?;
+    }
+
+    let state_root = world.commit()?;
+    if state_root != snapshot.state_root {
+        return Err(SnapshotError::StateRootMismatch {
+            expect: snapshot.state_root.clone(),
+            actual: state_root,
+        }
+        .into());
+    }
+
+    Ok(state_root)
+}
+
+/// Check that the state of the snapshot is the one `epoch` points to, that
+/// the proof in its header is the proof of the previous epoch, and that the
+/// receipts are the ones of its transactions. The signature of the proof is
+/// not verified.
+pub fn verify_snapshot_epoch(
+    epoch: &Epoch,
+    snapshot: &StateSnapshot,
+    receipts: &[Receipt],
+) -> ProtocolResult<()> {
+    let header = &epoch.header;
+    if header.state_root != snapshot.state_root {
+        return Err(SnapshotError::StateRootMismatch {
+            expect: header.state_root.clone(),
+            actual: snapshot.state_root.clone(),
+        }
+        .into());
+    }
+
+    if header.proof.epoch_id != header.epoch_id.saturating_sub(1)
+        || header.proof.epoch_hash != header.pre_hash
+    {
+        return Err(SnapshotError::InvalidProof {
+            epoch_id: header.epoch_id,
+        }
+        .into());
+    }
+
+    // The base fee of the next epoch follows the cycles used by the receipts.
+    // The transactions rejected by the executor have no receipt, so the
+    // receipts follow the order of the epoch with some of them missing.
+    let mut tx_hashes = epoch.ordered_tx_hashes.iter();
+    if !receipts
+        .iter()
+        .all(|receipt| tx_hashes.any(|tx_hash| *tx_hash == receipt.tx_hash))
+    {
+        return Err(SnapshotError::ReceiptsMismatch {
+            epoch_id: header.epoch_id,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+impl rlp::Encodable for StateSnapshot {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        s.append(&self.state_root.as_bytes().to_vec());
+        s.append_list(&self.contracts);
+    }
+}
+
+impl rlp::Decodable for StateSnapshot {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let state_root = MerkleRoot::from_bytes(Bytes::from(r.at(0)?.data()?))
+            .map_err(|_| rlp::DecoderError::Custom("state root"))?;
+        let contracts = r.list_at(1)?;
+
+        Ok(StateSnapshot {
+            state_root,
+            contracts,
+        })
+    }
+}
+
+impl rlp::Encodable for ContractSnapshot {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        s.append(&self.address.as_bytes().to_vec());
+
+        s.begin_list(self.entries.len());
+        for (key, value) in self.entries.iter() {
+            s.begin_list(2);
+            s.append(&key.to_vec());
+            s.append(&value.to_vec());
+        }
+    }
+}
+
+impl rlp::Decodable for ContractSnapshot {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let address = Address::from_bytes(Bytes::from(r.at(0)?.data()?))
+            .map_err(|_| rlp::DecoderError::Custom("contract address"))?;
+
+        let mut entries = vec![];
+        for entry in r.at(1)?.iter() {
+            let key = Bytes::from(entry.at(0)?.data()?);
+            let value = Bytes::from(entry.at(1)?.data()?);
+            entries.push((key, value));
+        }
+
+        Ok(ContractSnapshot { address, entries })
+    }
+}
+
+#[derive(Debug, Display, From)]
+pub enum SnapshotError {
+    #[display(fmt = "read snapshot {:?}", _0)]
+    Io(std::io::Error),
+
+    #[display(fmt = "decode snapshot {:?}", _0)]
+    Decode(rlp::DecoderError),
+
+    #[display(fmt = "state root mismatch, expect {:?}, actual {:?}", expect, actual)]
+    StateRootMismatch {
+        expect: MerkleRoot,
+        actual: MerkleRoot,
+    },
+
+    #[display(fmt = "the proof of epoch {} does not match its header", epoch_id)]
+    InvalidProof { epoch_id: u64 },
+
+    #[display(
+        fmt = "the receipts of epoch {} do not match its transactions",
+        epoch_id
+    )]
+    ReceiptsMismatch { epoch_id: u64 },
+}
+
+impl Error for SnapshotError {}
+
+impl From<SnapshotError> for ProtocolError {
+    fn from(err: SnapshotError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
@@ -1,11 +1,12 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use protocol::traits::executor::contract::AccountContract;
-use protocol::types::{Address, AssetID, Balance, CarryingAsset, Fee};
+use protocol::traits::executor::contract::{AccountContract, NativeContract};
+use protocol::types::{Address, AssetID, Balance, CarryingAsset, ContractAddress, Fee};
 
-use crate::native_contract::NativeAccountContract;
-use crate::tests::{create_state_adapter, mock_invoke_context};
+use crate::native_contract::{NativeAccountContract, ACCOUNT_CONTRACT_ADDRESS};
+use crate::tests::{create_executor, create_state_adapter, mock_invoke_context};
+use crate::ContractDispatcher;
 
 #[test]
 fn test_account_contract() {
@@ -48,3 +49,149 @@ fn test_account_contract() {
     let user2_balance = account.get_balance(&asset, &user2).unwrap();
     assert_eq!(user2_balance, Balance::from(1000u64));
 }
+
+#[test]
+fn test_account_contract_invoke() {
+    let state = Rc::new(RefCell::new(create_state_adapter()));
+    let mut account = NativeAccountContract::new(state);
+
+    let asset =
+        AssetID::from_hex("0000000000000000000000000000000000000000000000000000000000000003")
+            .unwrap();
+    let fee_asset =
+        AssetID::from_hex("0000000000000000000000000000000000000000000000000000000000000004")
+            .unwrap();
+    let user1 = Address::from_hex("100000000000000000000000000000000000000001").unwrap();
+    account
+        .add_balance(&asset, &user1, 10000u64.into())
+        .unwrap();
+
+    let cycles_used = Fee {
+        asset_id: fee_asset.clone(),
+        cycle:    0,
+    };
+    let cycles_limit = Fee {
+        asset_id: fee_asset.clone(),
+        cycle:    1_000_000,
+    };
+    let ctx = mock_invoke_context(user1.clone(), None, cycles_used, cycles_limit);
+    // The queries do not go through the dispatcher.
+    let executor = create_executor();
+    let dispatcher = ContractDispatcher {
+        executor: &executor,
+        contract: ACCOUNT_CONTRACT_ADDRESS.clone(),
+        caller:   user1.clone(),
+    };
+
+    let balance = account
+        .invoke(&dispatcher, Rc::clone(&ctx), "get_balance", vec![
+            asset.as_bytes(),
+            user1.as_bytes(),
+        ])
+        .unwrap();
+    assert_eq!(
+        Balance::from_bytes_be(balance.as_ref()),
+        Balance::from(10000u64)
+    );
+
+    let nonce = account
+        .invoke(&dispatcher, Rc::clone(&ctx), "get_nonce", vec![
+            user1.as_bytes()
+        ])
+        .unwrap();
+    assert_eq!(nonce.to_vec(), 0u64.to_be_bytes().to_vec());
+
+    assert_eq!(
+        account
+            .invoke(&dispatcher, Rc::clone(&ctx), "get_balance", vec![
+                asset.as_bytes()
+            ])
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        account
+            .invoke(&dispatcher, ctx, "not_exists", vec![])
+            .is_err(),
+        true
+    );
+}
+
+#[test]
+fn test_account_contract_approve() {
+    let state = Rc::new(RefCell::new(create_state_adapter()));
+    let mut account = NativeAccountContract::new(state);
+
+    let asset =
+        AssetID::from_hex("0000000000000000000000000000000000000000000000000000000000000003")
+            .unwrap();
+    let fee_asset =
+        AssetID::from_hex("0000000000000000000000000000000000000000000000000000000000000004")
+            .unwrap();
+    let user1 = Address::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let user2 = Address::from_hex("100000000000000000000000000000000000000002").unwrap();
+    let spender = ContractAddress::from_hex("210000000000000000000000000000000000000001").unwrap();
+    account
+        .add_balance(&asset, &user1, 10000u64.into())
+        .unwrap();
+
+    let cycles_used = Fee {
+        asset_id: fee_asset.clone(),
+        cycle:    0,
+    };
+    let cycles_limit = Fee {
+        asset_id: fee_asset.clone(),
+        cycle:    1_000_000,
+    };
+    let user_ctx = mock_invoke_context(
+        user1.clone(),
+        None,
+        cycles_used.clone(),
+        cycles_limit.clone(),
+    );
+    account
+        .approve(Rc::clone(&user_ctx), &spender, &asset, 1000u64.into())
+        .unwrap();
+
+    let spender_ctx = mock_invoke_context(
+        Address::Contract(spender.clone()),
+        None,
+        cycles_used,
+        cycles_limit,
+    );
+    account
+        .transfer_from(
+            Rc::clone(&spender_ctx),
+            &user1,
+            &user2,
+            &asset,
+            600u64.into(),
+        )
+        .unwrap();
+    assert_eq!(
+        account.get_balance(&asset, &user1).unwrap(),
+        Balance::from(9400u64)
+    );
+    assert_eq!(
+        account.get_balance(&asset, &user2).unwrap(),
+        Balance::from(600u64)
+    );
+
+    let allowance = account.get_allowance(&asset, &user1, &spender).unwrap();
+    assert_eq!(allowance.max, Balance::from(1000u64));
+    assert_eq!(allowance.used, Balance::from(600u64));
+
+    // exceed the allowance
+    let res = account.transfer_from(
+        Rc::clone(&spender_ctx),
+        &user1,
+        &user2,
+        &asset,
+        500u64.into(),
+    );
+    assert_eq!(res.is_err(), true);
+
+    // a user can not spend on behalf of others
+    let res = account.transfer_from(user_ctx, &user1, &user2, &asset, 100u64.into());
+    assert_eq!(res.is_err(), true);
+}
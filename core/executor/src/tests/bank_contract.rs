@@ -3,7 +3,7 @@ use std::rc::Rc;
 
 use protocol::traits::executor::contract::BankContract;
 use protocol::traits::executor::InvokeContext;
-use protocol::types::{Address, AssetID, Balance, ContractAddress, Fee, Hash};
+use protocol::types::{Address, AssetID, Balance, ContractAddress, DeployAsset, Fee, Hash};
 
 use crate::native_contract::NativeBankContract;
 use crate::tests::{create_state_adapter, mock_invoke_context};
@@ -28,30 +28,31 @@ fn test_bank_contract() {
         cycle:    1_000_000,
     };
     let ctx = mock_invoke_context(caller, None, cycles_used, cycles_limit);
-    let name = "Muta token".to_owned();
-    let symbol = "MTT".to_owned();
-    let supply = Balance::from(1e18 as u64);
+    let deploy_asset = DeployAsset {
+        name:            "Muta token".to_owned(),
+        symbol:          "MTT".to_owned(),
+        supply:          Balance::from(1e18 as u64),
+        decimals:        18,
+        manage_contract: None,
+    };
     let asset = bank
         .register(
             Rc::<RefCell<InvokeContext>>::clone(&ctx),
             &address,
-            name.clone(),
-            symbol.clone(),
-            supply.clone(),
+            &deploy_asset,
         )
         .unwrap();
-    assert_eq!(&asset.symbol, &symbol);
-    assert_eq!(&asset.name, &name);
-    assert_eq!(&asset.supply, &supply);
+    assert_eq!(&asset.symbol, &deploy_asset.symbol);
+    assert_eq!(&asset.name, &deploy_asset.name);
+    assert_eq!(&asset.supply, &deploy_asset.supply);
+    assert_eq!(asset.decimals, 18);
     assert_eq!(&asset.manage_contract, &address);
 
     // use the same address to register
     let asset2 = bank.register(
         Rc::<RefCell<InvokeContext>>::clone(&ctx),
         &address,
-        name.clone(),
-        symbol.clone(),
-        supply.clone(),
+        &deploy_asset,
     );
     assert_eq!(asset2.is_err(), true);
 
@@ -61,3 +62,62 @@ fn test_bank_contract() {
         .unwrap();
     assert_eq!(&asset, &asset_get);
 }
+
+#[test]
+fn test_mint_and_burn() {
+    let chain_id = Hash::from_empty();
+    let address = ContractAddress::from_hex("200000000000000000000000000000000000000000").unwrap();
+    let manager = ContractAddress::from_hex("210000000000000000000000000000000000000001").unwrap();
+    let state = Rc::new(RefCell::new(create_state_adapter()));
+    let mut bank = NativeBankContract::new(chain_id, state);
+    let fee = Fee {
+        asset_id: Hash::from_empty(),
+        cycle:    0,
+    };
+    let cycles_limit = Fee {
+        asset_id: Hash::from_empty(),
+        cycle:    1_000_000,
+    };
+
+    let ctx = mock_invoke_context(
+        Address::Contract(manager.clone()),
+        None,
+        fee.clone(),
+        cycles_limit.clone(),
+    );
+    let asset = bank
+        .register(Rc::clone(&ctx), &address, &DeployAsset {
+            name:            "Muta token".to_owned(),
+            symbol:          "MTT".to_owned(),
+            supply:          Balance::from(100u64),
+            decimals:        8,
+            manage_contract: Some(manager),
+        })
+        .unwrap();
+
+    let minted = bank
+        .mint(Rc::clone(&ctx), &asset.id, Balance::from(50u64))
+        .unwrap();
+    assert_eq!(minted.supply, Balance::from(150u64));
+
+    let burned = bank
+        .burn(Rc::clone(&ctx), &asset.id, Balance::from(120u64))
+        .unwrap();
+    assert_eq!(burned.supply, Balance::from(30u64));
+    assert!(bank
+        .burn(Rc::clone(&ctx), &asset.id, Balance::from(31u64))
+        .is_err());
+
+    // Only the manage contract can change the supply.
+    let other = mock_invoke_context(
+        Address::from_hex("230000000000000000000000000000000000000010").unwrap(),
+        None,
+        fee,
+        cycles_limit,
+    );
+    assert!(bank.mint(other, &asset.id, Balance::from(1u64)).is_err());
+    assert_eq!(
+        bank.get_asset(ctx, &asset.id).unwrap().supply,
+        Balance::from(30u64)
+    );
+}
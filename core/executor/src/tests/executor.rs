@@ -0,0 +1,1021 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use protocol::traits::executor::contract::{AccountContract, BankContract};
+use protocol::traits::executor::{Dispatcher, Executor, TraceEvent};
+use protocol::types::{
+    Address, Balance, BloomInput, CarryingAsset, ContractAddress, ContractType, CyclesSchedule,
+    DeployAsset, Fee, Genesis, GenesisCyclesSchedule, Hash, MerkleRoot, Receipt, ReceiptResult,
+    SignedTransaction, TransactionAction, UserAddress,
+};
+use protocol::ProtocolResult;
+
+use crate::native_contract::{ACCOUNT_CONTRACT_ADDRESS, BANK_CONTRACT_ADDRESS};
+use crate::parallel;
+use crate::tests::{
+    create_empty_memdb, create_executor, exec_one, mock_asset_id, mock_genesis,
+    mock_invoke_context, mock_pubkey, mock_signed_tx, COINBASE,
+};
+use crate::{verify_balance_proof, TransactionExecutor, TransactionExecutorError};
+
+use test::Bencher;
+
+// The receipt fails with the error `name`.
+fn is_fail(receipt: &Receipt, name: &str) -> bool {
+    match &receipt.result {
+        ReceiptResult::Fail { system, .. } => system.contains(name),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_charge_fee() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+
+    let action = TransactionAction::Transfer {
+        receiver:       receiver.clone(),
+        carrying_asset: CarryingAsset {
+            asset_id: asset_id.clone(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let resp = executor.exec(vec![mock_signed_tx(action)]).unwrap();
+    let receipt = &resp.receipts[0];
+    assert!(match receipt.result {
+        ReceiptResult::Transfer { .. } => true,
+        _ => false,
+    });
+
+    // cycles price is 1
+    let fee = Balance::from(receipt.cycles_used.cycle);
+    assert!(fee > Balance::from(0u64));
+
+    let account_contract = executor.account_contract.borrow();
+    assert_eq!(
+        account_contract.get_balance(&asset_id, &sender).unwrap(),
+        Balance::from(0xff_ffffu64) - Balance::from(100u64) - fee.clone()
+    );
+    assert_eq!(
+        account_contract
+            .get_balance(&asset_id, &Address::User(receiver))
+            .unwrap(),
+        Balance::from(100u64)
+    );
+    assert_eq!(
+        account_contract.get_balance(&asset_id, &coinbase).unwrap(),
+        fee
+    );
+}
+
+#[test]
+fn test_insufficient_fee() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let action = TransactionAction::Transfer {
+        receiver,
+        carrying_asset: CarryingAsset {
+            asset_id: asset_id.clone(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let mut signed_tx = mock_signed_tx(action);
+    signed_tx.raw.fee.cycle = 0x0100_0000;
+    executor.cycles_schedule.base = 21;
+
+    // The intrinsic cycles are charged instead of the cycles limit, and the
+    // nonce is used up.
+    let resp = executor.exec(vec![signed_tx.clone()]).unwrap();
+    assert_eq!(resp.receipts.len(), 1);
+    assert!(is_fail(&resp.receipts[0], "InsufficientFee"));
+    assert_eq!(resp.receipts[0].cycles_used.cycle, 21);
+    assert_eq!(
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &sender)
+            .unwrap(),
+        Balance::from(0xff_ffffu64 - 21)
+    );
+    let err = executor.check_tx(&signed_tx).unwrap_err();
+    assert!(err.to_string().contains("ReusedNonce"));
+}
+
+#[test]
+fn test_cycles_price_too_low() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let action = TransactionAction::Transfer {
+        receiver,
+        carrying_asset: CarryingAsset {
+            asset_id: asset_id.clone(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let mut signed_tx = mock_signed_tx(action);
+    signed_tx.raw.max_cycles_price = 0;
+
+    // Rejected with a failed receipt.
+    let resp = executor.exec(vec![signed_tx.clone()]).unwrap();
+    assert!(is_fail(&resp.receipts[0], "CyclesPriceTooLow"));
+    assert!(executor.check_tx(&signed_tx).is_err());
+    assert_eq!(
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &sender)
+            .unwrap(),
+        Balance::from(0xff_ffffu64)
+    );
+}
+
+#[test]
+fn test_replay_protection() {
+    let mut executor = create_executor();
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let action = TransactionAction::Transfer {
+        receiver,
+        carrying_asset: CarryingAsset {
+            asset_id: mock_asset_id(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let signed_tx = mock_signed_tx(action);
+
+    // The replayed transaction is rejected with a failed receipt, the
+    // proposals containing it are rejected by `check_tx` beforehand.
+    assert!(executor.check_tx(&signed_tx).is_ok());
+    let resp = executor
+        .exec(vec![signed_tx.clone(), signed_tx.clone()])
+        .unwrap();
+    assert_eq!(resp.receipts.len(), 2);
+    assert!(match resp.receipts[0].result {
+        ReceiptResult::Transfer { .. } => true,
+        _ => false,
+    });
+    assert!(is_fail(&resp.receipts[1], "ReusedNonce"));
+    assert!(executor.check_tx(&signed_tx).is_err());
+    let receipt = executor
+        .simulate(signed_tx.sender().unwrap(), signed_tx.clone())
+        .unwrap();
+    assert!(is_fail(&receipt, "ReusedNonce"));
+
+    // Replay in another epoch
+    let resp = executor.exec(vec![signed_tx.clone()]).unwrap();
+    assert!(is_fail(&resp.receipts[0], "ReusedNonce"));
+
+    let mut timeout_tx = signed_tx;
+    timeout_tx.raw.nonce = Hash::digest(Bytes::from("timeout"));
+    timeout_tx.raw.timeout = 0;
+    assert!(executor.check_tx(&timeout_tx).is_err());
+    let resp = executor.exec(vec![timeout_tx.clone()]).unwrap();
+    assert!(is_fail(&resp.receipts[0], "Timeout"));
+    let receipt = executor
+        .simulate(timeout_tx.sender().unwrap(), timeout_tx)
+        .unwrap();
+    assert!(is_fail(&receipt, "Timeout"));
+}
+
+#[test]
+fn test_prune_nonces() {
+    let db = create_empty_memdb();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+    let executor_at = |epoch_id: u64, state_root: MerkleRoot| {
+        TransactionExecutor::new(
+            Hash::from_empty(),
+            state_root,
+            Arc::clone(&db),
+            epoch_id,
+            1,
+            coinbase.clone(),
+            false,
+        )
+        .unwrap()
+    };
+
+    let mut executor = executor_at(1, Hash::from_empty());
+    let root = executor.create_genesis(&mock_genesis()).unwrap();
+
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let transfer = |timeout: u64| {
+        let mut signed_tx = mock_signed_tx(TransactionAction::Transfer {
+            receiver:       receiver.clone(),
+            carrying_asset: CarryingAsset {
+                asset_id: mock_asset_id(),
+                amount:   Balance::from(100u64),
+            },
+        });
+        signed_tx.raw.timeout = timeout;
+        signed_tx
+    };
+    let (short, long) = (transfer(1), transfer(2));
+
+    let mut executor = executor_at(1, root);
+    let resp = executor.exec(vec![short.clone(), long.clone()]).unwrap();
+    assert_eq!(resp.receipts.len(), 2);
+    let contains_nonce = |root: &MerkleRoot, epoch_id: u64, signed_tx: &SignedTransaction| {
+        executor_at(epoch_id, root.clone())
+            .account_contract
+            .borrow()
+            .contains_nonce(&sender, &signed_tx.raw.nonce)
+            .unwrap()
+    };
+    assert!(contains_nonce(&resp.state_root, 2, &short));
+    assert!(contains_nonce(&resp.state_root, 2, &long));
+
+    // The nonce expiring at epoch 1 is pruned by epoch 2, the other one is
+    // pruned by epoch 3.
+    let resp = executor_at(2, resp.state_root).exec(vec![]).unwrap();
+    assert!(!contains_nonce(&resp.state_root, 3, &short));
+    assert!(contains_nonce(&resp.state_root, 3, &long));
+
+    let resp = executor_at(3, resp.state_root).exec(vec![]).unwrap();
+    assert!(!contains_nonce(&resp.state_root, 4, &long));
+}
+
+fn create_genesis(genesis: &Genesis) -> ProtocolResult<MerkleRoot> {
+    TransactionExecutor::new(
+        genesis.chain_id()?,
+        Hash::from_empty(),
+        create_empty_memdb(),
+        0,
+        1,
+        Address::from_hex(COINBASE).unwrap(),
+        false,
+    )?
+    .create_genesis(genesis)
+}
+
+#[test]
+fn test_genesis_supply() {
+    // The genesis of the dev chain allocates exactly the supply.
+    let genesis: Genesis =
+        serde_json::from_str(include_str!("../../../../devtools/chain/genesis.json")).unwrap();
+    create_genesis(&genesis).unwrap();
+
+    let mut genesis = mock_genesis();
+    create_genesis(&genesis).unwrap();
+    genesis.system_token.supply += 1;
+    assert!(create_genesis(&genesis).is_err());
+}
+
+#[test]
+fn test_genesis_supply_mismatch() {
+    let supply_mismatch = |genesis: &Genesis| {
+        let err = create_genesis(genesis).unwrap_err();
+        match err.downcast_ref::<TransactionExecutorError>() {
+            Some(TransactionExecutorError::GenesisSupplyMismatch { supply, allocated }) => {
+                Some((supply.clone(), allocated.clone()))
+            }
+            _ => None,
+        }
+    };
+    let allocated = Balance::from(0x0100_0063u64);
+
+    // A supply above the allocation leaves room to mint what is never
+    // allocated, one below it can't account for the allocated balances.
+    let mut genesis = mock_genesis();
+    genesis.system_token.supply += 1;
+    assert_eq!(
+        supply_mismatch(&genesis),
+        Some((Balance::from(0x0100_0064u64), allocated.clone()))
+    );
+    genesis.system_token.supply -= 2;
+    assert_eq!(
+        supply_mismatch(&genesis),
+        Some((Balance::from(0x0100_0062u64), allocated))
+    );
+
+    // Nothing is allocated to a genesis without the allocations.
+    let mut genesis = mock_genesis();
+    genesis.state_alloc.clear();
+    assert_eq!(
+        supply_mismatch(&genesis),
+        Some((Balance::from(0x0100_0063u64), Balance::from(0u64)))
+    );
+}
+
+#[test]
+fn test_transfer_logs() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let action = TransactionAction::Transfer {
+        receiver,
+        carrying_asset: CarryingAsset {
+            asset_id: asset_id.clone(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let resp = executor.exec(vec![mock_signed_tx(action)]).unwrap();
+
+    let logs = &resp.receipts[0].logs;
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].topics, vec![
+        Hash::digest(Bytes::from("Transfer")),
+        asset_id.clone()
+    ]);
+    assert!(resp
+        .logs_bloom
+        .contains_input(BloomInput::Raw(asset_id.as_bytes().as_ref())));
+
+    // The events of a failed transaction are discarded.
+    let action = TransactionAction::Transfer {
+        receiver:       UserAddress::from_hex("100000000000000000000000000000000000000001")
+            .unwrap(),
+        carrying_asset: CarryingAsset {
+            asset_id,
+            amount: Balance::from(0x0100_0000u64),
+        },
+    };
+    let resp = executor.exec(vec![mock_signed_tx(action)]).unwrap();
+    assert!(resp.receipts[0].logs.is_empty());
+    assert!(resp.logs_bloom.is_empty());
+}
+
+#[test]
+fn test_simulate() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let action = TransactionAction::Transfer {
+        receiver,
+        carrying_asset: CarryingAsset {
+            asset_id: asset_id.clone(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let signed_tx = mock_signed_tx(action);
+
+    let receipt = executor
+        .simulate(signed_tx.sender().unwrap(), signed_tx.clone())
+        .unwrap();
+    assert!(match receipt.result {
+        ReceiptResult::Transfer { .. } => true,
+        _ => false,
+    });
+
+    // The sender is given instead of the public key, and the cycles limit is
+    // not reserved, so the sender unable to pay for it is simulated as well.
+    let mut unsigned_tx = signed_tx.clone();
+    unsigned_tx.pubkey = Bytes::new();
+    unsigned_tx.raw.fee.cycle = u64::max_value();
+    let unsigned_receipt = executor
+        .simulate(signed_tx.sender().unwrap(), unsigned_tx)
+        .unwrap();
+    assert!(match unsigned_receipt.result {
+        ReceiptResult::Transfer { .. } => true,
+        _ => false,
+    });
+    assert_eq!(
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &sender)
+            .unwrap(),
+        Balance::from(0xff_ffffu64)
+    );
+
+    // The simulation reports the same cycles as the real execution.
+    let resp = executor.exec(vec![signed_tx]).unwrap();
+    assert_eq!(resp.receipts[0].cycles_used, receipt.cycles_used);
+}
+
+#[test]
+fn test_trace() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let signed_txs = (0..2)
+        .map(|_| {
+            mock_signed_tx(TransactionAction::Transfer {
+                receiver:       receiver.clone(),
+                carrying_asset: CarryingAsset {
+                    asset_id: asset_id.clone(),
+                    amount:   Balance::from(100u64),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Only the last transaction is traced, the first one is executed before it.
+    let trace = executor.trace(signed_txs.clone()).unwrap();
+    assert!(match trace.receipt.result {
+        ReceiptResult::Transfer {
+            ref before_amount, ..
+        } => *before_amount < Balance::from(0xff_ffffu64),
+        _ => false,
+    });
+    assert!(trace.events.contains(&TraceEvent::Invoke {
+        address: ACCOUNT_CONTRACT_ADDRESS.clone(),
+        method:  "transfer".to_owned(),
+        args:    vec![Address::User(receiver).as_bytes()],
+    }));
+    assert!(trace.events.contains(&TraceEvent::Cycles {
+        action: "AccountTransfer".to_owned(),
+        cycles: CyclesSchedule::default().transfer,
+    }));
+    assert!(trace.events.iter().any(|event| match event {
+        TraceEvent::StateWrite {
+            address, old_value, ..
+        } => *address == *ACCOUNT_CONTRACT_ADDRESS && old_value.is_some(),
+        _ => false,
+    }));
+
+    // Nothing is changed by the trace.
+    assert_eq!(
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &sender)
+            .unwrap(),
+        Balance::from(0xff_ffffu64)
+    );
+
+    let resp = executor.exec(signed_txs).unwrap();
+    assert_eq!(resp.receipts[1].cycles_used, trace.receipt.cycles_used);
+}
+
+#[test]
+fn test_intermediate_state_root() {
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let signed_txs = (0..2)
+        .map(|_| {
+            mock_signed_tx(TransactionAction::Transfer {
+                receiver:       receiver.clone(),
+                carrying_asset: CarryingAsset {
+                    asset_id: mock_asset_id(),
+                    amount:   Balance::from(100u64),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut executor = create_executor();
+    executor.intermediate_state_root = true;
+    let resp = executor.exec(signed_txs.clone()).unwrap();
+    assert_ne!(resp.receipts[0].state_root, resp.receipts[1].state_root);
+    assert_eq!(resp.receipts[1].state_root, resp.state_root);
+
+    // The final state root does not depend on the option.
+    let mut executor = create_executor();
+    let expect = executor.exec(signed_txs).unwrap();
+    assert_eq!(expect.state_root, resp.state_root);
+    assert_eq!(expect.receipts[0].state_root, expect.state_root);
+}
+
+#[test]
+fn test_parallel_exec() {
+    let transfer = |pubkey: u8, receiver: &str, amount: u64| {
+        let action = TransactionAction::Transfer {
+            receiver:       UserAddress::from_hex(receiver).unwrap(),
+            carrying_asset: CarryingAsset {
+                asset_id: mock_asset_id(),
+                amount:   Balance::from(amount),
+            },
+        };
+        let mut signed_tx = mock_signed_tx(action);
+        signed_tx.pubkey = Bytes::from(vec![pubkey; 33]);
+        signed_tx
+    };
+    let user = |pubkey: u8| {
+        UserAddress::from_pubkey_bytes(Bytes::from(vec![pubkey; 33]))
+            .unwrap()
+            .as_hex()
+    };
+
+    // Fund the senders of the next epoch, all transfers share the sender so
+    // they are in the same group.
+    let fund_txs = (2..5)
+        .map(|pubkey| transfer(1, &user(pubkey), 0x20_0000))
+        .collect::<Vec<_>>();
+    // Three independent groups, the last one fails because of the
+    // insufficient balance.
+    let signed_txs = vec![
+        transfer(2, "100000000000000000000000000000000000000001", 100),
+        transfer(3, "100000000000000000000000000000000000000002", 100),
+        transfer(2, "100000000000000000000000000000000000000001", 100),
+        transfer(4, "100000000000000000000000000000000000000003", 0x20_0000),
+    ];
+    assert_eq!(
+        parallel::partition(&signed_txs, &Address::from_hex(COINBASE).unwrap())
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let mut executor = create_executor();
+    executor.exec(fund_txs.clone()).unwrap();
+    let resp = executor.exec(signed_txs.clone()).unwrap();
+
+    // Committing after every transaction forces the sequential execution.
+    let mut sequential = create_executor();
+    sequential.intermediate_state_root = true;
+    sequential.exec(fund_txs).unwrap();
+    let expect = sequential.exec(signed_txs).unwrap();
+
+    assert_eq!(resp.state_root, expect.state_root);
+    for (receipt, expect) in resp.receipts.iter().zip(expect.receipts.iter()) {
+        assert_eq!(receipt.tx_hash, expect.tx_hash);
+        assert_eq!(receipt.cycles_used, expect.cycles_used);
+    }
+    assert!(match resp.receipts[3].result {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_parallel_conflicts() {
+    let keys = |keys: &[&str]| {
+        keys.iter()
+            .map(|key| key.to_string())
+            .collect::<HashSet<_>>()
+    };
+
+    // The first group writes the key read by the second one, the third one
+    // reads and writes its own keys. Reading the same key is no conflict.
+    let accesses = vec![
+        (keys(&["a", "shared"]), keys(&["a", "b"])),
+        (keys(&["b", "shared"]), keys(&["c"])),
+        (keys(&["d", "shared"]), keys(&["d"])),
+    ];
+    assert_eq!(parallel::find_conflicts(&accesses), vec![true, true, false]);
+
+    // Two groups writing the same key conflict.
+    let accesses = vec![
+        (keys(&[]), keys(&["a"])),
+        (keys(&[]), keys(&["a"])),
+        (keys(&[]), keys(&["b"])),
+    ];
+    assert_eq!(parallel::find_conflicts(&accesses), vec![true, true, false]);
+}
+
+#[test]
+fn test_parallel_exec_non_transfer() {
+    let transfer = |receiver: &str, amount: u64| TransactionAction::Transfer {
+        receiver:       UserAddress::from_hex(receiver).unwrap(),
+        carrying_asset: CarryingAsset {
+            asset_id: mock_asset_id(),
+            amount:   Balance::from(amount),
+        },
+    };
+    let signed_tx = |pubkey: u8, action: TransactionAction| {
+        let mut signed_tx = mock_signed_tx(action);
+        signed_tx.pubkey = Bytes::from(vec![pubkey; 33]);
+        signed_tx
+    };
+    let user = |pubkey: u8| {
+        UserAddress::from_pubkey_bytes(Bytes::from(vec![pubkey; 33]))
+            .unwrap()
+            .as_hex()
+    };
+
+    let fund_txs = (2..4)
+        .map(|pubkey| signed_tx(1, transfer(&user(pubkey), 0x20_0000)))
+        .collect::<Vec<_>>();
+    // The transfers alone are two independent groups, the batch touches the
+    // receiver of the first one, which is unknown before its execution.
+    let transfers = vec![
+        signed_tx(
+            2,
+            transfer("100000000000000000000000000000000000000001", 100),
+        ),
+        signed_tx(
+            3,
+            transfer("100000000000000000000000000000000000000002", 100),
+        ),
+    ];
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+    assert_eq!(parallel::partition(&transfers, &coinbase).unwrap().len(), 2);
+
+    let mut signed_txs = transfers;
+    signed_txs.push(signed_tx(3, TransactionAction::Batch {
+        actions: vec![transfer("100000000000000000000000000000000000000001", 100)],
+    }));
+    assert!(parallel::partition(&signed_txs, &coinbase).is_none());
+
+    let mut executor = create_executor();
+    executor.exec(fund_txs.clone()).unwrap();
+    let resp = executor.exec(signed_txs.clone()).unwrap();
+
+    let mut sequential = create_executor();
+    sequential.intermediate_state_root = true;
+    sequential.exec(fund_txs).unwrap();
+    let expect = sequential.exec(signed_txs).unwrap();
+
+    assert_eq!(resp.state_root, expect.state_root);
+    assert!(match resp.receipts[2].result {
+        ReceiptResult::Batch { .. } => true,
+        _ => false,
+    });
+    assert_eq!(
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(
+                &mock_asset_id(),
+                &Address::from_hex("100000000000000000000000000000000000000001").unwrap()
+            )
+            .unwrap(),
+        Balance::from(200u64)
+    );
+}
+
+#[test]
+fn test_balance_proof() {
+    let mut executor = create_executor();
+    let action = TransactionAction::Transfer {
+        receiver:       UserAddress::from_hex("100000000000000000000000000000000000000001")
+            .unwrap(),
+        carrying_asset: CarryingAsset {
+            asset_id: mock_asset_id(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let resp = executor.exec(vec![mock_signed_tx(action)]).unwrap();
+
+    let receiver = Address::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let proof = executor
+        .get_balance_proof(&receiver, &mock_asset_id())
+        .unwrap();
+    assert_eq!(proof.state_root, resp.state_root);
+    assert_eq!(
+        verify_balance_proof(&resp.state_root, &receiver, &mock_asset_id(), &proof).unwrap(),
+        Balance::from(100u64)
+    );
+
+    // The proof can not be used for another account.
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    assert!(verify_balance_proof(&resp.state_root, &sender, &mock_asset_id(), &proof).is_err());
+
+    // The balance of an account that does not exist is proved to be zero.
+    let unknown = Address::from_hex("100000000000000000000000000000000000000009").unwrap();
+    let proof = executor
+        .get_balance_proof(&unknown, &mock_asset_id())
+        .unwrap();
+    assert_eq!(
+        verify_balance_proof(&resp.state_root, &unknown, &mock_asset_id(), &proof).unwrap(),
+        Balance::from(0u64)
+    );
+
+    // A forged balance is rejected.
+    let mut forged = proof.clone();
+    forged.balance = Balance::from(1u64);
+    assert!(verify_balance_proof(&resp.state_root, &unknown, &mock_asset_id(), &forged).is_err());
+}
+
+#[test]
+fn test_cycles_schedule() {
+    // The state bytes are free, so only the schedules of the actions count.
+    let mut genesis = mock_genesis();
+    genesis.cycles_schedules = vec![
+        GenesisCyclesSchedule {
+            epoch_id: 5,
+            schedule: CyclesSchedule {
+                base: 100,
+                state_byte: 0,
+                transfer: 5000,
+                ..CyclesSchedule::default()
+            },
+        },
+        GenesisCyclesSchedule {
+            epoch_id: 0,
+            schedule: CyclesSchedule {
+                state_byte: 0,
+                transfer: 1000,
+                ..CyclesSchedule::default()
+            },
+        },
+    ];
+
+    let transfer = || {
+        mock_signed_tx(TransactionAction::Transfer {
+            receiver:       UserAddress::from_hex("100000000000000000000000000000000000000001")
+                .unwrap(),
+            carrying_asset: CarryingAsset {
+                asset_id: mock_asset_id(),
+                amount:   Balance::from(100u64),
+            },
+        })
+    };
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+    let db = create_empty_memdb();
+
+    let mut executor = TransactionExecutor::new(
+        Hash::from_empty(),
+        Hash::from_empty(),
+        Arc::clone(&db),
+        1,
+        1,
+        coinbase.clone(),
+        false,
+    )
+    .unwrap();
+    let state_root = executor.create_genesis(&genesis).unwrap();
+
+    let resp = executor.exec(vec![transfer()]).unwrap();
+    assert_eq!(resp.receipts[0].cycles_used.cycle, 1000);
+
+    // The later schedule takes effect from its epoch.
+    let mut executor =
+        TransactionExecutor::new(Hash::from_empty(), state_root, db, 5, 1, coinbase, false)
+            .unwrap();
+    let resp = executor.exec(vec![transfer()]).unwrap();
+    assert_eq!(resp.receipts[0].cycles_used.cycle, 5100);
+}
+
+#[test]
+fn test_deploy_asset() {
+    let mut executor = create_executor();
+    let sender = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let manager = ContractAddress::from_hex("210000000000000000000000000000000000000001").unwrap();
+
+    // The asset contract requires the metadata.
+    let action = TransactionAction::Deploy {
+        code:          Bytes::from("asset"),
+        contract_type: ContractType::Asset,
+        asset:         None,
+    };
+    match exec_one(&mut executor, action) {
+        ReceiptResult::Fail { .. } => (),
+        res => panic!("deploy should fail {:?}", res),
+    }
+
+    let action = TransactionAction::Deploy {
+        code:          Bytes::from("asset"),
+        contract_type: ContractType::Asset,
+        asset:         Some(DeployAsset {
+            name:            "Test token".to_owned(),
+            symbol:          "TT".to_owned(),
+            supply:          Balance::from(1000u64),
+            decimals:        8,
+            manage_contract: Some(manager.clone()),
+        }),
+    };
+    let address = match exec_one(&mut executor, action) {
+        ReceiptResult::Deploy { contract, .. } => contract,
+        res => panic!("deploy failed {:?}", res),
+    };
+
+    let ictx = mock_invoke_context(
+        Address::Contract(manager),
+        None,
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    0,
+        },
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    1_000_000,
+        },
+    );
+    let bank = ContractAddress::from_bytes(BANK_CONTRACT_ADDRESS.as_bytes()).unwrap();
+    let asset_id = Hash::digest(Bytes::from(
+        [Hash::from_empty().as_bytes(), address.as_bytes()].concat(),
+    ));
+    let asset = executor
+        .bank_account
+        .borrow()
+        .get_asset(Rc::clone(&ictx), &asset_id)
+        .unwrap();
+    assert_eq!(asset.name, "Test token");
+    assert_eq!(asset.decimals, 8);
+
+    // The initial supply is credited to the deployer.
+    let balance = |executor: &TransactionExecutor<_>, address: &Address| {
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, address)
+            .unwrap()
+    };
+    assert_eq!(balance(&executor, &sender), Balance::from(1000u64));
+
+    executor
+        .invoke(Rc::clone(&ictx), bank.clone(), "mint", vec![
+            asset_id.as_bytes(),
+            sender.as_bytes(),
+            Bytes::from(Balance::from(500u64).to_bytes_be()),
+        ])
+        .unwrap();
+    assert_eq!(balance(&executor, &sender), Balance::from(1500u64));
+
+    // The manage contract burns from its own balance.
+    let manager = ictx.borrow().caller.clone();
+    assert!(executor
+        .invoke(Rc::clone(&ictx), bank.clone(), "burn", vec![
+            asset_id.as_bytes(),
+            Bytes::from(Balance::from(1u64).to_bytes_be()),
+        ])
+        .is_err());
+    executor
+        .invoke(Rc::clone(&ictx), bank.clone(), "mint", vec![
+            asset_id.as_bytes(),
+            manager.as_bytes(),
+            Bytes::from(Balance::from(200u64).to_bytes_be()),
+        ])
+        .unwrap();
+    executor
+        .invoke(Rc::clone(&ictx), bank, "burn", vec![
+            asset_id.as_bytes(),
+            Bytes::from(Balance::from(150u64).to_bytes_be()),
+        ])
+        .unwrap();
+    assert_eq!(balance(&executor, &manager), Balance::from(50u64));
+
+    let asset = executor
+        .bank_account
+        .borrow()
+        .get_asset(ictx, &asset_id)
+        .unwrap();
+    assert_eq!(asset.supply, Balance::from(1550u64));
+}
+
+#[test]
+fn test_burn_insufficient_balance() {
+    let mut executor = create_executor();
+    let manager = ContractAddress::from_hex("210000000000000000000000000000000000000001").unwrap();
+    let mut deploy = |code: &str| {
+        let action = TransactionAction::Deploy {
+            code:          Bytes::from(code),
+            contract_type: ContractType::Asset,
+            asset:         Some(DeployAsset {
+                name:            code.to_owned(),
+                symbol:          "TT".to_owned(),
+                supply:          Balance::from(1000u64),
+                decimals:        8,
+                manage_contract: Some(manager.clone()),
+            }),
+        };
+        match exec_one(&mut executor, action) {
+            ReceiptResult::Deploy { contract, .. } => Hash::digest(Bytes::from(
+                [Hash::from_empty().as_bytes(), contract.as_bytes()].concat(),
+            )),
+            res => panic!("deploy failed {:?}", res),
+        }
+    };
+    let held = deploy("held asset");
+    let unheld = deploy("unheld asset");
+
+    let ictx = mock_invoke_context(
+        Address::Contract(manager.clone()),
+        None,
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    0,
+        },
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    1_000_000,
+        },
+    );
+    let bank = ContractAddress::from_bytes(BANK_CONTRACT_ADDRESS.as_bytes()).unwrap();
+    let burn = |executor: &TransactionExecutor<_>, asset_id: &Hash, amount: u64| {
+        executor.invoke(Rc::clone(&ictx), bank.clone(), "burn", vec![
+            asset_id.as_bytes(),
+            Bytes::from(Balance::from(amount).to_bytes_be()),
+        ])
+    };
+    let supply = |executor: &TransactionExecutor<_>, asset_id: &Hash| {
+        executor
+            .bank_account
+            .borrow()
+            .get_asset(Rc::clone(&ictx), asset_id)
+            .unwrap()
+            .supply
+    };
+
+    executor
+        .invoke(Rc::clone(&ictx), bank.clone(), "mint", vec![
+            held.as_bytes(),
+            Address::Contract(manager.clone()).as_bytes(),
+            Bytes::from(Balance::from(100u64).to_bytes_be()),
+        ])
+        .unwrap();
+
+    // More than the balance of the manage contract.
+    assert!(burn(&executor, &held, 101).is_err());
+    assert_eq!(
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&held, &Address::Contract(manager.clone()))
+            .unwrap(),
+        Balance::from(100u64)
+    );
+    assert_eq!(supply(&executor, &held), Balance::from(1100u64));
+
+    // An asset the manage contract holds none of.
+    assert!(burn(&executor, &unheld, 1).is_err());
+    assert_eq!(supply(&executor, &unheld), Balance::from(1000u64));
+
+    burn(&executor, &held, 100).unwrap();
+    assert_eq!(supply(&executor, &held), Balance::from(1000u64));
+}
+
+#[test]
+fn test_batch() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let a = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let b = UserAddress::from_hex("100000000000000000000000000000000000000002").unwrap();
+    let transfer = |receiver: &UserAddress, amount: u64| TransactionAction::Transfer {
+        receiver:       receiver.clone(),
+        carrying_asset: CarryingAsset {
+            asset_id: asset_id.clone(),
+            amount:   Balance::from(amount),
+        },
+    };
+    let balance = |executor: &TransactionExecutor<_>, address: &UserAddress| {
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &Address::User(address.clone()))
+            .unwrap()
+    };
+
+    let result = exec_one(&mut executor, TransactionAction::Batch {
+        actions: vec![transfer(&a, 100), transfer(&b, 200)],
+    });
+    match result {
+        ReceiptResult::Batch { results } => {
+            assert_eq!(results.len(), 2);
+            assert!(match &results[1] {
+                ReceiptResult::Transfer { receiver, .. } => *receiver == b,
+                _ => false,
+            });
+        }
+        _ => panic!("batch failed"),
+    }
+    assert_eq!(balance(&executor, &a), Balance::from(100u64));
+    assert_eq!(balance(&executor, &b), Balance::from(200u64));
+
+    // The transfer to `a` is reverted with the one exceeding the balance.
+    let result = exec_one(&mut executor, TransactionAction::Batch {
+        actions: vec![transfer(&a, 100), transfer(&b, 0x0100_0000)],
+    });
+    assert!(match result {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+    assert_eq!(balance(&executor, &a), Balance::from(100u64));
+    assert_eq!(balance(&executor, &b), Balance::from(200u64));
+
+    // A batch can be neither empty nor nested.
+    for actions in vec![vec![], vec![TransactionAction::Batch {
+        actions: vec![transfer(&a, 100)],
+    }]] {
+        let result = exec_one(&mut executor, TransactionAction::Batch { actions });
+        assert!(match result {
+            ReceiptResult::Fail { .. } => true,
+            _ => false,
+        });
+    }
+    assert_eq!(balance(&executor, &a), Balance::from(100u64));
+}
+
+// An epoch of transfers among a few accounts, their balances are read and
+// written by every transaction.
+#[bench]
+fn bench_transfer_epoch(b: &mut Bencher) {
+    let signed_txs = (0..1000)
+        .map(|i| {
+            mock_signed_tx(TransactionAction::Transfer {
+                receiver:       UserAddress::from_hex(&format!(
+                    "10000000000000000000000000000000000000000{}",
+                    i % 4
+                ))
+                .unwrap(),
+                carrying_asset: CarryingAsset {
+                    asset_id: mock_asset_id(),
+                    amount:   Balance::from(1u64),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    b.iter(|| {
+        let mut executor = create_executor();
+        executor.exec(signed_txs.clone()).unwrap();
+    });
+}
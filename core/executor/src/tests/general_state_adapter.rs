@@ -9,6 +9,8 @@ use protocol::ProtocolResult;
 use crate::adapter::GeneralContractStateAdapter;
 use crate::tests;
 
+use test::Bencher;
+
 struct FixedTestSchema;
 impl ContractSchema for FixedTestSchema {
     type Key = FixedTestBytes;
@@ -36,6 +38,30 @@ impl ContractSer for FixedTestBytes {
     }
 }
 
+// Reads the values of `FixedTestSchema` as strings.
+struct FixedTestStringSchema;
+impl ContractSchema for FixedTestStringSchema {
+    type Key = FixedTestBytes;
+    type Value = FixedTestString;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedTestString {
+    inner: String,
+}
+
+impl ContractSer for FixedTestString {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(self.inner.as_bytes()))
+    }
+
+    fn decode(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(FixedTestString {
+            inner: String::from_utf8_lossy(&bytes).into_owned(),
+        })
+    }
+}
+
 #[test]
 fn insert() {
     let memdb = tests::create_empty_memdb();
@@ -79,3 +105,199 @@ fn revert() {
     let get_value = state_adapter.get::<FixedTestSchema>(&key).unwrap();
     assert_eq!(get_value, None);
 }
+
+#[test]
+fn revert_stash() {
+    let memdb = tests::create_empty_memdb();
+    let trie = tests::create_empty_trie(Arc::clone(&memdb));
+    let mut state_adapter = GeneralContractStateAdapter::new(trie);
+
+    let key = FixedTestBytes::new(Bytes::from(b"test-key".to_vec()));
+    let value = FixedTestBytes::new(Bytes::from(b"test-value".to_vec()));
+    state_adapter
+        .insert_cache::<FixedTestSchema>(key.clone(), value.clone())
+        .unwrap();
+    state_adapter.stash().unwrap();
+
+    let get_value = state_adapter.get::<FixedTestSchema>(&key).unwrap().unwrap();
+    assert_eq!(get_value, value.clone());
+
+    state_adapter.revert_stash().unwrap();
+    let get_value = state_adapter.get::<FixedTestSchema>(&key).unwrap();
+    assert_eq!(get_value, None);
+}
+
+#[test]
+fn remove() {
+    let memdb = tests::create_empty_memdb();
+    let trie = tests::create_empty_trie(Arc::clone(&memdb));
+    let mut state_adapter = GeneralContractStateAdapter::new(trie);
+
+    let key = FixedTestBytes::new(Bytes::from(b"test-key".to_vec()));
+    let value = FixedTestBytes::new(Bytes::from(b"test-value".to_vec()));
+    state_adapter
+        .insert_cache::<FixedTestSchema>(key.clone(), value.clone())
+        .unwrap();
+    state_adapter.stash().unwrap();
+    let root = state_adapter.commit().unwrap();
+
+    // The removal hides the committed value until it is reverted.
+    state_adapter.remove_cache::<FixedTestSchema>(&key).unwrap();
+    assert_eq!(state_adapter.get::<FixedTestSchema>(&key).unwrap(), None);
+    assert!(!state_adapter.contains::<FixedTestSchema>(&key).unwrap());
+    state_adapter.revert_cache().unwrap();
+    assert_eq!(
+        state_adapter.get::<FixedTestSchema>(&key).unwrap(),
+        Some(value)
+    );
+
+    state_adapter.remove_cache::<FixedTestSchema>(&key).unwrap();
+    state_adapter.stash().unwrap();
+    assert_ne!(state_adapter.commit().unwrap(), root);
+    assert!(!state_adapter.contains::<FixedTestSchema>(&key).unwrap());
+}
+
+#[test]
+fn checkpoint() {
+    let memdb = tests::create_empty_memdb();
+    let trie = tests::create_empty_trie(Arc::clone(&memdb));
+    let mut state_adapter = GeneralContractStateAdapter::new(trie);
+
+    let bytes = |s: &str| FixedTestBytes::new(Bytes::from(s));
+    let get = |state_adapter: &GeneralContractStateAdapter<_>, key: &str| {
+        state_adapter
+            .get::<FixedTestSchema>(&bytes(key))
+            .unwrap()
+            .map(|value| value.inner)
+    };
+
+    state_adapter
+        .insert_cache::<FixedTestSchema>(bytes("a"), bytes("1"))
+        .unwrap();
+
+    // The inner checkpoint sees the outer changes.
+    state_adapter.push_checkpoint();
+    assert_eq!(get(&state_adapter, "a"), Some(Bytes::from("1")));
+    state_adapter
+        .insert_cache::<FixedTestSchema>(bytes("a"), bytes("2"))
+        .unwrap();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(bytes("b"), bytes("2"))
+        .unwrap();
+
+    state_adapter.push_checkpoint();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(bytes("c"), bytes("3"))
+        .unwrap();
+    state_adapter.rollback_checkpoint().unwrap();
+    assert_eq!(get(&state_adapter, "c"), None);
+    assert_eq!(get(&state_adapter, "a"), Some(Bytes::from("2")));
+
+    state_adapter.commit_checkpoint().unwrap();
+    assert_eq!(get(&state_adapter, "a"), Some(Bytes::from("2")));
+    assert_eq!(get(&state_adapter, "b"), Some(Bytes::from("2")));
+    assert!(state_adapter.rollback_checkpoint().is_err());
+
+    // A rolled back checkpoint restores the overwritten value.
+    state_adapter.push_checkpoint();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(bytes("a"), bytes("4"))
+        .unwrap();
+    state_adapter.rollback_checkpoint().unwrap();
+    assert_eq!(get(&state_adapter, "a"), Some(Bytes::from("2")));
+
+    // Stashing flattens the open checkpoints.
+    state_adapter.push_checkpoint();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(bytes("d"), bytes("5"))
+        .unwrap();
+    state_adapter.stash().unwrap();
+    assert!(state_adapter.rollback_checkpoint().is_err());
+    assert_eq!(get(&state_adapter, "d"), Some(Bytes::from("5")));
+}
+
+#[test]
+fn get_by_another_schema() {
+    let mut state_adapter = tests::create_state_adapter();
+
+    let key = FixedTestBytes::new(Bytes::from(b"test-key".to_vec()));
+    let value = FixedTestBytes::new(Bytes::from(b"test-value".to_vec()));
+    state_adapter
+        .insert_cache::<FixedTestSchema>(key.clone(), value.clone())
+        .unwrap();
+
+    // The cached value is converted by its encoding.
+    let get_value = state_adapter
+        .get::<FixedTestStringSchema>(&key)
+        .unwrap()
+        .unwrap();
+    assert_eq!(get_value.inner, "test-value");
+
+    state_adapter.stash().unwrap();
+    state_adapter.commit().unwrap();
+    let get_value = state_adapter.get::<FixedTestSchema>(&key).unwrap().unwrap();
+    assert_eq!(get_value, value);
+}
+
+#[test]
+fn cached_bytes() {
+    let mut state_adapter = tests::create_state_adapter();
+    let key = FixedTestBytes::new(Bytes::from(b"test-key".to_vec()));
+    let other_key = FixedTestBytes::new(Bytes::from(b"other-key".to_vec()));
+
+    // A key written again is measured by its latest value.
+    state_adapter
+        .insert_cache::<FixedTestSchema>(
+            key.clone(),
+            FixedTestBytes::new(Bytes::from("long-value")),
+        )
+        .unwrap();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(key.clone(), FixedTestBytes::new(Bytes::from("value")))
+        .unwrap();
+    assert_eq!(state_adapter.cached_bytes(), 8 + 5);
+
+    // A removed key is measured by itself.
+    state_adapter.push_checkpoint();
+    state_adapter.remove_cache::<FixedTestSchema>(&key).unwrap();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(other_key, FixedTestBytes::new(Bytes::from("value")))
+        .unwrap();
+    assert_eq!(state_adapter.cached_bytes(), 8 + 5 + 8 + 9 + 5);
+
+    state_adapter.commit_checkpoint().unwrap();
+    assert_eq!(state_adapter.cached_bytes(), 8 + 9 + 5);
+
+    state_adapter.push_checkpoint();
+    state_adapter
+        .insert_cache::<FixedTestSchema>(key, FixedTestBytes::new(Bytes::from("value")))
+        .unwrap();
+    state_adapter.rollback_checkpoint().unwrap();
+    assert_eq!(state_adapter.cached_bytes(), 8 + 9 + 5);
+
+    state_adapter.stash().unwrap();
+    assert_eq!(state_adapter.cached_bytes(), 0);
+}
+
+#[bench]
+fn bench_hot_keys(b: &mut Bencher) {
+    let mut state_adapter = tests::create_state_adapter();
+    let keys = (0..10)
+        .map(|i| FixedTestBytes::new(Bytes::from(format!("test-key-{}", i))))
+        .collect::<Vec<_>>();
+
+    b.iter(|| {
+        for i in 0..1000 {
+            let key = &keys[i % keys.len()];
+            let value = state_adapter
+                .get::<FixedTestSchema>(key)
+                .unwrap()
+                .unwrap_or_else(|| FixedTestBytes::new(Bytes::new()));
+            state_adapter
+                .insert_cache::<FixedTestSchema>(key.clone(), value)
+                .unwrap();
+            state_adapter.stash().unwrap();
+        }
+        state_adapter.commit().unwrap();
+    });
+}
@@ -0,0 +1,154 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bytes::Bytes;
+
+use protocol::traits::executor::contract::GovernanceContract;
+use protocol::traits::executor::{ContractSer, Executor, RcInvokeContext};
+use protocol::types::{
+    Address, ChainParams, ContractAddress, Fee, ReceiptResult, TransactionAction, UserAddress,
+    Validator,
+};
+
+use crate::fixed_types::FixedChainParams;
+use crate::native_contract::{NativeGovernanceContract, GOVERNANCE_CONTRACT_ADDRESS};
+use crate::tests::{
+    create_executor, create_state_adapter, exec_one, mock_asset_id, mock_genesis,
+    mock_invoke_context, mock_signed_tx,
+};
+
+fn user_context(address: &UserAddress) -> RcInvokeContext {
+    mock_invoke_context(
+        Address::User(address.clone()),
+        None,
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    0,
+        },
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    1_000_000,
+        },
+    )
+}
+
+fn validator(address: &UserAddress, weight: u8) -> Validator {
+    Validator {
+        address:        address.clone(),
+        propose_weight: 1,
+        vote_weight:    weight,
+    }
+}
+
+fn mock_params(cycles_limit: u64) -> ChainParams {
+    let mut params = mock_genesis().chain_params();
+    params.cycles_limit = cycles_limit;
+    params
+}
+
+#[test]
+fn test_governance_contract() {
+    let state = Rc::new(RefCell::new(create_state_adapter()));
+    let mut governance = NativeGovernanceContract::new(state);
+
+    let a = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let b = UserAddress::from_hex("100000000000000000000000000000000000000002").unwrap();
+    let c = UserAddress::from_hex("100000000000000000000000000000000000000003").unwrap();
+    let d = UserAddress::from_hex("100000000000000000000000000000000000000004").unwrap();
+    governance
+        .init(mock_params(1000), vec![
+            validator(&a, 2),
+            validator(&b, 1),
+            validator(&c, 1),
+        ])
+        .unwrap();
+
+    // Only the validators can propose, the parameters take effect from a
+    // future epoch. The epoch of the context is 1.
+    assert!(governance
+        .propose(user_context(&d), 3, mock_params(2000))
+        .is_err());
+    assert!(governance
+        .propose(user_context(&a), 1, mock_params(2000))
+        .is_err());
+    assert!(governance
+        .propose(user_context(&a), 3, mock_params(0))
+        .is_err());
+
+    let proposal_id = governance
+        .propose(user_context(&a), 3, mock_params(2000))
+        .unwrap();
+    assert!(governance.vote(user_context(&a), proposal_id).is_err());
+    // 3 of the 4 vote weight approve the proposal.
+    governance.vote(user_context(&b), proposal_id).unwrap();
+    assert!(governance.vote(user_context(&c), proposal_id).is_err());
+
+    assert_eq!(
+        governance.advance(2, vec![]).unwrap(),
+        Some(mock_params(1000))
+    );
+    assert_eq!(
+        governance.advance(3, vec![]).unwrap(),
+        Some(mock_params(2000))
+    );
+    assert_eq!(governance.get_params().unwrap(), mock_params(2000));
+
+    // The proposal is dropped if it is not approved before its activate
+    // epoch.
+    let proposal_id = governance
+        .propose(user_context(&c), 4, mock_params(3000))
+        .unwrap();
+    governance.advance(4, vec![]).unwrap();
+    assert!(governance.vote(user_context(&a), proposal_id).is_err());
+
+    // The validators follow the elected ones.
+    governance.advance(5, vec![validator(&d, 1)]).unwrap();
+    assert!(governance
+        .propose(user_context(&a), 6, mock_params(3000))
+        .is_err());
+    governance
+        .propose(user_context(&d), 6, mock_params(3000))
+        .unwrap();
+    assert_eq!(
+        governance.advance(6, vec![]).unwrap(),
+        Some(mock_params(3000))
+    );
+}
+
+#[test]
+fn test_propose_params() {
+    let mut executor = create_executor();
+    let propose = |activate_epoch_id: u64, params: ChainParams| TransactionAction::Call {
+        contract:       ContractAddress::from_bytes(GOVERNANCE_CONTRACT_ADDRESS.as_bytes())
+            .unwrap(),
+        method:         "propose".to_owned(),
+        args:           vec![
+            Bytes::from(activate_epoch_id.to_be_bytes().to_vec()),
+            FixedChainParams::new(params).encode().unwrap(),
+        ],
+        carrying_asset: None,
+    };
+
+    assert_eq!(
+        executor.get_chain_params().unwrap(),
+        Some(mock_genesis().chain_params())
+    );
+
+    // The epoch of the executor is 1.
+    let result = exec_one(&mut executor, propose(1, mock_params(2000)));
+    assert!(match result {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+
+    // The sender is the only validator of the genesis, its proposal is
+    // approved at once and takes effect from the next epoch.
+    let resp = executor
+        .exec(vec![mock_signed_tx(propose(2, mock_params(2000)))])
+        .unwrap();
+    assert_eq!(resp.chain_params, Some(mock_params(2000)));
+    assert_eq!(
+        executor.get_chain_params().unwrap(),
+        Some(mock_params(2000))
+    );
+}
@@ -1,17 +1,36 @@
+extern crate test;
+
 mod account_contract;
 mod bank_contract;
+mod executor;
 mod general_state_adapter;
+mod governance_contract;
+mod native_contract;
+mod snapshot;
+mod staking_contract;
 mod trie;
+mod wasm;
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use protocol::traits::executor::{InvokeContext, RcInvokeContext};
-use protocol::types::{Address, CarryingAsset, Fee, Hash, MerkleRoot};
+use bytes::Bytes;
+
+use protocol::traits::executor::{Executor, InvokeContext, RcInvokeContext};
+use protocol::types::{
+    Address, AssetID, CarryingAsset, ContractAddress, ContractType, CyclesSchedule, Fee, Genesis,
+    GenesisConsensus, GenesisDuration, GenesisStaking, GenesisStateAlloc, GenesisStateAsset,
+    GenesisSystemToken, GenesisValidator, Hash, MerkleRoot, RawTransaction, ReceiptResult,
+    SignedTransaction, TransactionAction, UserAddress,
+};
 
 use crate::adapter::GeneralContractStateAdapter;
 use crate::trie::MPTTrie;
+use crate::TransactionExecutor;
+
+const COINBASE: &str = "100000000000000000000000000000000000000000";
 
 type MemTrie = MPTTrie<cita_trie::MemoryDB>;
 
@@ -48,7 +67,114 @@ fn mock_invoke_context(
         cycles_used,
         cycles_limit,
         carrying_asset,
+        logs: vec![],
+        cycles_schedule: CyclesSchedule::default(),
+        tracer: None,
     };
 
     Rc::new(RefCell::new(ictx))
 }
+
+// The id of the system token registered in `mock_genesis`, it is also used to
+// pay the fee.
+fn mock_asset_id() -> AssetID {
+    let address = ContractAddress::from_code(Bytes::new(), 0, ContractType::Asset).unwrap();
+    Hash::digest(Bytes::from(
+        [Hash::from_empty().as_bytes(), address.as_bytes()].concat(),
+    ))
+}
+
+fn mock_pubkey() -> Bytes {
+    Bytes::from(vec![1u8; 33])
+}
+
+fn mock_genesis() -> Genesis {
+    let address = UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap();
+
+    Genesis {
+        chain_id:         Hash::from_empty().as_hex(),
+        timestamp:        0,
+        prevhash:         "".to_owned(),
+        validators:       vec![GenesisValidator {
+            address:        address.as_hex(),
+            propose_weight: 1,
+            vote_weight:    1,
+        }],
+        consensus:        GenesisConsensus {
+            cycles_limit: 99_999_999,
+            cycles_price: 1,
+            interval:     3000,
+            duration:     GenesisDuration::default(),
+        },
+        system_token:     GenesisSystemToken {
+            code:     "".to_owned(),
+            name:     "Muta token".to_owned(),
+            symbol:   "MTT".to_owned(),
+            supply:   0x0100_0063,
+            decimals: 8,
+        },
+        state_alloc:      vec![GenesisStateAlloc {
+            address: address.as_hex(),
+            assets:  vec![GenesisStateAsset {
+                asset_id: mock_asset_id().as_hex(),
+                balance:  "01000063".to_owned(),
+            }],
+        }],
+        cycles_schedules: vec![],
+        staking:          GenesisStaking {
+            min_stake:        100,
+            max_validators:   21,
+            validator_stake:  100,
+            unbonding_epochs: 2,
+        },
+    }
+}
+
+// Every transaction gets a new nonce.
+fn mock_signed_tx(action: TransactionAction) -> SignedTransaction {
+    static NONCE: AtomicU64 = AtomicU64::new(0);
+    let nonce = NONCE.fetch_add(1, Ordering::SeqCst);
+
+    let raw = RawTransaction {
+        chain_id: Hash::from_empty(),
+        nonce: Hash::digest(Bytes::from(nonce.to_be_bytes().to_vec())),
+        timeout: 100,
+        fee: Fee {
+            asset_id: mock_asset_id(),
+            cycle:    1_000_000,
+        },
+        max_cycles_price: 1,
+        action,
+    };
+
+    SignedTransaction {
+        raw,
+        tx_hash: Hash::digest(Bytes::from(format!("tx {}", nonce))),
+        pubkey: mock_pubkey(),
+        signature: Bytes::new(),
+        multisig: None,
+    }
+}
+
+// Create an executor with the genesis state, the sender of `mock_signed_tx`
+// owns 0xffffff system token after bonding 100 as the genesis validator.
+fn create_executor() -> TransactionExecutor<cita_trie::MemoryDB> {
+    let mut executor = TransactionExecutor::new(
+        Hash::from_empty(),
+        Hash::from_empty(),
+        create_empty_memdb(),
+        1,
+        1,
+        Address::from_hex(COINBASE).unwrap(),
+        false,
+    )
+    .unwrap();
+
+    executor.create_genesis(&mock_genesis()).unwrap();
+    executor
+}
+
+fn exec_one(executor: &mut dyn Executor, action: TransactionAction) -> ReceiptResult {
+    let mut resp = executor.exec(vec![mock_signed_tx(action)]).unwrap();
+    resp.receipts.remove(0).result
+}
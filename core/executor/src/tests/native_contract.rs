@@ -0,0 +1,275 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use protocol::traits::executor::contract::{
+    ContractStateAdapter, NativeContract, NativeDispatcher,
+};
+use protocol::traits::executor::{Executor, ExecutorFactory, RcInvokeContext};
+use protocol::types::{
+    Address, Balance, ContractAddress, Fee, Hash, ReceiptResult, TransactionAction,
+};
+use protocol::ProtocolResult;
+
+use crate::fixed_types::{FixedBytes, FixedStorageKey, FixedStorageSchema};
+use crate::native_contract::BANK_CONTRACT_ADDRESS;
+use crate::tests::{create_empty_memdb, mock_asset_id, mock_genesis, mock_signed_tx, COINBASE};
+use crate::{
+    GeneralContractStateAdapter, RcGeneralContractStateAdapter, TransactionExecutorFactory,
+};
+
+const COUNTER_ADDRESS: &str = "230000000000000000000000000000000000000100";
+const RECEIVER: &str = "100000000000000000000000000000000000000002";
+
+// Counts the calls of `inc`, `get` returns the count as a single byte.
+struct MockCounterContract<StateAdapter: ContractStateAdapter> {
+    state_adapter: Rc<RefCell<StateAdapter>>,
+}
+
+impl<StateAdapter: ContractStateAdapter> MockCounterContract<StateAdapter> {
+    fn get_count(&self) -> ProtocolResult<u8> {
+        let count = self
+            .state_adapter
+            .borrow()
+            .get::<FixedStorageSchema>(&FixedStorageKey::new(Bytes::from("count")))?
+            .map_or(0, |count| count.inner[0]);
+        Ok(count)
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeContract<StateAdapter>
+    for MockCounterContract<StateAdapter>
+{
+    fn invoke(
+        &mut self,
+        dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        _args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        let count = self.get_count()?;
+        let receiver = Address::from_hex(RECEIVER)?;
+        let one = Balance::from(1u64);
+        match method {
+            "inc" => {
+                self.state_adapter
+                    .borrow_mut()
+                    .insert_cache::<FixedStorageSchema>(
+                        FixedStorageKey::new(Bytes::from("count")),
+                        FixedBytes::new(Bytes::from(vec![count + 1])),
+                    )?;
+            }
+            // Pay one of the asset of the caller to the receiver.
+            "pay" => {
+                let caller = ictx.borrow().caller.clone();
+                dispatcher.transfer(&mock_asset_id(), &caller, &receiver, one)?;
+            }
+            // Pay the asset of the coinbase instead, which is not allowed.
+            "take" => {
+                let coinbase = Address::from_hex(COINBASE)?;
+                dispatcher.transfer(&mock_asset_id(), &coinbase, &receiver, one)?;
+            }
+            // Only the bank mints.
+            "mint" => dispatcher.mint(&mock_asset_id(), &receiver, one)?,
+            _ => (),
+        }
+        Ok(Bytes::from(vec![count]))
+    }
+}
+
+fn build_counter(
+    state_adapter: RcGeneralContractStateAdapter<cita_trie::MemoryDB>,
+) -> Box<dyn NativeContract<GeneralContractStateAdapter<cita_trie::MemoryDB>>> {
+    Box::new(MockCounterContract { state_adapter })
+}
+
+fn counter_factory() -> TransactionExecutorFactory<cita_trie::MemoryDB> {
+    let mut factory = TransactionExecutorFactory::new();
+    factory
+        .register_native_contract(Address::from_hex(COUNTER_ADDRESS).unwrap(), build_counter)
+        .unwrap();
+    factory
+}
+
+fn read_count(executor: &dyn Executor) -> ProtocolResult<Bytes> {
+    let resp = executor.read(
+        &Address::from_hex(COINBASE).unwrap(),
+        &Fee {
+            asset_id: mock_asset_id(),
+            cycle:    1_000_000,
+        },
+        &ContractAddress::from_hex(COUNTER_ADDRESS).unwrap(),
+        "get",
+        &[],
+    )?;
+    Ok(resp.return_value)
+}
+
+#[test]
+fn test_register_native_contract() {
+    let mut factory = counter_factory();
+
+    // The user addresses, the builtin contracts and the registered ones are
+    // rejected.
+    assert!(factory
+        .register_native_contract(Address::from_hex(COINBASE).unwrap(), build_counter)
+        .is_err());
+    assert!(factory
+        .register_native_contract(BANK_CONTRACT_ADDRESS.clone(), build_counter)
+        .is_err());
+    assert!(factory
+        .register_native_contract(Address::from_hex(COUNTER_ADDRESS).unwrap(), build_counter)
+        .is_err());
+}
+
+#[test]
+fn test_call_native_contract() {
+    let factory = counter_factory();
+    let db = create_empty_memdb();
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+
+    let mut executor = factory
+        .from_root(
+            Hash::from_empty(),
+            Hash::from_empty(),
+            Arc::clone(&db),
+            1,
+            1,
+            coinbase.clone(),
+            false,
+        )
+        .unwrap();
+    executor.create_genesis(&mock_genesis()).unwrap();
+
+    let inc = || {
+        mock_signed_tx(TransactionAction::Call {
+            contract:       ContractAddress::from_hex(COUNTER_ADDRESS).unwrap(),
+            method:         "inc".to_owned(),
+            args:           vec![],
+            carrying_asset: None,
+        })
+    };
+    let resp = executor.exec(vec![inc(), inc()]).unwrap();
+    for receipt in resp.receipts.iter() {
+        assert!(match receipt.result {
+            ReceiptResult::Call { .. } => true,
+            _ => false,
+        });
+    }
+    assert_eq!(read_count(&*executor).unwrap(), Bytes::from(vec![2u8]));
+
+    // The state of the contract is committed with the others.
+    let executor = factory
+        .from_root(
+            Hash::from_empty(),
+            resp.state_root.clone(),
+            Arc::clone(&db),
+            2,
+            1,
+            coinbase.clone(),
+            false,
+        )
+        .unwrap();
+    assert_eq!(read_count(&*executor).unwrap(), Bytes::from(vec![2u8]));
+
+    // The contract does not exist without the registration.
+    let executor = TransactionExecutorFactory::new()
+        .from_root(
+            Hash::from_empty(),
+            resp.state_root,
+            db,
+            2,
+            1,
+            coinbase,
+            false,
+        )
+        .unwrap();
+    assert!(read_count(&*executor).is_err());
+}
+
+#[test]
+fn test_native_contract_balance() {
+    let factory = counter_factory();
+    let db = create_empty_memdb();
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+
+    let mut executor = factory
+        .from_root(
+            Hash::from_empty(),
+            Hash::from_empty(),
+            Arc::clone(&db),
+            1,
+            1,
+            coinbase.clone(),
+            false,
+        )
+        .unwrap();
+    executor.create_genesis(&mock_genesis()).unwrap();
+    let call = |method: &str| {
+        mock_signed_tx(TransactionAction::Call {
+            contract:       ContractAddress::from_hex(COUNTER_ADDRESS).unwrap(),
+            method:         method.to_owned(),
+            args:           vec![],
+            carrying_asset: None,
+        })
+    };
+
+    // A native contract moves the balances of its caller and itself only.
+    let resp = executor
+        .exec(vec![call("pay"), call("take"), call("mint")])
+        .unwrap();
+    assert!(match resp.receipts[0].result {
+        ReceiptResult::Call { .. } => true,
+        _ => false,
+    });
+    for receipt in resp.receipts[1..].iter() {
+        assert!(match &receipt.result {
+            ReceiptResult::Fail { system, .. } => system.contains("BalanceNotAllowed"),
+            _ => false,
+        });
+    }
+
+    let executor = factory
+        .from_root(
+            Hash::from_empty(),
+            resp.state_root,
+            db,
+            2,
+            1,
+            coinbase,
+            false,
+        )
+        .unwrap();
+    let receiver = Address::from_hex(RECEIVER).unwrap();
+    let proof = executor
+        .get_balance_proof(&receiver, &mock_asset_id())
+        .unwrap();
+    assert_eq!(proof.balance, Balance::from(1u64));
+}
+
+#[test]
+fn test_untouched_native_contract() {
+    let genesis_root = |factory: TransactionExecutorFactory<cita_trie::MemoryDB>| {
+        factory
+            .from_root(
+                Hash::from_empty(),
+                Hash::from_empty(),
+                create_empty_memdb(),
+                1,
+                1,
+                Address::from_hex(COINBASE).unwrap(),
+                false,
+            )
+            .unwrap()
+            .create_genesis(&mock_genesis())
+            .unwrap()
+    };
+
+    // The state of a registered contract is not written until it is invoked.
+    assert_eq!(
+        genesis_root(counter_factory()),
+        genesis_root(TransactionExecutorFactory::new())
+    );
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use protocol::traits::executor::Executor;
+use protocol::types::{
+    Address, Balance, CarryingAsset, Fee, Hash, Receipt, ReceiptResult, TransactionAction,
+    UserAddress,
+};
+
+use crate::snapshot::{export_snapshot, import_snapshot, verify_snapshot_epoch, StateSnapshot};
+use crate::tests::{create_empty_memdb, mock_asset_id, mock_genesis, mock_signed_tx, COINBASE};
+use crate::TransactionExecutor;
+
+#[test]
+fn test_snapshot() {
+    let db = create_empty_memdb();
+    let coinbase = Address::from_hex(COINBASE).unwrap();
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let mut executor = TransactionExecutor::new(
+        Hash::from_empty(),
+        Hash::from_empty(),
+        Arc::clone(&db),
+        1,
+        1,
+        coinbase.clone(),
+        false,
+    )
+    .unwrap();
+    executor.create_genesis(&mock_genesis()).unwrap();
+    let action = TransactionAction::Transfer {
+        receiver:       receiver.clone(),
+        carrying_asset: CarryingAsset {
+            asset_id: mock_asset_id(),
+            amount:   Balance::from(100u64),
+        },
+    };
+    let state_root = executor
+        .exec(vec![mock_signed_tx(action)])
+        .unwrap()
+        .state_root;
+
+    let snapshot = export_snapshot(Arc::clone(&db), &state_root).unwrap();
+    let snapshot = StateSnapshot::decode(snapshot.encode()).unwrap();
+    assert_eq!(snapshot.state_root, state_root);
+
+    // Bootstrap another database from the snapshot.
+    let new_db = create_empty_memdb();
+    assert_eq!(
+        import_snapshot(Arc::clone(&new_db), &snapshot).unwrap(),
+        state_root
+    );
+    let executor = TransactionExecutor::new(
+        Hash::from_empty(),
+        state_root.clone(),
+        new_db,
+        2,
+        1,
+        coinbase,
+        false,
+    )
+    .unwrap();
+    let proof = executor
+        .get_balance_proof(&Address::User(receiver), &mock_asset_id())
+        .unwrap();
+    assert_eq!(proof.balance, Balance::from(100u64));
+
+    // A tampered snapshot is rejected.
+    let mut tampered = snapshot.clone();
+    tampered.contracts[0]
+        .entries
+        .push((Bytes::from("key"), Bytes::from("value")));
+    assert!(import_snapshot(create_empty_memdb(), &tampered).is_err());
+}
+
+#[test]
+fn test_verify_snapshot_epoch() {
+    let snapshot = StateSnapshot {
+        state_root: Hash::digest(Bytes::from("state")),
+        contracts:  vec![],
+    };
+    let mut epoch = mock_genesis().epoch(snapshot.state_root.clone()).unwrap();
+    verify_snapshot_epoch(&epoch, &snapshot, &[]).unwrap();
+
+    let mut mismatched = epoch.clone();
+    mismatched.header.state_root = Hash::from_empty();
+    assert!(verify_snapshot_epoch(&mismatched, &snapshot, &[]).is_err());
+
+    // The proof in the header is the proof of the previous epoch.
+    epoch.header.epoch_id = 2;
+    assert!(verify_snapshot_epoch(&epoch, &snapshot, &[]).is_err());
+    epoch.header.proof.epoch_id = 1;
+    epoch.header.pre_hash = Hash::digest(Bytes::from("epoch 1"));
+    assert!(verify_snapshot_epoch(&epoch, &snapshot, &[]).is_err());
+    epoch.header.proof.epoch_hash = epoch.header.pre_hash.clone();
+    verify_snapshot_epoch(&epoch, &snapshot, &[]).unwrap();
+
+    // The receipts give the cycles used by the transactions of the epoch, the
+    // rejected transactions have none.
+    let tx_hash = Hash::digest(Bytes::from("tx"));
+    epoch.ordered_tx_hashes = vec![tx_hash.clone()];
+    verify_snapshot_epoch(&epoch, &snapshot, &[]).unwrap();
+    let mut receipt = Receipt {
+        state_root: Hash::from_empty(),
+        epoch_id: 2,
+        tx_hash,
+        cycles_used: Fee {
+            asset_id: mock_asset_id(),
+            cycle:    100,
+        },
+        result: ReceiptResult::Fail {
+            system: "".to_owned(),
+            user:   "".to_owned(),
+        },
+        logs: vec![],
+    };
+    verify_snapshot_epoch(&epoch, &snapshot, &[receipt.clone()]).unwrap();
+    assert!(verify_snapshot_epoch(&epoch, &snapshot, &[receipt.clone(), receipt.clone()]).is_err());
+    receipt.tx_hash = Hash::digest(Bytes::from("another tx"));
+    assert!(verify_snapshot_epoch(&epoch, &snapshot, &[receipt]).is_err());
+}
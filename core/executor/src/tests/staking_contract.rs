@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bytes::Bytes;
+
+use protocol::traits::executor::contract::{AccountContract, StakingContract};
+use protocol::traits::executor::Executor;
+use protocol::types::{
+    Address, Balance, CarryingAsset, ContractAddress, Fee, ReceiptResult, TransactionAction,
+    UserAddress, Validator,
+};
+
+use crate::native_contract::{NativeStakingContract, STAKING_CONTRACT_ADDRESS};
+use crate::tests::{
+    create_executor, create_state_adapter, exec_one, mock_asset_id, mock_invoke_context,
+    mock_pubkey, mock_signed_tx,
+};
+use crate::TransactionExecutor;
+
+fn withdraw_call() -> TransactionAction {
+    TransactionAction::Call {
+        contract:       ContractAddress::from_bytes(STAKING_CONTRACT_ADDRESS.as_bytes()).unwrap(),
+        method:         "withdraw".to_owned(),
+        args:           vec![],
+        carrying_asset: None,
+    }
+}
+
+fn staking_call(method: &str, amount: u64) -> TransactionAction {
+    TransactionAction::Call {
+        contract:       ContractAddress::from_bytes(STAKING_CONTRACT_ADDRESS.as_bytes()).unwrap(),
+        method:         method.to_owned(),
+        args:           vec![Bytes::from(Balance::from(amount).to_bytes_be())],
+        carrying_asset: None,
+    }
+}
+
+#[test]
+fn test_staking_contract() {
+    let state = Rc::new(RefCell::new(create_state_adapter()));
+    let mut staking = NativeStakingContract::new(state);
+    staking
+        .init(mock_asset_id(), Balance::from(100u64), 2, 2)
+        .unwrap();
+
+    let caller = Address::from_hex("100000000000000000000000000000000000000000").unwrap();
+    let ctx = mock_invoke_context(
+        caller,
+        None,
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    0,
+        },
+        Fee {
+            asset_id: mock_asset_id(),
+            cycle:    1_000_000,
+        },
+    );
+    let a = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let b = UserAddress::from_hex("100000000000000000000000000000000000000002").unwrap();
+    let c = UserAddress::from_hex("100000000000000000000000000000000000000003").unwrap();
+
+    staking
+        .bond(Rc::clone(&ctx), &a, Balance::from(400u64))
+        .unwrap();
+    staking
+        .bond(Rc::clone(&ctx), &b, Balance::from(200u64))
+        .unwrap();
+    // Below the minimum stake.
+    assert!(staking
+        .bond(Rc::clone(&ctx), &c, Balance::from(50u64))
+        .is_err());
+    assert_eq!(staking.get_stake(&c).unwrap(), Balance::from(0u64));
+    assert_eq!(staking.get_validators().unwrap(), vec![
+        Validator {
+            address:        a.clone(),
+            propose_weight: 255,
+            vote_weight:    255,
+        },
+        Validator {
+            address:        b.clone(),
+            propose_weight: 127,
+            vote_weight:    127,
+        },
+    ]);
+
+    // Only the top `max_validators` accounts are elected.
+    staking
+        .bond(Rc::clone(&ctx), &c, Balance::from(300u64))
+        .unwrap();
+    staking
+        .bond(Rc::clone(&ctx), &c, Balance::from(50u64))
+        .unwrap();
+    let validators = staking.get_validators().unwrap();
+    assert_eq!(
+        validators
+            .iter()
+            .map(|validator| validator.address.clone())
+            .collect::<Vec<_>>(),
+        vec![a.clone(), c.clone()]
+    );
+
+    assert!(staking
+        .unbond(Rc::clone(&ctx), &b, Balance::from(201u64))
+        .is_err());
+    // The remaining stake would be below the minimum.
+    assert!(staking
+        .unbond(Rc::clone(&ctx), &b, Balance::from(150u64))
+        .is_err());
+    staking
+        .unbond(Rc::clone(&ctx), &b, Balance::from(100u64))
+        .unwrap();
+    assert_eq!(staking.get_stake(&b).unwrap(), Balance::from(100u64));
+    staking
+        .unbond(Rc::clone(&ctx), &b, Balance::from(100u64))
+        .unwrap();
+    assert_eq!(staking.get_stake(&b).unwrap(), Balance::from(0u64));
+
+    // The unbonded stake of the epoch 1 is released from the epoch 4, after
+    // the validators of the epoch 2 and the 2 unbonding epochs.
+    ctx.borrow_mut().epoch_id = 3;
+    assert!(staking.withdraw(Rc::clone(&ctx), &b).is_err());
+    staking
+        .unbond(Rc::clone(&ctx), &a, Balance::from(300u64))
+        .unwrap();
+    ctx.borrow_mut().epoch_id = 4;
+    assert_eq!(
+        staking.withdraw(Rc::clone(&ctx), &b).unwrap(),
+        Balance::from(200u64)
+    );
+    assert!(staking.withdraw(Rc::clone(&ctx), &b).is_err());
+    assert!(staking.withdraw(Rc::clone(&ctx), &a).is_err());
+    ctx.borrow_mut().epoch_id = 6;
+    assert_eq!(
+        staking.withdraw(Rc::clone(&ctx), &a).unwrap(),
+        Balance::from(300u64)
+    );
+    assert_eq!(staking.get_stake(&a).unwrap(), Balance::from(100u64));
+}
+
+#[test]
+fn test_bond_and_unbond() {
+    let mut executor = create_executor();
+    let asset_id = mock_asset_id();
+    let sender = UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap();
+    let staked_balance = |executor: &TransactionExecutor<_>| {
+        executor
+            .account_contract
+            .borrow()
+            .get_balance(&asset_id, &STAKING_CONTRACT_ADDRESS)
+            .unwrap()
+    };
+    let is_call = |result: ReceiptResult| match result {
+        ReceiptResult::Call { .. } => true,
+        _ => false,
+    };
+
+    // The validator of the genesis bonds the minimum stake.
+    assert_eq!(staked_balance(&executor), Balance::from(100u64));
+    assert_eq!(executor.get_validators().unwrap(), vec![Validator {
+        address:        sender.clone(),
+        propose_weight: 255,
+        vote_weight:    255,
+    }]);
+
+    for amount in [101, 50].iter() {
+        let result = exec_one(&mut executor, staking_call("unbond", *amount));
+        assert!(!is_call(result));
+    }
+
+    let result = exec_one(&mut executor, staking_call("bond", 100));
+    assert!(is_call(result));
+    assert_eq!(staked_balance(&executor), Balance::from(200u64));
+
+    // The unbonded stake is held until its release epoch.
+    let result = exec_one(&mut executor, staking_call("unbond", 200));
+    assert!(is_call(result));
+    assert!(executor.get_validators().unwrap().is_empty());
+    let result = exec_one(&mut executor, withdraw_call());
+    assert!(!is_call(result));
+    assert_eq!(staked_balance(&executor), Balance::from(200u64));
+
+    // The epoch of the executor is 1, the validators change from the epoch
+    // 2, and the unbonding epochs of the genesis are 2.
+    executor.epoch_id = 4;
+    let result = exec_one(&mut executor, withdraw_call());
+    assert!(match result {
+        ReceiptResult::Call { return_value, .. } => {
+            return_value == Bytes::from(Balance::from(200u64).to_bytes_be())
+        }
+        _ => false,
+    });
+    assert_eq!(staked_balance(&executor), Balance::from(0u64));
+}
+
+#[test]
+fn test_bond_with_genesis_validators() {
+    let mut executor = create_executor();
+    let sender = UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap();
+    let bonder_pubkey = Bytes::from(vec![2u8; 33]);
+    let bonder = UserAddress::from_pubkey_bytes(bonder_pubkey.clone()).unwrap();
+
+    exec_one(&mut executor, TransactionAction::Transfer {
+        receiver:       bonder.clone(),
+        carrying_asset: CarryingAsset {
+            asset_id: mock_asset_id(),
+            amount:   Balance::from(0x10_0000u64),
+        },
+    });
+
+    // A bond of the minimum stake joins the validators of the genesis rather
+    // than replacing them.
+    let mut bond = mock_signed_tx(staking_call("bond", 100));
+    bond.pubkey = bonder_pubkey;
+    let resp = executor.exec(vec![bond]).unwrap();
+    let mut validators = resp
+        .validators
+        .iter()
+        .map(|validator| validator.address.clone())
+        .collect::<Vec<_>>();
+    validators.sort();
+    let mut expect = vec![bonder, sender];
+    expect.sort();
+    assert_eq!(validators, expect);
+}
@@ -1,8 +1,14 @@
 use std::sync::Arc;
+use std::{env, fs, process};
 
 use bytes::Bytes;
 
+use protocol::traits::executor::{Executor, TrieDB};
+use protocol::types::{Address, Balance, CarryingAsset, Hash, TransactionAction, UserAddress};
+
 use crate::tests;
+use crate::trie::{verify_proof, MPTTrie, RocksTrieDB};
+use crate::TransactionExecutor;
 
 #[test]
 fn insert() {
@@ -60,3 +66,125 @@ fn commit() {
 
     assert_eq!(root2 != root3, true)
 }
+
+#[test]
+fn proof() {
+    let memdb = tests::create_empty_memdb();
+    let mut trie = tests::create_empty_trie(Arc::clone(&memdb));
+
+    let key = Bytes::from(b"test-key".to_vec());
+    let value = Bytes::from(b"test-value".to_vec());
+
+    trie.insert(key.clone(), value.clone()).unwrap();
+    trie.insert(Bytes::from(b"test-key-2".to_vec()), value.clone())
+        .unwrap();
+    let root = trie.commit().unwrap();
+
+    let proof = trie.get_proof(&key).unwrap();
+    let get_value = verify_proof(&root, &key, proof.clone()).unwrap();
+    assert_eq!(get_value, Some(value));
+
+    // Proof of absence
+    let missing = Bytes::from(b"missing-key".to_vec());
+    let missing_proof = trie.get_proof(&missing).unwrap();
+    assert_eq!(verify_proof(&root, &missing, missing_proof).unwrap(), None);
+
+    // The proof does not match the root
+    let other_root = Hash::digest(Bytes::from(b"other-root".to_vec()));
+    assert!(verify_proof(&other_root, &key, proof).is_err());
+}
+
+#[test]
+fn prune() {
+    let path = env::temp_dir().join(format!("muta-prune-{}", process::id()));
+    let db = Arc::new(RocksTrieDB::with_pruning(&path, 2).unwrap());
+    let coinbase = Address::from_hex(tests::COINBASE).unwrap();
+    let receiver = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+
+    let mut executor = TransactionExecutor::new(
+        Hash::from_empty(),
+        Hash::from_empty(),
+        Arc::clone(&db),
+        0,
+        1,
+        coinbase.clone(),
+        false,
+    )
+    .unwrap();
+    let mut roots = vec![executor.create_genesis(&tests::mock_genesis()).unwrap()];
+
+    for epoch_id in 1..4 {
+        let mut executor = TransactionExecutor::new(
+            Hash::from_empty(),
+            roots[roots.len() - 1].clone(),
+            Arc::clone(&db),
+            epoch_id,
+            1,
+            coinbase.clone(),
+            false,
+        )
+        .unwrap();
+        let action = TransactionAction::Transfer {
+            receiver:       receiver.clone(),
+            carrying_asset: CarryingAsset {
+                asset_id: tests::mock_asset_id(),
+                amount:   Balance::from(100u64),
+            },
+        };
+        let resp = executor.exec(vec![tests::mock_signed_tx(action)]).unwrap();
+
+        db.record_root(&resp.state_root);
+        roots.push(resp.state_root);
+    }
+    assert!(db.prune().unwrap() > 0);
+
+    // Only the state of the last two epochs is kept.
+    assert!(MPTTrie::from(roots[1].clone(), Arc::clone(&db)).is_err());
+    for (index, root) in roots[2..].iter().enumerate() {
+        let executor = TransactionExecutor::new(
+            Hash::from_empty(),
+            root.clone(),
+            Arc::clone(&db),
+            4,
+            1,
+            coinbase.clone(),
+            false,
+        )
+        .unwrap();
+        let proof = executor
+            .get_balance_proof(&Address::User(receiver.clone()), &tests::mock_asset_id())
+            .unwrap();
+        assert_eq!(proof.balance, Balance::from(200u64 + index as u64 * 100));
+    }
+
+    drop(db);
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn prune_foreign_key() {
+    let path = env::temp_dir().join(format!("muta-prune-foreign-{}", process::id()));
+    let db = Arc::new(RocksTrieDB::with_pruning(&path, 2).unwrap());
+
+    let mut executor = TransactionExecutor::new(
+        Hash::from_empty(),
+        Hash::from_empty(),
+        Arc::clone(&db),
+        0,
+        1,
+        Address::from_hex(tests::COINBASE).unwrap(),
+        false,
+    )
+    .unwrap();
+    let root = executor.create_genesis(&tests::mock_genesis()).unwrap();
+    db.record_root(&root);
+
+    // Only the trie nodes can be stored, a round stops at any other key.
+    cita_trie::DB::insert(&*db, b"foreign".to_vec(), b"value".to_vec()).unwrap();
+    let err = db.prune().unwrap_err();
+    assert!(err.to_string().contains("NotTrieNode"));
+    assert!(MPTTrie::from(root, Arc::clone(&db)).is_ok());
+
+    drop(db);
+    fs::remove_dir_all(path).unwrap();
+}
@@ -0,0 +1,358 @@
+use bytes::Bytes;
+
+use protocol::traits::executor::Executor;
+use protocol::types::{
+    Address, BloomInput, ContractAddress, ContractType, CyclesSchedule, Fee, Hash, ReceiptResult,
+    TransactionAction, UserAddress,
+};
+
+use crate::tests::{create_executor, exec_one, mock_asset_id, mock_pubkey, mock_signed_tx};
+
+const STORAGE_CONTRACT: &str = r#"
+(module
+  (import "env" "arg_len" (func $arg_len (param i32) (result i32)))
+  (import "env" "arg_copy" (func $arg_copy (param i32 i32)))
+  (import "env" "storage_get" (func $storage_get (param i32 i32) (result i32)))
+  (import "env" "storage_set" (func $storage_set (param i32 i32 i32 i32)))
+  (import "env" "return_data_copy" (func $return_data_copy (param i32)))
+  (import "env" "ret" (func $ret (param i32 i32)))
+  (import "env" "log" (func $log (param i32 i32 i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "key")
+  (data (i32.const 64) "0123456789abcdef0123456789abcdef")
+
+  (func (export "set")
+    (call $arg_copy (i32.const 0) (i32.const 16))
+    (call $storage_set
+      (i32.const 0) (i32.const 3)
+      (i32.const 16) (call $arg_len (i32.const 0))))
+
+  (func (export "get")
+    (local $len i32)
+    (set_local $len (call $storage_get (i32.const 0) (i32.const 3)))
+    (call $return_data_copy (i32.const 16))
+    (call $ret (i32.const 16) (get_local $len)))
+
+  (func (export "emit")
+    (call $log (i32.const 64) (i32.const 1) (i32.const 0) (i32.const 3)))
+
+  (func (export "loop")
+    (loop $continue (br $continue))))
+"#;
+
+// `proxy` writes "outer" and invokes `fail` of the contract at arg 0, which
+// writes "inner" to the same key and aborts. `proxy_loop` invokes `loop` of
+// the contract at arg 0 and ignores its failure.
+const PROXY_CONTRACT: &str = r#"
+(module
+  (import "env" "arg_copy" (func $arg_copy (param i32 i32)))
+  (import "env" "storage_get" (func $storage_get (param i32 i32) (result i32)))
+  (import "env" "storage_set" (func $storage_set (param i32 i32 i32 i32)))
+  (import "env" "invoke" (func $invoke (param i32 i32 i32 i32 i32) (result i32)))
+  (import "env" "return_data_copy" (func $return_data_copy (param i32)))
+  (import "env" "ret" (func $ret (param i32 i32)))
+  (import "env" "abort" (func $abort (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "key")
+  (data (i32.const 8) "outer")
+  (data (i32.const 16) "inner")
+  (data (i32.const 24) "fail")
+  (data (i32.const 32) "\c0")
+  (data (i32.const 40) "loop")
+
+  (func (export "fail")
+    (call $storage_set (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 5))
+    (call $abort (i32.const 24) (i32.const 4)))
+
+  (func (export "proxy")
+    (call $storage_set (i32.const 0) (i32.const 3) (i32.const 8) (i32.const 5))
+    (call $arg_copy (i32.const 0) (i32.const 64))
+    (if (i32.ne
+          (call $invoke (i32.const 64) (i32.const 24) (i32.const 4) (i32.const 32) (i32.const 1))
+          (i32.const -1))
+      (then (unreachable))))
+
+  (func (export "proxy_loop")
+    (call $arg_copy (i32.const 0) (i32.const 64))
+    (drop
+      (call $invoke (i32.const 64) (i32.const 40) (i32.const 4) (i32.const 32) (i32.const 1))))
+
+  (func (export "get")
+    (local $len i32)
+    (set_local $len (call $storage_get (i32.const 0) (i32.const 3)))
+    (call $return_data_copy (i32.const 128))
+    (call $ret (i32.const 128) (get_local $len))))
+"#;
+
+// The memory has no maximum, so it is limited to 16 pages by the executor.
+const MEMORY_CONTRACT: &str = r#"
+(module
+  (memory (export "memory") 1)
+
+  (func (export "grow_to_max")
+    (if (i32.eq (memory.grow (i32.const 15)) (i32.const -1))
+      (then (unreachable))))
+
+  (func (export "grow_past_max")
+    (if (i32.eq (memory.grow (i32.const 16)) (i32.const -1))
+      (then (unreachable)))))
+"#;
+
+fn deploy(executor: &mut dyn Executor, contract_type: ContractType) -> ContractAddress {
+    deploy_code(executor, STORAGE_CONTRACT, contract_type)
+}
+
+fn deploy_code(
+    executor: &mut dyn Executor,
+    wat: &str,
+    contract_type: ContractType,
+) -> ContractAddress {
+    let code = Bytes::from(wabt::wat2wasm(wat).unwrap());
+    let action = TransactionAction::Deploy {
+        code,
+        contract_type,
+        asset: None,
+    };
+
+    match exec_one(executor, action) {
+        ReceiptResult::Deploy { contract, .. } => contract,
+        res => panic!("deploy failed {:?}", res),
+    }
+}
+
+fn call(
+    executor: &mut dyn Executor,
+    contract: &ContractAddress,
+    method: &str,
+    args: Vec<Bytes>,
+) -> ReceiptResult {
+    let action = TransactionAction::Call {
+        contract: contract.clone(),
+        method: method.to_owned(),
+        args,
+        carrying_asset: None,
+    };
+    exec_one(executor, action)
+}
+
+#[test]
+fn test_app_contract() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::App);
+
+    let res = call(&mut executor, &contract, "set", vec![Bytes::from("muta")]);
+    assert!(match res {
+        ReceiptResult::Call { .. } => true,
+        _ => false,
+    });
+
+    match call(&mut executor, &contract, "get", vec![]) {
+        ReceiptResult::Call { return_value, .. } => assert_eq!(return_value, Bytes::from("muta")),
+        res => panic!("call failed {:?}", res),
+    }
+
+    // Unknown method
+    let res = call(&mut executor, &contract, "unknown", vec![]);
+    assert!(match res {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_read_contract() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::App);
+    call(&mut executor, &contract, "set", vec![Bytes::from("muta")]);
+
+    let caller = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let cycles_limit = Fee {
+        asset_id: mock_asset_id(),
+        cycle:    1_000_000,
+    };
+
+    // The changes made by a read are discarded.
+    executor
+        .read(&caller, &cycles_limit, &contract, "set", &[Bytes::from(
+            "other",
+        )])
+        .unwrap();
+    let resp = executor
+        .read(&caller, &cycles_limit, &contract, "get", &[])
+        .unwrap();
+    assert_eq!(resp.return_value, Bytes::from("muta"));
+    assert!(resp.cycles_used.cycle > 0);
+
+    let res = executor.read(&caller, &cycles_limit, &contract, "loop", &[]);
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_library_contract_is_readonly() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::Library);
+
+    let res = call(&mut executor, &contract, "set", vec![Bytes::from("muta")]);
+    assert!(match res {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_contract_logs() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::App);
+    let topic = Hash::from_bytes(Bytes::from("0123456789abcdef0123456789abcdef")).unwrap();
+
+    let action = TransactionAction::Call {
+        contract:       contract.clone(),
+        method:         "emit".to_owned(),
+        args:           vec![],
+        carrying_asset: None,
+    };
+    let resp = executor.exec(vec![mock_signed_tx(action)]).unwrap();
+    let receipt = &resp.receipts[0];
+
+    assert_eq!(receipt.logs.len(), 1);
+    assert_eq!(receipt.logs[0].address, contract);
+    assert_eq!(receipt.logs[0].topics, vec![topic.clone()]);
+    assert_eq!(receipt.logs[0].data, Bytes::from("key"));
+    match &receipt.result {
+        ReceiptResult::Call { logs_bloom, .. } => {
+            assert!(logs_bloom.contains_input(BloomInput::Raw(topic.as_bytes().as_ref())))
+        }
+        res => panic!("call failed {:?}", res),
+    }
+    assert!(resp
+        .logs_bloom
+        .contains_input(BloomInput::Raw(contract.as_bytes().as_ref())));
+
+    // A library contract can not emit events.
+    let library = deploy(&mut executor, ContractType::Library);
+    let res = call(&mut executor, &library, "emit", vec![]);
+    assert!(match res {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_contract_out_of_cycles() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::App);
+
+    let res = call(&mut executor, &contract, "loop", vec![]);
+    match res {
+        ReceiptResult::Fail { system, .. } => assert!(system.contains("OutOfCycles")),
+        res => panic!("should be out of cycles {:?}", res),
+    }
+}
+
+#[test]
+fn test_deploy_invalid_code() {
+    let mut executor = create_executor();
+    let action = TransactionAction::Deploy {
+        code:          Bytes::from("not a wasm module"),
+        contract_type: ContractType::App,
+        asset:         None,
+    };
+
+    let res = exec_one(&mut executor, action);
+    assert!(match res {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_memory_limit() {
+    let mut executor = create_executor();
+    let contract = deploy_code(&mut executor, MEMORY_CONTRACT, ContractType::App);
+
+    let res = call(&mut executor, &contract, "grow_past_max", vec![]);
+    assert!(match res {
+        ReceiptResult::Fail { .. } => true,
+        _ => false,
+    });
+    let res = call(&mut executor, &contract, "grow_to_max", vec![]);
+    assert!(match res {
+        ReceiptResult::Call { .. } => true,
+        _ => false,
+    });
+
+    // A declared maximum above the limit is rejected.
+    let code = Bytes::from(wabt::wat2wasm(r#"(module (memory (export "memory") 1 17))"#).unwrap());
+    let res = exec_one(&mut executor, TransactionAction::Deploy {
+        code,
+        contract_type: ContractType::App,
+        asset: None,
+    });
+    match res {
+        ReceiptResult::Fail { system, .. } => assert!(system.contains("memory exceeds")),
+        res => panic!("deploy should fail {:?}", res),
+    }
+}
+
+#[test]
+fn test_failed_inner_call_is_reverted() {
+    let mut executor = create_executor();
+    let contract = deploy_code(&mut executor, PROXY_CONTRACT, ContractType::App);
+
+    let res = call(&mut executor, &contract, "proxy", vec![contract.as_bytes()]);
+    assert!(match res {
+        ReceiptResult::Call { .. } => true,
+        _ => false,
+    });
+
+    // Only the changes of the failed call are dropped.
+    match call(&mut executor, &contract, "get", vec![]) {
+        ReceiptResult::Call { return_value, .. } => assert_eq!(return_value, Bytes::from("outer")),
+        res => panic!("call failed {:?}", res),
+    }
+}
+
+#[test]
+fn test_inner_call_out_of_cycles() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::App);
+    let proxy = deploy_code(&mut executor, PROXY_CONTRACT, ContractType::App);
+
+    // The failure of the inner call is not ignored if the cycles run out.
+    let res = call(&mut executor, &proxy, "proxy_loop", vec![
+        contract.as_bytes()
+    ]);
+    match res {
+        ReceiptResult::Fail { system, .. } => assert!(system.contains("OutOfCycles")),
+        res => panic!("should be out of cycles {:?}", res),
+    }
+}
+
+#[test]
+fn test_host_call_cycles() {
+    let mut executor = create_executor();
+    let contract = deploy(&mut executor, ContractType::App);
+    let caller = Address::User(UserAddress::from_pubkey_bytes(mock_pubkey()).unwrap());
+    let cycles_limit = Fee {
+        asset_id: mock_asset_id(),
+        cycle:    1_000_000,
+    };
+
+    // The same instructions are executed, only the bytes passed to the
+    // storage differ.
+    let set = |value: Vec<u8>| {
+        executor
+            .read(&caller, &cycles_limit, &contract, "set", &[Bytes::from(
+                value,
+            )])
+            .unwrap()
+            .cycles_used
+            .cycle
+    };
+    let short = set(vec![0u8]);
+    let long = set(vec![0u8; 101]);
+
+    let schedule = CyclesSchedule::default();
+    assert!(short > schedule.host_call);
+    assert_eq!(long - short, 100 * schedule.host_byte);
+}
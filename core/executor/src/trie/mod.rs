@@ -1,3 +1,4 @@
+mod pruner;
 mod trie_db;
 
 pub use trie_db::RocksTrieDB;
@@ -6,7 +7,7 @@ use std::error::Error;
 use std::sync::Arc;
 
 use bytes::Bytes;
-use cita_trie::{PatriciaTrie, Trie, TrieError};
+use cita_trie::{MemoryDB, PatriciaTrie, Trie, TrieError};
 use derive_more::{Display, From};
 use hasher::HasherKeccak;
 use lazy_static::lazy_static;
@@ -41,6 +42,10 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(Self { root, trie })
     }
 
+    pub fn root(&self) -> MerkleRoot {
+        self.root.clone()
+    }
+
     pub fn get(&self, key: &Bytes) -> ProtocolResult<Option<Bytes>> {
         Ok(self
             .trie
@@ -53,6 +58,13 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(self.trie.contains(key).map_err(MPTTrieError::from)?)
     }
 
+    /// Iterate over all the key-value pairs in the trie.
+    pub fn iter(&self) -> impl Iterator<Item = (Bytes, Bytes)> + '_ {
+        self.trie
+            .iter()
+            .map(|(key, value)| (Bytes::from(key), Bytes::from(value)))
+    }
+
     pub fn insert(&mut self, key: Bytes, value: Bytes) -> ProtocolResult<()> {
         self.trie
             .insert(key.to_vec(), value.to_vec())
@@ -60,6 +72,19 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(())
     }
 
+    pub fn remove(&mut self, key: &Bytes) -> ProtocolResult<()> {
+        self.trie.remove(key).map_err(MPTTrieError::from)?;
+        Ok(())
+    }
+
+    /// Generate the proof of a key, it consists of the encoded nodes on the
+    /// path from the root to the key, so it proves the absence of the key as
+    /// well.
+    pub fn get_proof(&self, key: &Bytes) -> ProtocolResult<Vec<Bytes>> {
+        let proof = self.trie.get_proof(key).map_err(MPTTrieError::from)?;
+        Ok(proof.into_iter().map(Bytes::from).collect())
+    }
+
     pub fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
         let root_bytes = self.trie.root().map_err(MPTTrieError::from)?;
         let root = MerkleRoot::from_bytes(Bytes::from(root_bytes))?;
@@ -68,6 +93,22 @@ impl<DB: TrieDB> MPTTrie<DB> {
     }
 }
 
+/// Verify the proof of a key against the root without accessing any database,
+/// returns the value of the key or `None` if the absence of the key is proved.
+pub fn verify_proof(
+    root: &MerkleRoot,
+    key: &Bytes,
+    proof: Vec<Bytes>,
+) -> ProtocolResult<Option<Bytes>> {
+    let trie = PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::clone(&HASHER_INST));
+    let proof = proof.into_iter().map(|node| node.to_vec()).collect();
+
+    let value = trie
+        .verify_proof(&root.as_bytes(), key, proof)
+        .map_err(MPTTrieError::from)?;
+    Ok(value.map(Bytes::from))
+}
+
 #[derive(Debug, Display, From)]
 pub enum MPTTrieError {
     #[display(fmt = "{:?}", _0)]
@@ -0,0 +1,306 @@
+use std::collections::{HashSet, VecDeque};
+use std::mem;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Weak};
+use std::thread;
+
+use bytes::Bytes;
+use parking_lot::{Condvar, Mutex};
+use rlp::Rlp;
+use rocksdb::{IteratorMode, WriteBatch, DB};
+
+use protocol::types::{Hash, MerkleRoot};
+use protocol::ProtocolResult;
+
+use crate::trie::trie_db::RocksTrieDBError;
+
+// The length of the hash referencing a trie node, the smaller nodes are
+// embedded in their parents.
+const HASH_LEN: usize = 32;
+// Start a round of pruning after the roots of so many epochs are recorded.
+const PRUNE_INTERVAL: usize = 16;
+// The number of keys deleted in one write batch.
+const DELETE_BATCH_SIZE: usize = 1024;
+
+/// Delete the trie nodes which are not reachable from the state roots of the
+/// recent epochs.
+///
+/// The reachable nodes are marked by walking the world state trie from every
+/// kept root, and the trie of every contract from the roots found in its
+/// leaves. All other keys in the database are swept in the background.
+///
+/// The database must hold nothing but the trie nodes, keyed by the hashes of
+/// their encodings. Every key is checked before it is deleted, and a round
+/// stops at the first key which is not a trie node. The marked nodes are kept
+/// in memory during a round, it takes 32 bytes and the overhead of a hash set
+/// entry for every node of the kept states.
+///
+/// The nodes written while a round is running can not be marked, they belong
+/// to an epoch whose root may be recorded after the round started. So the keys
+/// written since the previous round started are never deleted, it covers an
+/// epoch whose execution is interleaved with the start of the round. Writing
+/// a key which is being deleted waits until the deletion is written.
+pub struct StatePruner {
+    db:          Arc<DB>,
+    keep_epochs: usize,
+
+    roots:    Mutex<VecDeque<MerkleRoot>>,
+    unpruned: Mutex<usize>,
+    written:  Mutex<WrittenKeys>,
+    // Notified once the keys being deleted are deleted.
+    deleted:    Condvar,
+    round_lock: Mutex<()>,
+    notify:     Mutex<Sender<()>>,
+}
+
+#[derive(Default)]
+struct WrittenKeys {
+    previous: HashSet<Vec<u8>>,
+    current:  HashSet<Vec<u8>>,
+    deleting: HashSet<Vec<u8>>,
+}
+
+impl StatePruner {
+    /// Create a pruner which keeps the state of the last `keep_epochs` epochs,
+    /// the rounds of pruning are run on a background thread.
+    pub fn new(db: Arc<DB>, keep_epochs: usize) -> Arc<Self> {
+        let (notify, rx) = channel();
+        let pruner = Arc::new(StatePruner {
+            db,
+            keep_epochs: keep_epochs.max(1),
+
+            roots: Mutex::new(VecDeque::new()),
+            unpruned: Mutex::new(0),
+            written: Mutex::new(WrittenKeys::default()),
+            deleted: Condvar::new(),
+            round_lock: Mutex::new(()),
+            notify: Mutex::new(notify),
+        });
+
+        let background = Arc::downgrade(&pruner);
+        thread::spawn(move || run(background, rx));
+
+        pruner
+    }
+
+    pub fn record_root(&self, state_root: &MerkleRoot) {
+        {
+            // An epoch without transactions has the same root as the previous
+            // one.
+            let mut roots = self.roots.lock();
+            roots.retain(|root| root != state_root);
+
+            roots.push_back(state_root.clone());
+            while roots.len() > self.keep_epochs {
+                roots.pop_front();
+            }
+        }
+
+        let mut unpruned = self.unpruned.lock();
+        *unpruned += 1;
+        if *unpruned >= PRUNE_INTERVAL {
+            *unpruned = 0;
+            // The background thread only exits after the pruner is dropped.
+            let _ = self.notify.lock().send(());
+        }
+    }
+
+    // Must be called before the key is written, so the key is either kept by
+    // the running round or written after it is deleted.
+    pub fn protect(&self, key: &[u8]) {
+        let mut written = self.written.lock();
+        while written.deleting.contains(key) {
+            self.deleted.wait(&mut written);
+        }
+        written.current.insert(key.to_vec());
+    }
+
+    /// Run a round of pruning, returns the number of deleted keys.
+    pub fn prune(&self) -> ProtocolResult<usize> {
+        let _round = self.round_lock.lock();
+
+        {
+            let mut written = self.written.lock();
+            written.previous = mem::replace(&mut written.current, HashSet::new());
+        }
+        let roots = self.roots.lock().clone();
+        if roots.is_empty() {
+            return Ok(0);
+        }
+
+        let mut reachable = HashSet::new();
+        let mut contract_roots = vec![];
+        for root in roots.iter() {
+            self.mark(&root.as_bytes(), &mut reachable, Some(&mut contract_roots))?;
+        }
+        for root in contract_roots.iter() {
+            self.mark(root, &mut reachable, None)?;
+        }
+
+        let mut deleted = 0;
+        let mut garbage = Vec::with_capacity(DELETE_BATCH_SIZE);
+        for (key, value) in self.db.iterator(IteratorMode::Start) {
+            if reachable.contains(key.as_ref()) {
+                continue;
+            }
+
+            let hash = Hash::digest(Bytes::from(value.as_ref()));
+            if key.len() != HASH_LEN || hash.as_bytes().as_ref() != key.as_ref() {
+                return Err(RocksTrieDBError::NotTrieNode {
+                    key: hex::encode(key),
+                }
+                .into());
+            }
+
+            garbage.push(key);
+            if garbage.len() >= DELETE_BATCH_SIZE {
+                deleted += self.delete(garbage.drain(..))?;
+            }
+        }
+        deleted += self.delete(garbage.drain(..))?;
+
+        Ok(deleted)
+    }
+
+    fn delete<K: AsRef<[u8]>>(&self, keys: impl Iterator<Item = K>) -> ProtocolResult<usize> {
+        // The keys to delete are taken under the lock, a key protected later
+        // waits until the batch is written.
+        let keys = {
+            let mut written = self.written.lock();
+            let keys = keys
+                .map(|key| key.as_ref().to_vec())
+                .filter(|key| !written.previous.contains(key) && !written.current.contains(key))
+                .collect::<Vec<_>>();
+            written.deleting.extend(keys.iter().cloned());
+            keys
+        };
+
+        let res = self.write_deletion(&keys);
+
+        self.written.lock().deleting.clear();
+        self.deleted.notify_all();
+        res.map(|_| keys.len())
+    }
+
+    fn write_deletion(&self, keys: &[Vec<u8>]) -> ProtocolResult<()> {
+        let mut batch = WriteBatch::default();
+        for key in keys.iter() {
+            batch.delete(key).map_err(RocksTrieDBError::from)?;
+        }
+
+        self.db.write(batch).map_err(RocksTrieDBError::from)?;
+        Ok(())
+    }
+
+    // Mark the nodes of the trie, the values in its leaves are collected to
+    // `sub_roots` if it is the world state trie.
+    fn mark(
+        &self,
+        hash: &[u8],
+        reachable: &mut HashSet<[u8; HASH_LEN]>,
+        mut sub_roots: Option<&mut Vec<Vec<u8>>>,
+    ) -> ProtocolResult<()> {
+        if hash.len() != HASH_LEN {
+            return Ok(());
+        }
+        let mut key = [0u8; HASH_LEN];
+        key.copy_from_slice(hash);
+        if !reachable.insert(key) {
+            return Ok(());
+        }
+
+        // The root of an empty trie may not be stored.
+        match self.db.get(hash).map_err(RocksTrieDBError::from)? {
+            Some(encoded) => self.mark_node(&Rlp::new(&encoded), reachable, &mut sub_roots),
+            None => Ok(()),
+        }
+    }
+
+    fn mark_node(
+        &self,
+        node: &Rlp,
+        reachable: &mut HashSet<[u8; HASH_LEN]>,
+        sub_roots: &mut Option<&mut Vec<Vec<u8>>>,
+    ) -> ProtocolResult<()> {
+        match node.item_count().map_err(RocksTrieDBError::from)? {
+            // Leaf or extension, the flag of a leaf is encoded in the first
+            // nibble of its compact path.
+            2 => {
+                let path = node
+                    .at(0)
+                    .and_then(|path| path.data())
+                    .map_err(RocksTrieDBError::from)?;
+                let child = node.at(1).map_err(RocksTrieDBError::from)?;
+
+                if path.first().map(|flag| flag >> 4 >= 2).unwrap_or(false) {
+                    self.collect_value(&child, sub_roots)
+                } else {
+                    self.mark_child(&child, reachable, sub_roots)
+                }
+            }
+            // Branch
+            17 => {
+                for i in 0..16 {
+                    let child = node.at(i).map_err(RocksTrieDBError::from)?;
+                    self.mark_child(&child, reachable, sub_roots)?;
+                }
+
+                let value = node.at(16).map_err(RocksTrieDBError::from)?;
+                self.collect_value(&value, sub_roots)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn mark_child(
+        &self,
+        child: &Rlp,
+        reachable: &mut HashSet<[u8; HASH_LEN]>,
+        sub_roots: &mut Option<&mut Vec<Vec<u8>>>,
+    ) -> ProtocolResult<()> {
+        if child.is_list() {
+            return self.mark_node(child, reachable, sub_roots);
+        }
+
+        let hash = child.data().map_err(RocksTrieDBError::from)?;
+        if hash.len() != HASH_LEN {
+            return Ok(());
+        }
+
+        self.mark(
+            hash,
+            reachable,
+            sub_roots.as_mut().map(|roots| &mut **roots),
+        )
+    }
+
+    fn collect_value(
+        &self,
+        value: &Rlp,
+        sub_roots: &mut Option<&mut Vec<Vec<u8>>>,
+    ) -> ProtocolResult<()> {
+        if let Some(sub_roots) = sub_roots {
+            let value = value.data().map_err(RocksTrieDBError::from)?;
+            if value.len() == HASH_LEN {
+                sub_roots.push(value.to_vec());
+            }
+        }
+        Ok(())
+    }
+}
+
+// The thread holds a weak reference, so it exits once the pruner and the
+// sender in it are dropped.
+fn run(pruner: Weak<StatePruner>, rx: Receiver<()>) {
+    while rx.recv().is_ok() {
+        let pruner = match pruner.upgrade() {
+            Some(pruner) => pruner,
+            None => return,
+        };
+
+        match pruner.prune() {
+            Ok(deleted) => log::debug!("[executor]: pruned {} state nodes", deleted),
+            Err(e) => log::warn!("[executor]: prune state {:?}", e),
+        }
+    }
+}
@@ -6,26 +6,71 @@ use bytes::Bytes;
 use derive_more::{Display, From};
 use rocksdb::{Options, WriteBatch, DB};
 
+use protocol::traits::executor::TrieDB;
+use protocol::types::MerkleRoot;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+use crate::trie::pruner::StatePruner;
+
 pub struct RocksTrieDB {
-    light: bool,
-    db:    Arc<DB>,
+    light:  bool,
+    db:     Arc<DB>,
+    pruner: Option<Arc<StatePruner>>,
 }
 
 impl RocksTrieDB {
     pub fn new<P: AsRef<Path>>(path: P, light: bool) -> ProtocolResult<Self> {
-        let mut opts = Options::default();
-        opts.create_if_missing(true);
-        opts.create_missing_column_families(true);
+        Ok(RocksTrieDB {
+            light,
+            db: Arc::new(open(path)?),
+            pruner: None,
+        })
+    }
 
-        let db = DB::open(&opts, path).map_err(RocksTrieDBError::from)?;
+    /// Open the database and keep the state of the last `keep_epochs` epochs,
+    /// the nodes not reachable from their state roots are deleted in the
+    /// background. The nodes are never deleted on `remove`, because they may
+    /// be shared by the kept roots.
+    pub fn with_pruning<P: AsRef<Path>>(path: P, keep_epochs: usize) -> ProtocolResult<Self> {
+        let db = Arc::new(open(path)?);
+        let pruner = StatePruner::new(Arc::clone(&db), keep_epochs);
 
         Ok(RocksTrieDB {
-            light,
-            db: Arc::new(db),
+            light: false,
+            db,
+            pruner: Some(pruner),
         })
     }
+
+    /// Run a round of pruning now, returns the number of deleted nodes.
+    pub fn prune(&self) -> ProtocolResult<usize> {
+        match &self.pruner {
+            Some(pruner) => pruner.prune(),
+            None => Ok(0),
+        }
+    }
+
+    fn protect(&self, key: &[u8]) {
+        if let Some(pruner) = &self.pruner {
+            pruner.protect(key);
+        }
+    }
+}
+
+fn open<P: AsRef<Path>>(path: P) -> ProtocolResult<DB> {
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+
+    Ok(DB::open(&opts, path).map_err(RocksTrieDBError::from)?)
+}
+
+impl TrieDB for RocksTrieDB {
+    fn record_root(&self, state_root: &MerkleRoot) {
+        if let Some(pruner) = &self.pruner {
+            pruner.record_root(state_root);
+        }
+    }
 }
 
 impl cita_trie::DB for RocksTrieDB {
@@ -44,6 +89,7 @@ impl cita_trie::DB for RocksTrieDB {
     }
 
     fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Self::Error> {
+        self.protect(&key);
         self.db
             .put(Bytes::from(key), Bytes::from(value))
             .map_err(RocksTrieDBError::from)?;
@@ -59,6 +105,7 @@ impl cita_trie::DB for RocksTrieDB {
         for i in 0..keys.len() {
             let key = &keys[i];
             let value = &values[i];
+            self.protect(key);
             batch.put(key, value).map_err(RocksTrieDBError::from)?;
         }
 
@@ -96,11 +143,17 @@ pub enum RocksTrieDBError {
     #[display(fmt = "rocksdb {}", _0)]
     RocksDB(rocksdb::Error),
 
+    #[display(fmt = "decode trie node {:?}", _0)]
+    Decode(rlp::DecoderError),
+
     #[display(fmt = "parameters do not match")]
     InsertParameter,
 
     #[display(fmt = "batch length dont match")]
     BatchLengthMismatch,
+
+    #[display(fmt = "key {} is not a trie node", key)]
+    NotTrieNode { key: String },
 }
 
 impl Error for RocksTrieDBError {}
@@ -0,0 +1,180 @@
+mod runtime;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+use parity_wasm::elements::{self, MemoryType, Module};
+use pwasm_utils::rules;
+use wasmi::{ImportsBuilder, ModuleInstance};
+
+use protocol::traits::executor::contract::ContractStateAdapter;
+use protocol::traits::executor::{Dispatcher, RcInvokeContext};
+use protocol::types::{ContractAddress, ContractType};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::wasm::runtime::{Runtime, RuntimeResolver};
+
+// 64KiB per page, a contract can use up to 1MiB memory.
+const MAX_MEMORY_PAGES: u32 = 16;
+// The cycles charged for growing one page of memory.
+const MEMORY_GROW_CYCLES: u32 = 8192;
+
+/// Validate the code of a contract and inject the cycles counter, the returned
+/// code is the one that should be stored and executed.
+pub fn prepare(code: &Bytes) -> ProtocolResult<Bytes> {
+    let mut module: Module =
+        parity_wasm::deserialize_buffer(code.as_ref()).map_err(WasmError::from)?;
+
+    if module.start_section().is_some() {
+        return Err(WasmError::StartFunction.into());
+    }
+
+    // A memory without a maximum could grow up to 4GiB, it is limited to
+    // `MAX_MEMORY_PAGES` instead.
+    if let Some(memory_section) = module.memory_section_mut() {
+        for entry in memory_section.entries_mut().iter_mut() {
+            let limits = entry.limits();
+            let initial = limits.initial();
+            let maximum = limits.maximum().unwrap_or(MAX_MEMORY_PAGES);
+
+            if initial > MAX_MEMORY_PAGES || maximum > MAX_MEMORY_PAGES {
+                return Err(WasmError::MemoryTooLarge {
+                    max: MAX_MEMORY_PAGES,
+                }
+                .into());
+            }
+            *entry = MemoryType::new(initial, Some(maximum));
+        }
+    }
+
+    let rules = rules::Set::default()
+        .with_grow_cost(MEMORY_GROW_CYCLES)
+        .with_forbidden_floats();
+    let module =
+        pwasm_utils::inject_gas_counter(module, &rules).map_err(|_| WasmError::CyclesInjection)?;
+
+    // Make sure the instrumented module is still a valid one.
+    let code = elements::serialize(module).map_err(WasmError::from)?;
+    wasmi::Module::from_buffer(&code).map_err(WasmError::from)?;
+
+    Ok(Bytes::from(code))
+}
+
+/// Execute the exported function `method` of a prepared contract code.
+///
+/// The arguments and the return value are passed through the host functions,
+/// see `Runtime` for details. A library contract is executed in readonly mode,
+/// it traps on any attempt to change the state.
+pub fn execute<D: Dispatcher, StateAdapter: ContractStateAdapter>(
+    dispatcher: &D,
+    ictx: RcInvokeContext,
+    address: &ContractAddress,
+    code: &Bytes,
+    method: &str,
+    args: Vec<Bytes>,
+    state_adapter: Rc<RefCell<StateAdapter>>,
+) -> ProtocolResult<Bytes> {
+    let readonly = address.contract_type() == ContractType::Library;
+
+    let module = wasmi::Module::from_buffer(code.as_ref()).map_err(WasmError::from)?;
+    let imports = ImportsBuilder::new().with_resolver("env", &RuntimeResolver);
+    let instance = ModuleInstance::new(&module, &imports)
+        .map_err(WasmError::from)?
+        .assert_no_start();
+
+    let memory = instance
+        .export_by_name("memory")
+        .and_then(|export| export.as_memory().cloned())
+        .ok_or(WasmError::MemoryNotFound)?;
+
+    match instance.export_by_name(method) {
+        Some(ref export) if export.as_func().is_some() => (),
+        _ => {
+            return Err(WasmError::MethodNotFound {
+                method: method.to_owned(),
+            }
+            .into())
+        }
+    }
+
+    let mut runtime = Runtime::new(
+        dispatcher,
+        ictx,
+        address.clone(),
+        memory,
+        args,
+        state_adapter,
+        readonly,
+    );
+
+    let res = instance.invoke_export(method, &[], &mut runtime);
+    // The error raised by a host function takes precedence over the trap it
+    // causes.
+    if let Some(err) = runtime.take_error() {
+        return Err(err);
+    }
+    res.map_err(WasmError::from)?;
+
+    Ok(runtime.into_return_value())
+}
+
+#[derive(Debug, Display, From)]
+pub enum WasmError {
+    #[display(fmt = "invalid wasm module {:?}", _0)]
+    Deserialize(elements::Error),
+
+    #[display(fmt = "wasm {:?}", _0)]
+    Wasmi(wasmi::Error),
+
+    #[display(fmt = "failed to inject cycles counter")]
+    CyclesInjection,
+
+    #[display(fmt = "start function is not allowed")]
+    StartFunction,
+
+    #[display(fmt = "memory exceeds {} pages", max)]
+    MemoryTooLarge { max: u32 },
+
+    #[display(fmt = "memory is not exported")]
+    MemoryNotFound,
+
+    #[display(fmt = "memory access out of bounds")]
+    MemoryAccess,
+
+    #[display(fmt = "method {} not found", method)]
+    MethodNotFound { method: String },
+
+    #[display(fmt = "host function {} not found", name)]
+    HostFunctionNotFound { name: String },
+
+    #[display(fmt = "argument {} not found", index)]
+    ArgNotFound { index: u32 },
+
+    #[display(fmt = "invalid args")]
+    InvalidArgs,
+
+    #[display(fmt = "library contract can not change the state")]
+    ReadOnly,
+
+    #[display(fmt = "an event can have at most {} topics", max)]
+    TooManyTopics { max: u32 },
+
+    #[display(fmt = "contract aborted: {}", msg)]
+    Abort { msg: String },
+
+    #[display(fmt = "host function failed")]
+    HostCall,
+}
+
+impl Error for WasmError {}
+
+impl wasmi::HostError for WasmError {}
+
+impl From<WasmError> for ProtocolError {
+    fn from(err: WasmError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
@@ -0,0 +1,412 @@
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use wasmi::{
+    Externals, FuncInstance, FuncRef, MemoryRef, ModuleImportResolver, RuntimeArgs, RuntimeValue,
+    Signature, Trap, TrapKind, ValueType,
+};
+
+use protocol::traits::executor::contract::ContractStateAdapter;
+use protocol::traits::executor::{Dispatcher, RcInvokeContext};
+use protocol::types::{Address, ContractAddress, Hash, Log};
+use protocol::{ProtocolError, ProtocolResult};
+
+use crate::cycles::{charge_cycles, consume_cycles, CyclesAction, CyclesError};
+use crate::fixed_types::{FixedBytes, FixedStorageKey, FixedStorageSchema};
+use crate::wasm::WasmError;
+
+const ADDRESS_LEN: usize = 21;
+
+// Index of the host functions.
+const GAS: usize = 0;
+const ARGS_COUNT: usize = 1;
+const ARG_LEN: usize = 2;
+const ARG_COPY: usize = 3;
+const STORAGE_GET: usize = 4;
+const STORAGE_SET: usize = 5;
+const INVOKE: usize = 6;
+const RETURN_DATA_COPY: usize = 7;
+const CALLER: usize = 8;
+const ADDRESS: usize = 9;
+const RET: usize = 10;
+const ABORT: usize = 11;
+const LOG: usize = 12;
+
+const TOPIC_LEN: usize = 32;
+// An event can be indexed by up to 4 topics.
+const MAX_TOPICS: u32 = 4;
+
+/// Resolve the host functions imported from the `env` module.
+///
+/// `storage_get`, `storage_set`, `invoke` and `log` are charged the
+/// `host_call` cycles per call and the `host_byte` cycles per byte passed.
+///
+/// - gas(cycles: i32), injected by the executor to meter cycles.
+/// - args_count() -> i32
+/// - arg_len(index: i32) -> i32
+/// - arg_copy(index: i32, ptr: i32)
+/// - storage_get(key_ptr: i32, key_len: i32) -> i32, -1 if the key does not
+///   exist.
+/// - storage_set(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32)
+/// - invoke(address_ptr: i32, method_ptr: i32, method_len: i32, args_ptr: i32,
+///   args_len: i32) -> i32, the args are encoded as a rlp list of bytes. -1 if
+///   the call fails, its changes are reverted and the caller can carry on,
+///   unless the cycles run out.
+/// - return_data_copy(ptr: i32), copy the data returned by the last
+///   `storage_get` or `invoke`.
+/// - caller(ptr: i32), 21 bytes address.
+/// - address(ptr: i32), 21 bytes address.
+/// - ret(ptr: i32, len: i32), set the return value.
+/// - abort(ptr: i32, len: i32), abort with a utf8 message.
+/// - log(topics_ptr: i32, topics_count: i32, data_ptr: i32, data_len: i32),
+///   emit an event, each topic is a 32 bytes hash.
+pub struct RuntimeResolver;
+
+impl ModuleImportResolver for RuntimeResolver {
+    fn resolve_func(
+        &self,
+        field_name: &str,
+        signature: &Signature,
+    ) -> Result<FuncRef, wasmi::Error> {
+        let (index, params, ret): (usize, &[ValueType], Option<ValueType>) = match field_name {
+            "gas" => (GAS, &[ValueType::I32][..], None),
+            "args_count" => (ARGS_COUNT, &[][..], Some(ValueType::I32)),
+            "arg_len" => (ARG_LEN, &[ValueType::I32][..], Some(ValueType::I32)),
+            "arg_copy" => (ARG_COPY, &[ValueType::I32; 2][..], None),
+            "storage_get" => (STORAGE_GET, &[ValueType::I32; 2][..], Some(ValueType::I32)),
+            "storage_set" => (STORAGE_SET, &[ValueType::I32; 4][..], None),
+            "invoke" => (INVOKE, &[ValueType::I32; 5][..], Some(ValueType::I32)),
+            "return_data_copy" => (RETURN_DATA_COPY, &[ValueType::I32][..], None),
+            "caller" => (CALLER, &[ValueType::I32][..], None),
+            "address" => (ADDRESS, &[ValueType::I32][..], None),
+            "ret" => (RET, &[ValueType::I32; 2][..], None),
+            "abort" => (ABORT, &[ValueType::I32; 2][..], None),
+            "log" => (LOG, &[ValueType::I32; 4][..], None),
+            _ => {
+                return Err(wasmi::Error::Instantiation(
+                    WasmError::HostFunctionNotFound {
+                        name: field_name.to_owned(),
+                    }
+                    .to_string(),
+                ))
+            }
+        };
+
+        let expected = Signature::new(params, ret);
+        if signature != &expected {
+            return Err(wasmi::Error::Instantiation(format!(
+                "host function {} expects signature {:?}",
+                field_name, expected
+            )));
+        }
+
+        Ok(FuncInstance::alloc_host(expected, index))
+    }
+}
+
+pub struct Runtime<'a, D: Dispatcher, StateAdapter: ContractStateAdapter> {
+    dispatcher:    &'a D,
+    ictx:          RcInvokeContext,
+    address:       ContractAddress,
+    memory:        MemoryRef,
+    args:          Vec<Bytes>,
+    state_adapter: Rc<RefCell<StateAdapter>>,
+    readonly:      bool,
+
+    return_data:  Bytes,
+    return_value: Bytes,
+    error:        Option<ProtocolError>,
+}
+
+impl<'a, D: Dispatcher, StateAdapter: ContractStateAdapter> Runtime<'a, D, StateAdapter> {
+    pub fn new(
+        dispatcher: &'a D,
+        ictx: RcInvokeContext,
+        address: ContractAddress,
+        memory: MemoryRef,
+        args: Vec<Bytes>,
+        state_adapter: Rc<RefCell<StateAdapter>>,
+        readonly: bool,
+    ) -> Self {
+        Self {
+            dispatcher,
+            ictx,
+            address,
+            memory,
+            args,
+            state_adapter,
+            readonly,
+
+            return_data: Bytes::new(),
+            return_value: Bytes::new(),
+            error: None,
+        }
+    }
+
+    pub fn take_error(&mut self) -> Option<ProtocolError> {
+        self.error.take()
+    }
+
+    pub fn into_return_value(self) -> Bytes {
+        self.return_value
+    }
+
+    fn gas(&mut self, cycles: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        let mut fee = self.ictx.borrow().cycles_used.clone();
+        charge_cycles(
+            u64::from(cycles),
+            &mut fee,
+            &self.ictx.borrow().cycles_limit,
+        )?;
+        self.ictx.borrow_mut().cycles_used = fee;
+        Ok(None)
+    }
+
+    fn arg_len(&self, index: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        let arg = self.get_arg(index)?;
+        Ok(Some(RuntimeValue::I32(arg.len() as i32)))
+    }
+
+    fn arg_copy(&self, index: u32, ptr: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        let arg = self.get_arg(index)?;
+        self.write_memory(ptr, arg.as_ref())?;
+        Ok(None)
+    }
+
+    fn storage_get(&mut self, key_ptr: u32, key_len: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        self.charge_host_call(key_len)?;
+        let key = self.read_memory(key_ptr, key_len)?;
+        let value = self
+            .state_adapter
+            .borrow()
+            .get::<FixedStorageSchema>(&FixedStorageKey::new(key))?;
+
+        match value {
+            Some(value) => {
+                consume_cycles(&self.ictx, CyclesAction::HostBytes(value.inner.len()))?;
+                let len = value.inner.len() as i32;
+                self.return_data = value.inner;
+                Ok(Some(RuntimeValue::I32(len)))
+            }
+            None => {
+                self.return_data = Bytes::new();
+                Ok(Some(RuntimeValue::I32(-1)))
+            }
+        }
+    }
+
+    fn storage_set(
+        &mut self,
+        key_ptr: u32,
+        key_len: u32,
+        value_ptr: u32,
+        value_len: u32,
+    ) -> ProtocolResult<Option<RuntimeValue>> {
+        if self.readonly {
+            return Err(WasmError::ReadOnly.into());
+        }
+
+        self.charge_host_call(key_len.saturating_add(value_len))?;
+        let key = self.read_memory(key_ptr, key_len)?;
+        let value = self.read_memory(value_ptr, value_len)?;
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedStorageSchema>(
+                FixedStorageKey::new(key),
+                FixedBytes::new(value),
+            )?;
+        Ok(None)
+    }
+
+    fn invoke(
+        &mut self,
+        address_ptr: u32,
+        method_ptr: u32,
+        method_len: u32,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> ProtocolResult<Option<RuntimeValue>> {
+        // Any contract may change the state, so a library is not allowed to
+        // invoke others.
+        if self.readonly {
+            return Err(WasmError::ReadOnly.into());
+        }
+
+        self.charge_host_call(method_len.saturating_add(args_len))?;
+        let address =
+            ContractAddress::from_bytes(self.read_memory(address_ptr, ADDRESS_LEN as u32)?)?;
+        let method = String::from_utf8(self.read_memory(method_ptr, method_len)?.to_vec())
+            .map_err(|_| WasmError::InvalidArgs)?;
+        let args = rlp::Rlp::new(self.read_memory(args_ptr, args_len)?.as_ref())
+            .as_list::<Vec<u8>>()
+            .map_err(|_| WasmError::InvalidArgs)?
+            .into_iter()
+            .map(Bytes::from)
+            .collect::<Vec<_>>();
+
+        // The callee sees this contract as its caller, the carrying asset has
+        // already been paid to this contract.
+        let (caller, carrying_asset) = {
+            let mut ictx = self.ictx.borrow_mut();
+            let caller = mem::replace(&mut ictx.caller, Address::Contract(self.address.clone()));
+            (caller, ictx.carrying_asset.take())
+        };
+
+        let res = self
+            .dispatcher
+            .invoke(Rc::clone(&self.ictx), address, &method, args);
+
+        {
+            let mut ictx = self.ictx.borrow_mut();
+            ictx.caller = caller;
+            ictx.carrying_asset = carrying_asset;
+        }
+
+        match res {
+            Ok(return_data) => {
+                let len = return_data.len() as i32;
+                self.return_data = return_data;
+                Ok(Some(RuntimeValue::I32(len)))
+            }
+            // The caller can't carry on without cycles.
+            Err(err) if err.downcast_ref::<CyclesError>().is_some() => Err(err),
+            Err(_) => {
+                self.return_data = Bytes::new();
+                Ok(Some(RuntimeValue::I32(-1)))
+            }
+        }
+    }
+
+    fn return_data_copy(&self, ptr: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        self.write_memory(ptr, self.return_data.as_ref())?;
+        Ok(None)
+    }
+
+    fn caller(&self, ptr: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        let caller = self.ictx.borrow().caller.as_bytes();
+        self.write_memory(ptr, caller.as_ref())?;
+        Ok(None)
+    }
+
+    fn address(&self, ptr: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        self.write_memory(ptr, self.address.as_bytes().as_ref())?;
+        Ok(None)
+    }
+
+    fn ret(&mut self, ptr: u32, len: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        self.return_value = self.read_memory(ptr, len)?;
+        Ok(None)
+    }
+
+    fn abort(&self, ptr: u32, len: u32) -> ProtocolResult<Option<RuntimeValue>> {
+        let msg = String::from_utf8_lossy(self.read_memory(ptr, len)?.as_ref()).into_owned();
+        Err(WasmError::Abort { msg }.into())
+    }
+
+    fn log(
+        &mut self,
+        topics_ptr: u32,
+        topics_count: u32,
+        data_ptr: u32,
+        data_len: u32,
+    ) -> ProtocolResult<Option<RuntimeValue>> {
+        if self.readonly {
+            return Err(WasmError::ReadOnly.into());
+        }
+        if topics_count > MAX_TOPICS {
+            return Err(WasmError::TooManyTopics { max: MAX_TOPICS }.into());
+        }
+
+        self.charge_host_call((topics_count * TOPIC_LEN as u32).saturating_add(data_len))?;
+        let topics = self
+            .read_memory(topics_ptr, topics_count * TOPIC_LEN as u32)?
+            .chunks(TOPIC_LEN)
+            .map(|topic| Hash::from_bytes(Bytes::from(topic)))
+            .collect::<ProtocolResult<Vec<_>>>()?;
+        let data = self.read_memory(data_ptr, data_len)?;
+
+        self.ictx.borrow_mut().logs.push(Log {
+            address: self.address.clone(),
+            topics,
+            data,
+        });
+        Ok(None)
+    }
+
+    // Charge a host call passing `len` bytes, before the bytes are read.
+    fn charge_host_call(&self, len: u32) -> ProtocolResult<()> {
+        consume_cycles(&self.ictx, CyclesAction::HostCall)?;
+        consume_cycles(&self.ictx, CyclesAction::HostBytes(len as usize))
+    }
+
+    fn get_arg(&self, index: u32) -> ProtocolResult<&Bytes> {
+        self.args
+            .get(index as usize)
+            .ok_or_else(|| WasmError::ArgNotFound { index }.into())
+    }
+
+    fn read_memory(&self, ptr: u32, len: u32) -> ProtocolResult<Bytes> {
+        let data = self
+            .memory
+            .get(ptr, len as usize)
+            .map_err(|_| WasmError::MemoryAccess)?;
+        Ok(Bytes::from(data))
+    }
+
+    fn write_memory(&self, ptr: u32, data: &[u8]) -> ProtocolResult<()> {
+        self.memory
+            .set(ptr, data)
+            .map_err(|_| WasmError::MemoryAccess)?;
+        Ok(())
+    }
+}
+
+impl<'a, D: Dispatcher, StateAdapter: ContractStateAdapter> Externals
+    for Runtime<'a, D, StateAdapter>
+{
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let res = match index {
+            GAS => self.gas(args.nth_checked(0)?),
+            ARGS_COUNT => Ok(Some(RuntimeValue::I32(self.args.len() as i32))),
+            ARG_LEN => self.arg_len(args.nth_checked(0)?),
+            ARG_COPY => self.arg_copy(args.nth_checked(0)?, args.nth_checked(1)?),
+            STORAGE_GET => self.storage_get(args.nth_checked(0)?, args.nth_checked(1)?),
+            STORAGE_SET => self.storage_set(
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+                args.nth_checked(3)?,
+            ),
+            INVOKE => self.invoke(
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+                args.nth_checked(3)?,
+                args.nth_checked(4)?,
+            ),
+            RETURN_DATA_COPY => self.return_data_copy(args.nth_checked(0)?),
+            CALLER => self.caller(args.nth_checked(0)?),
+            ADDRESS => self.address(args.nth_checked(0)?),
+            RET => self.ret(args.nth_checked(0)?, args.nth_checked(1)?),
+            ABORT => self.abort(args.nth_checked(0)?, args.nth_checked(1)?),
+            LOG => self.log(
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+                args.nth_checked(3)?,
+            ),
+            _ => return Err(Trap::new(TrapKind::UnexpectedSignature)),
+        };
+
+        res.map_err(|err| {
+            self.error = Some(err);
+            Trap::new(TrapKind::Host(Box::new(WasmError::HostCall)))
+        })
+    }
+}
@@ -2,6 +2,7 @@ pub mod message;
 mod rlp_types;
 
 use std::{
+    collections::HashMap,
     marker::PhantomData,
     sync::{
         atomic::{AtomicU64, Ordering},
@@ -11,11 +12,13 @@ use std::{
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use parking_lot::RwLock;
 
 use common_crypto::Crypto;
 use protocol::{
+    traits::executor::{ExecutorFactory, TrieDB},
     traits::{Context, Gossip, MemPoolAdapter, Priority, Rpc, Storage},
-    types::{Hash, SignedTransaction},
+    types::{next_cycles_price, Address, Epoch, Hash, MerkleRoot, SignedTransaction},
     ProtocolResult,
 };
 
@@ -25,38 +28,177 @@ use crate::adapter::message::{
 use crate::adapter::rlp_types::RlpRawTransaction;
 use crate::MemPoolError;
 
-pub struct DefaultMemPoolAdapter<C, N, S> {
+pub struct DefaultMemPoolAdapter<C, N, S, EF, DB> {
     network: N,
     storage: Arc<S>,
+    trie_db: Arc<DB>,
+    // Builds the executors to check the transactions against the state.
+    executor_factory: Arc<EF>,
 
     timeout_gap: AtomicU64,
 
+    /// The nonces admitted within the timeout window, Hash(sender + nonce) ->
+    /// timeout.
+    nonce_window: RwLock<HashMap<Hash, u64>>,
+    pruned_epoch_id: AtomicU64,
+    // The state after the latest epoch, computed once per epoch.
+    latest_state: RwLock<Option<LatestState>>,
+
     pin_c: PhantomData<C>,
 }
 
-impl<C, N, S> DefaultMemPoolAdapter<C, N, S>
+#[derive(Clone)]
+struct LatestState {
+    epoch_id:   u64,
+    state_root: MerkleRoot,
+    // The base fee of the epoch the transactions are packed into.
+    cycles_price: u64,
+}
+
+impl<C, N, S, EF, DB> DefaultMemPoolAdapter<C, N, S, EF, DB>
 where
     C: Crypto,
     N: Rpc + Gossip,
     S: Storage,
+    EF: ExecutorFactory<DB>,
+    DB: TrieDB,
 {
-    pub fn new(network: N, storage: Arc<S>, timeout_gap: u64) -> Self {
+    pub fn new(
+        network: N,
+        storage: Arc<S>,
+        trie_db: Arc<DB>,
+        executor_factory: Arc<EF>,
+        timeout_gap: u64,
+    ) -> Self {
         DefaultMemPoolAdapter {
             network,
             storage,
+            trie_db,
+            executor_factory,
             timeout_gap: AtomicU64::new(timeout_gap),
 
+            nonce_window: RwLock::new(HashMap::new()),
+            pruned_epoch_id: AtomicU64::new(0),
+            latest_state: RwLock::new(None),
+
             pin_c: PhantomData,
         }
     }
+
+    // A nonce can be used only once by the same sender. Since a transaction
+    // can't be committed after its timeout, the nonce is forgotten then.
+    fn check_nonce(&self, stx: &SignedTransaction, latest_epoch_id: u64) -> ProtocolResult<()> {
+        let key = nonce_key(stx)?;
+        let mut nonce_window = self.nonce_window.write();
+
+        if self.pruned_epoch_id.load(Ordering::SeqCst) < latest_epoch_id {
+            nonce_window.retain(|_, timeout| *timeout >= latest_epoch_id);
+            self.pruned_epoch_id
+                .store(latest_epoch_id, Ordering::SeqCst);
+        }
+
+        if nonce_window.contains_key(&key) {
+            return Err(MemPoolError::ReusedNonce {
+                tx_hash: stx.tx_hash.clone(),
+                nonce:   stx.raw.nonce.clone(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    // The header carries the state root and the cycles used of the epoch
+    // before, the ones of the latest epoch are in its receipts. The base fee
+    // of the next epoch follows them the same as the consensus computes it,
+    // the chain without the governance contract keeps the latest price.
+    async fn latest_state(&self, latest_epoch: &Epoch) -> ProtocolResult<LatestState> {
+        let header = &latest_epoch.header;
+        if let Some(state) = self.latest_state.read().as_ref() {
+            if state.epoch_id == header.epoch_id {
+                return Ok(state.clone());
+            }
+        }
+
+        let receipts = self
+            .storage
+            .get_receipts(latest_epoch.ordered_tx_hashes.clone())
+            .await?;
+        let state_root = match receipts.last() {
+            Some(receipt) => receipt.state_root.clone(),
+            None => header.state_root.clone(),
+        };
+        let cycles_used = receipts.iter().fold(0u64, |acc, receipt| {
+            acc.saturating_add(receipt.cycles_used.cycle)
+        });
+
+        let params = self
+            .executor_factory
+            .from_root(
+                header.chain_id.clone(),
+                state_root.clone(),
+                Arc::clone(&self.trie_db),
+                header.epoch_id + 1,
+                header.cycles_price,
+                Address::User(header.proposer.clone()),
+                false,
+            )?
+            .get_chain_params()?;
+        let cycles_price = match params {
+            Some(params) => {
+                next_cycles_price(header.cycles_price, cycles_used, params.cycles_limit)
+                    .max(params.cycles_price)
+            }
+            None => header.cycles_price,
+        };
+
+        let state = LatestState {
+            epoch_id: header.epoch_id,
+            state_root,
+            cycles_price,
+        };
+        *self.latest_state.write() = Some(state.clone());
+        Ok(state)
+    }
+
+    // Check the transaction against the state committed by the latest epoch
+    // the same as a proposal is checked. The nonces recorded on chain are
+    // kept across restarts, unlike the nonce window.
+    fn check_state(
+        &self,
+        stx: &SignedTransaction,
+        latest_epoch: &Epoch,
+        latest_state: LatestState,
+    ) -> ProtocolResult<()> {
+        let header = &latest_epoch.header;
+        let executor = self.executor_factory.from_root(
+            header.chain_id.clone(),
+            latest_state.state_root,
+            Arc::clone(&self.trie_db),
+            header.epoch_id + 1,
+            latest_state.cycles_price,
+            Address::User(header.proposer.clone()),
+            false,
+        )?;
+        executor.check_tx(stx)
+    }
+}
+
+fn nonce_key(stx: &SignedTransaction) -> ProtocolResult<Hash> {
+    let sender = stx.sender()?;
+    Ok(Hash::digest(Bytes::from(
+        [sender.as_bytes(), stx.raw.nonce.as_bytes()].concat(),
+    )))
 }
 
 #[async_trait]
-impl<C, N, S> MemPoolAdapter for DefaultMemPoolAdapter<C, N, S>
+impl<C, N, S, EF, DB> MemPoolAdapter for DefaultMemPoolAdapter<C, N, S, EF, DB>
 where
     C: Crypto + Send + Sync + 'static,
     N: Rpc + Gossip + 'static,
     S: Storage + 'static,
+    EF: ExecutorFactory<DB> + 'static,
+    DB: TrieDB + 'static,
 {
     async fn pull_txs(
         &self,
@@ -82,34 +224,14 @@ where
     }
 
     async fn check_signature(&self, _ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
-        let hash = tx.tx_hash.as_bytes();
-        let pub_key = tx.pubkey.as_ref();
-        let sig = tx.signature.as_ref();
-
-        C::verify_signature(hash.as_ref(), sig, pub_key).map_err(|_| {
-            MemPoolError::CheckSig {
-                tx_hash: tx.tx_hash,
-            }
-            .into()
-        })
+        verify_signature::<C>(&tx)
     }
 
     // TODO: Verify Fee?
-    // TODO: Verify Nonce?
     // TODO: Cycle limit?
     async fn check_transaction(&self, _ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
         // Verify transaction hash
-        let rlp_stx = rlp::encode(&RlpRawTransaction { inner: &stx.raw });
-        let stx_hash = Hash::digest(Bytes::from(rlp_stx));
-
-        if stx_hash != stx.tx_hash {
-            let wrong_hash = MemPoolError::CheckHash {
-                expect: stx.tx_hash,
-                actual: stx_hash,
-            };
-
-            return Err(wrong_hash.into());
-        }
+        verify_tx_hash(&stx)?;
 
         // Verify chain id
         let latest_epoch = self.storage.get_latest_epoch().await?;
@@ -142,6 +264,31 @@ where
             return Err(timeout.into());
         }
 
+        // The base fee may change again in the epochs after the next one, the
+        // executor checks the price again.
+        let latest_state = self.latest_state(&latest_epoch).await?;
+        if stx.raw.max_cycles_price < latest_state.cycles_price {
+            let low_price = MemPoolError::CyclesPriceTooLow {
+                tx_hash:          stx.tx_hash,
+                max_cycles_price: stx.raw.max_cycles_price,
+                cycles_price:     latest_state.cycles_price,
+            };
+
+            return Err(low_price.into());
+        }
+
+        // Verify nonce
+        self.check_nonce(&stx, latest_epoch_id)?;
+        self.check_state(&stx, &latest_epoch, latest_state)?;
+
+        Ok(())
+    }
+
+    // Two transactions of the same nonce checked at the same time may both be
+    // accepted, the executor rejects the later one.
+    async fn record_nonce(&self, _ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
+        let key = nonce_key(&stx)?;
+        self.nonce_window.write().insert(key, stx.raw.timeout);
         Ok(())
     }
 
@@ -159,3 +306,74 @@ where
         }
     }
 }
+
+// A transaction is signed by its single key, or by at least `threshold` keys
+// of its multi-signature.
+pub fn verify_tx_hash(tx: &SignedTransaction) -> ProtocolResult<()> {
+    let rlp_tx = rlp::encode(&RlpRawTransaction { inner: &tx.raw });
+    let tx_hash = Hash::digest(Bytes::from(rlp_tx));
+
+    if tx_hash != tx.tx_hash {
+        let wrong_hash = MemPoolError::CheckHash {
+            expect: tx.tx_hash.clone(),
+            actual: tx_hash,
+        };
+
+        return Err(wrong_hash.into());
+    }
+
+    Ok(())
+}
+
+pub fn verify_signature<C: Crypto>(tx: &SignedTransaction) -> ProtocolResult<()> {
+    let hash = tx.tx_hash.as_bytes();
+
+    let multisig = match &tx.multisig {
+        Some(multisig) => multisig,
+        None => {
+            let pub_key = tx.pubkey.as_ref();
+            let sig = tx.signature.as_ref();
+
+            return C::verify_signature(hash.as_ref(), sig, pub_key).map_err(|_| {
+                MemPoolError::CheckSig {
+                    tx_hash: tx.tx_hash.clone(),
+                }
+                .into()
+            });
+        }
+    };
+
+    // Every given signature must be valid, and at least `threshold` keys
+    // must sign.
+    if multisig.signatures.len() != multisig.pubkeys.len() {
+        return Err(MemPoolError::CheckSig {
+            tx_hash: tx.tx_hash.clone(),
+        }
+        .into());
+    }
+
+    let mut signed = 0;
+    for (pub_key, sig) in multisig.pubkeys.iter().zip(multisig.signatures.iter()) {
+        if sig.is_empty() {
+            continue;
+        }
+
+        C::verify_signature(hash.as_ref(), sig.as_ref(), pub_key.as_ref()).map_err(|_| {
+            MemPoolError::CheckSig {
+                tx_hash: tx.tx_hash.clone(),
+            }
+        })?;
+        signed += 1;
+    }
+
+    if signed < usize::from(multisig.threshold) {
+        return Err(MemPoolError::InsufficientSignatures {
+            tx_hash: tx.tx_hash.clone(),
+            signed,
+            threshold: multisig.threshold,
+        }
+        .into());
+    }
+
+    Ok(())
+}
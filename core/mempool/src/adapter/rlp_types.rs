@@ -6,10 +6,11 @@ pub struct RlpRawTransaction<'a> {
     pub inner: &'a RawTransaction,
 }
 
-const TRANSFER_TRANSACTION_FIELD_LENGTH: usize = 8;
-const APPROVE_TRANSACTION_FIELD_LENGTH: usize = 9;
-const DEPLOY_TRANSACTION_FIELD_LENGTH: usize = 7;
-const CALL_TRANSACTION_FIELD_LENGTH: usize = 10;
+const TRANSFER_TRANSACTION_FIELD_LENGTH: usize = 9;
+const APPROVE_TRANSACTION_FIELD_LENGTH: usize = 10;
+const DEPLOY_TRANSACTION_FIELD_LENGTH: usize = 9;
+const CALL_TRANSACTION_FIELD_LENGTH: usize = 11;
+const BATCH_TRANSACTION_FIELD_LENGTH: usize = 7;
 
 impl<'a> Encodable for RlpRawTransaction<'a> {
     fn rlp_append(&self, s: &mut RlpStream) {
@@ -19,6 +20,7 @@ impl<'a> Encodable for RlpRawTransaction<'a> {
             TransactionAction::Approve { .. } => APPROVE_TRANSACTION_FIELD_LENGTH,
             TransactionAction::Deploy { .. } => DEPLOY_TRANSACTION_FIELD_LENGTH,
             TransactionAction::Call { .. } => CALL_TRANSACTION_FIELD_LENGTH,
+            TransactionAction::Batch { .. } => BATCH_TRANSACTION_FIELD_LENGTH,
         };
 
         s.begin_list(list_size);
@@ -27,42 +29,109 @@ impl<'a> Encodable for RlpRawTransaction<'a> {
         s.append(&inner.fee.asset_id.as_bytes().to_vec());
         s.append(&inner.nonce.as_bytes().to_vec());
         s.append(&inner.timeout);
+        s.append(&inner.max_cycles_price);
+        append_action(s, &inner.action);
+    }
+}
 
-        match &inner.action {
-            TransactionAction::Transfer {
-                receiver,
-                carrying_asset,
-            } => {
-                s.append(&carrying_asset.amount.to_bytes_be());
-                s.append(&carrying_asset.asset_id.as_bytes().to_vec());
-                s.append(&receiver.as_bytes().to_vec());
+// An action in a batch is a list of its kind and its fields, the kinds can not
+// be told apart by the number of fields.
+struct RlpBatchAction<'a> {
+    inner: &'a TransactionAction,
+}
+
+impl<'a> Encodable for RlpBatchAction<'a> {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let (kind, field_length): (u32, usize) = match self.inner {
+            TransactionAction::Transfer { .. } => (0, 3),
+            TransactionAction::Approve { .. } => (1, 3),
+            TransactionAction::Deploy { .. } => (2, 3),
+            TransactionAction::Call { .. } => (3, 5),
+            TransactionAction::Batch { .. } => (4, 1),
+        };
+
+        s.begin_list(field_length + 1);
+        s.append(&kind);
+        append_action(s, self.inner);
+    }
+}
+
+fn append_action(s: &mut RlpStream, action: &TransactionAction) {
+    match action {
+        TransactionAction::Transfer {
+            receiver,
+            carrying_asset,
+        } => {
+            s.append(&carrying_asset.amount.to_bytes_be());
+            s.append(&carrying_asset.asset_id.as_bytes().to_vec());
+            s.append(&receiver.as_bytes().to_vec());
+        }
+        TransactionAction::Approve {
+            spender,
+            asset_id,
+            max,
+        } => {
+            s.append(&asset_id.as_bytes().to_vec());
+            s.append(&max.to_bytes_be());
+            s.append(&spender.as_bytes().to_vec());
+        }
+        TransactionAction::Deploy {
+            code,
+            contract_type,
+            asset,
+        } => {
+            s.append(&code.to_vec());
+            let type_flag: u32 = match contract_type {
+                ContractType::Asset => 0,
+                ContractType::App => 1,
+                ContractType::Library => 2,
+                ContractType::Native => 3,
+            };
+            s.append(&type_flag);
+
+            // An empty data stands for no asset.
+            if let Some(asset) = asset {
+                s.begin_list(5);
+                s.append(&asset.name.as_bytes());
+                s.append(&asset.symbol.as_bytes());
+                s.append(&asset.supply.to_bytes_be());
+                s.append(&asset.decimals);
+                match &asset.manage_contract {
+                    Some(address) => s.append(&address.as_bytes().to_vec()),
+                    None => s.append_empty_data(),
+                };
+            } else {
+                s.append_empty_data();
             }
-            TransactionAction::Approve {
-                spender,
-                asset_id,
-                max,
-            } => {
-                s.append(&asset_id.as_bytes().to_vec());
-                s.append(&max.to_bytes_be());
-                s.append(&spender.as_bytes().to_vec());
+        }
+        TransactionAction::Call {
+            contract,
+            method,
+            args,
+            carrying_asset,
+        } => {
+            s.append(&contract.as_bytes().to_vec());
+            s.append(&method.as_bytes());
+
+            s.begin_list(args.len());
+            for arg in args.iter() {
+                s.append(&arg.to_vec());
             }
-            TransactionAction::Deploy {
-                code,
-                contract_type,
-            } => {
-                s.append(&code.to_vec());
-                let type_flag: u32 = match contract_type {
-                    ContractType::Asset => 0,
-                    ContractType::App => 1,
-                    ContractType::Library => 2,
-                    ContractType::Native => 3,
-                };
-                s.append(&type_flag);
+
+            // An empty amount and asset id stand for no carrying asset.
+            if let Some(carrying_asset) = carrying_asset {
+                s.append(&carrying_asset.amount.to_bytes_be());
+                s.append(&carrying_asset.asset_id.as_bytes().to_vec());
+            } else {
+                s.append_empty_data();
+                s.append_empty_data();
             }
-            TransactionAction::Call { .. } => {
-                // TODO(@yejiayu): The interface for `call` is about to be modified.
-                unimplemented!()
+        }
+        TransactionAction::Batch { actions } => {
+            s.begin_list(actions.len());
+            for action in actions.iter() {
+                s.append(&RlpBatchAction { inner: action });
             }
-        };
-    }
+        }
+    };
 }
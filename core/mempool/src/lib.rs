@@ -10,7 +10,7 @@ mod tx_cache;
 pub use adapter::message::{
     NewTxsHandler, PullTxsHandler, END_GOSSIP_NEW_TXS, END_RESP_PULL_TXS, END_RPC_PULL_TXS,
 };
-pub use adapter::DefaultMemPoolAdapter;
+pub use adapter::{verify_signature, verify_tx_hash, DefaultMemPoolAdapter};
 
 use std::error::Error;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -97,6 +97,8 @@ where
             .check_storage_exist(ctx.clone(), tx_hash.clone())
             .await?;
         self.tx_cache.insert_new_tx(tx.clone())?;
+        // The nonce is taken only by the transaction in the pool.
+        self.adapter.record_nonce(ctx.clone(), tx.clone()).await?;
 
         if !ctx.is_network_origin_txs() {
             self.adapter.broadcast_tx(ctx, tx).await?;
@@ -213,6 +215,18 @@ pub enum MemPoolError {
     #[display(fmt = "Tx: {:?} check_sig failed", tx_hash)]
     CheckSig { tx_hash: Hash },
 
+    #[display(
+        fmt = "Tx: {:?} is signed by {} keys, {} required",
+        tx_hash,
+        signed,
+        threshold
+    )]
+    InsufficientSignatures {
+        tx_hash:   Hash,
+        signed:    usize,
+        threshold: u8,
+    },
+
     #[display(fmt = "Check_hash failed, expect: {:?}, get: {:?}", expect, actual)]
     CheckHash { expect: Hash, actual: Hash },
 
@@ -227,6 +241,21 @@ pub enum MemPoolError {
 
     #[display(fmt = "Tx: {:?} invalid timeout", tx_hash)]
     InvalidTimeout { tx_hash: Hash },
+
+    #[display(fmt = "Tx: {:?} nonce {:?} has been used", tx_hash, nonce)]
+    ReusedNonce { tx_hash: Hash, nonce: Hash },
+
+    #[display(
+        fmt = "Tx: {:?} max cycles price {} is lower than the base fee {}",
+        tx_hash,
+        max_cycles_price,
+        cycles_price
+    )]
+    CyclesPriceTooLow {
+        tx_hash:          Hash,
+        max_cycles_price: u64,
+        cycles_price:     u64,
+    },
 }
 
 impl Error for MemPoolError {}
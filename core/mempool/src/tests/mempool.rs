@@ -195,6 +195,57 @@ fn test_sync_propose_txs() {
     assert_eq!(mempool.get_tx_cache().len(), 50);
 }
 
+#[test]
+fn test_record_nonce() {
+    let mempool = Arc::new(default_mempool());
+    let adapter = mempool.get_adapter();
+    let tx = default_mock_txs(1).remove(0);
+    let insert = |tx: &SignedTransaction| {
+        executor::block_on(async { mempool.insert(Context::new(), tx.clone()).await })
+    };
+
+    // The nonce of a transaction rejected after the checks is still free.
+    adapter.committed_txs.insert(tx.tx_hash.clone(), ());
+    assert!(insert(&tx).is_err());
+    assert!(!adapter.nonces.contains_key(&tx.raw.nonce));
+
+    adapter.committed_txs.remove(&tx.tx_hash);
+    insert(&tx).unwrap();
+    assert!(adapter.nonces.contains_key(&tx.raw.nonce));
+
+    // The nonce stays taken after the transaction leaves the pool.
+    exec_flush(vec![tx.tx_hash.clone()], Arc::clone(&mempool));
+    assert!(insert(&tx).is_err());
+    assert_eq!(mempool.get_tx_cache().len(), 0);
+}
+
+#[test]
+fn test_check_multisig() {
+    // Exactly the threshold of the keys sign.
+    check_sig(&mock_multisig_tx(2, 3, 2)).unwrap();
+    check_sig(&mock_multisig_tx(3, 3, 3)).unwrap();
+
+    // Fewer keys than the threshold sign.
+    let tx = mock_multisig_tx(2, 3, 1);
+    let err = check_sig(&tx).unwrap_err();
+    assert!(err.to_string().contains("InsufficientSignatures"));
+
+    // The signatures do not match the keys one by one.
+    let mut tx = mock_multisig_tx(2, 3, 3);
+    if let Some(multisig) = tx.multisig.as_mut() {
+        multisig.signatures.pop();
+    }
+    let err = check_sig(&tx).unwrap_err();
+    assert!(err.to_string().contains("CheckSig"));
+
+    // A signature of another key is invalid even if the threshold is met.
+    let mut tx = mock_multisig_tx(1, 3, 2);
+    if let Some(multisig) = tx.multisig.as_mut() {
+        multisig.signatures.swap(0, 1);
+    }
+    assert!(check_sig(&tx).is_err());
+}
+
 #[bench]
 fn bench_insert(b: &mut Bencher) {
     let mempool = &Arc::new(default_mempool());
@@ -22,11 +22,12 @@ use common_crypto::{
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes};
 use protocol::types::{
-    CarryingAsset, Fee, Hash, RawTransaction, SignedTransaction, TransactionAction,
+    CarryingAsset, Fee, Hash, MultiSignature, RawTransaction, SignedTransaction, TransactionAction,
     UserAddress as Address,
 };
 use protocol::ProtocolResult;
 
+use crate::adapter::verify_signature;
 use crate::{HashMemPool, MemPoolError};
 
 const AMOUNT: i32 = 42;
@@ -39,12 +40,17 @@ const TX_CYCLE: u64 = 1;
 
 pub struct HashMemPoolAdapter {
     network_txs: CHashMap<Hash, SignedTransaction>,
+    // The mock transactions share the sender, so the nonce is the key.
+    nonces:        CHashMap<Hash, ()>,
+    committed_txs: CHashMap<Hash, ()>,
 }
 
 impl HashMemPoolAdapter {
     fn new() -> HashMemPoolAdapter {
         HashMemPoolAdapter {
-            network_txs: CHashMap::new(),
+            network_txs:   CHashMap::new(),
+            nonces:        CHashMap::new(),
+            committed_txs: CHashMap::new(),
         }
     }
 }
@@ -75,11 +81,26 @@ impl MemPoolAdapter for HashMemPoolAdapter {
         check_sig(&tx)
     }
 
-    async fn check_transaction(&self, _ctx: Context, _tx: SignedTransaction) -> ProtocolResult<()> {
+    async fn check_transaction(&self, _ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
+        if self.nonces.contains_key(&tx.raw.nonce) {
+            return Err(MemPoolError::ReusedNonce {
+                tx_hash: tx.tx_hash.clone(),
+                nonce:   tx.raw.nonce.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn record_nonce(&self, _ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
+        self.nonces.insert(tx.raw.nonce, ());
         Ok(())
     }
 
-    async fn check_storage_exist(&self, _ctx: Context, _tx_hash: Hash) -> ProtocolResult<()> {
+    async fn check_storage_exist(&self, _ctx: Context, tx_hash: Hash) -> ProtocolResult<()> {
+        if self.committed_txs.contains_key(&tx_hash) {
+            return Err(MemPoolError::CommittedTx { tx_hash }.into());
+        }
         Ok(())
     }
 }
@@ -140,13 +161,7 @@ async fn check_hash(tx: SignedTransaction) -> ProtocolResult<()> {
 }
 
 fn check_sig(tx: &SignedTransaction) -> ProtocolResult<()> {
-    if Secp256k1::verify_signature(&tx.tx_hash.as_bytes(), &tx.signature, &tx.pubkey).is_err() {
-        return Err(MemPoolError::CheckSig {
-            tx_hash: tx.tx_hash.clone(),
-        }
-        .into());
-    }
-    Ok(())
+    verify_signature::<Secp256k1>(tx)
 }
 
 fn concurrent_check_sig(txs: Vec<SignedTransaction>) {
@@ -245,6 +260,7 @@ fn mock_signed_tx(
         nonce,
         timeout,
         fee,
+        max_cycles_price: 10,
         action,
     };
 
@@ -262,9 +278,49 @@ fn mock_signed_tx(
         tx_hash,
         pubkey: pub_key.to_bytes(),
         signature: signature.to_bytes(),
+        multisig: None,
     }
 }
 
+// A transaction of a multi-signature account with `keys` keys, the first
+// `signed` of them sign it.
+fn mock_multisig_tx(threshold: u8, keys: usize, signed: usize) -> SignedTransaction {
+    let mut rng = OsRng::new().expect("OsRng");
+    let keypairs = (0..keys)
+        .map(|_| Secp256k1::generate_keypair(&mut rng))
+        .collect::<Vec<_>>();
+    let pubkeys = keypairs
+        .iter()
+        .map(|(_, pub_key)| pub_key.to_bytes())
+        .collect::<Vec<_>>();
+    let address = Address::from_multisig(threshold, &pubkeys).unwrap();
+
+    let (priv_key, pub_key) = &keypairs[0];
+    let mut tx = mock_signed_tx(priv_key, pub_key, &address, TIMEOUT, true);
+    let signatures = keypairs
+        .iter()
+        .enumerate()
+        .map(|(i, (priv_key, _))| {
+            if i < signed {
+                Secp256k1::sign_message(&tx.tx_hash.as_bytes(), &priv_key.to_bytes())
+                    .unwrap()
+                    .to_bytes()
+            } else {
+                Bytes::new()
+            }
+        })
+        .collect();
+
+    tx.pubkey = Bytes::new();
+    tx.signature = Bytes::new();
+    tx.multisig = Some(MultiSignature {
+        threshold,
+        pubkeys,
+        signatures,
+    });
+    tx
+}
+
 fn get_random_bytes(len: usize) -> Vec<u8> {
     (0..len).map(|_| random::<u8>()).collect()
 }
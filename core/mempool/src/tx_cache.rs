@@ -412,6 +412,7 @@ mod tests {
             nonce,
             timeout: TIMEOUT,
             fee,
+            max_cycles_price: 10,
             action,
         };
         SignedTransaction {
@@ -419,6 +420,7 @@ mod tests {
             tx_hash,
             pubkey: bytes.clone(),
             signature: bytes,
+            multisig: None,
         }
     }
 
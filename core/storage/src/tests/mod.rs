@@ -38,6 +38,7 @@ fn mock_signed_tx(tx_hash: Hash) -> SignedTransaction {
         nonce,
         timeout: 10,
         fee,
+        max_cycles_price: 10,
         action,
     };
 
@@ -46,6 +47,7 @@ fn mock_signed_tx(tx_hash: Hash) -> SignedTransaction {
         tx_hash,
         pubkey: Default::default(),
         signature: Default::default(),
+        multisig: None,
     }
 }
 
@@ -69,6 +71,7 @@ fn mock_receipt(tx_hash: Hash) -> Receipt {
         tx_hash,
         cycles_used,
         result,
+        logs: vec![],
     }
 }
 
@@ -86,6 +89,7 @@ fn mock_epoch(epoch_id: u64, epoch_hash: Hash) -> Epoch {
         state_root: nonce.clone(),
         receipt_root: Vec::new(),
         cycles_used: 999_999,
+        cycles_price: 1,
         proposer: UserAddress::from_hex(addr_str).unwrap(),
         proof: mock_proof(epoch_hash),
         validator_version: 1,
@@ -104,6 +108,7 @@ fn mock_proof(epoch_hash: Hash) -> Proof {
         round: 0,
         epoch_hash,
         signature: Default::default(),
+        multisig: None,
         bitmap: Default::default(),
     }
 }
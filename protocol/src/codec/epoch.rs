@@ -70,6 +70,9 @@ pub struct EpochHeader {
 
     #[prost(message, repeated, tag = "14")]
     pub validators: Vec<Validator>,
+
+    #[prost(uint64, tag = "15")]
+    pub cycles_price: u64,
 }
 
 #[derive(Clone, Message)]
@@ -197,6 +200,7 @@ impl From<epoch::EpochHeader> for EpochHeader {
             state_root,
             receipt_root,
             cycles_used: epoch_header.cycles_used,
+            cycles_price: epoch_header.cycles_price,
             proposer,
             proof,
             validator_version: epoch_header.validator_version,
@@ -242,6 +246,7 @@ impl TryFrom<EpochHeader> for epoch::EpochHeader {
             state_root: protocol_primitive::Hash::try_from(state_root)?,
             receipt_root,
             cycles_used: epoch_header.cycles_used,
+            cycles_price: epoch_header.cycles_price,
             proposer: protocol_primitive::UserAddress::try_from(proposer)?,
             proof: epoch::Proof::try_from(proof)?,
             validator_version: epoch_header.validator_version,
@@ -68,6 +68,9 @@ pub enum CodecError {
     #[display(fmt = "invalid contract type {}", _0)]
     InvalidContractType(i32),
 
+    #[display(fmt = "invalid multisig threshold {}", _0)]
+    InvalidMultiSigThreshold(u32),
+
     #[display(fmt = "wrong bytes length: {{ expect: {}, got: {} }}", expect, real)]
     WrongBytesLength { expect: usize, real: usize },
 }
@@ -88,6 +88,9 @@ pub struct Asset {
 
     #[prost(message, tag = "6")]
     pub storage_root: Option<MerkleRoot>,
+
+    #[prost(uint64, tag = "7")]
+    pub decimals: u64,
 }
 
 #[derive(Clone, Message)]
@@ -206,6 +209,21 @@ impl TryFrom<UserAddress> for protocol_primitive::UserAddress {
     }
 }
 
+// ContractType
+
+// The discriminants of `protocol_primitive::ContractType` differ from the
+// protobuf ones, so never cast it to i32 directly.
+impl From<protocol_primitive::ContractType> for ContractType {
+    fn from(contract_type: protocol_primitive::ContractType) -> ContractType {
+        match contract_type {
+            protocol_primitive::ContractType::Asset => ContractType::Asset,
+            protocol_primitive::ContractType::Library => ContractType::Library,
+            protocol_primitive::ContractType::App => ContractType::App,
+            protocol_primitive::ContractType::Native => ContractType::Native,
+        }
+    }
+}
+
 // ContractAddress
 
 impl From<protocol_primitive::ContractAddress> for ContractAddress {
@@ -213,12 +231,7 @@ impl From<protocol_primitive::ContractAddress> for ContractAddress {
         let value = contract.as_bytes().to_vec();
         let address = Some(Address { value });
 
-        let contract_type = match contract.contract_type() {
-            protocol_primitive::ContractType::Asset => ContractType::Asset,
-            protocol_primitive::ContractType::Library => ContractType::Library,
-            protocol_primitive::ContractType::App => ContractType::App,
-            protocol_primitive::ContractType::Native => ContractType::Native,
-        };
+        let contract_type = ContractType::from(contract.contract_type());
 
         ContractAddress {
             value:         address,
@@ -256,6 +269,7 @@ impl From<protocol_primitive::Asset> for Asset {
             supply:          Some(supply),
             manage_contract: Some(manage_contract),
             storage_root:    Some(storage_root),
+            decimals:        asset.decimals,
         }
     }
 }
@@ -274,6 +288,7 @@ impl TryFrom<Asset> for protocol_primitive::Asset {
             name:            asset.name,
             symbol:          asset.symbol,
             supply:          protocol_primitive::Balance::try_from(supply)?,
+            decimals:        asset.decimals,
             manage_contract: protocol_primitive::ContractAddress::try_from(manage_contract)?,
             storage_root:    protocol_primitive::MerkleRoot::try_from(storage_root)?,
         };
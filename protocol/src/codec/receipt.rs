@@ -32,8 +32,21 @@ pub struct Receipt {
     #[prost(message, tag = "4")]
     pub cycles_used: Option<Fee>,
 
-    #[prost(oneof = "ReceiptResult", tags = "5, 6, 7, 8, 9")]
+    #[prost(oneof = "ReceiptResult", tags = "5, 6, 7, 8, 9, 11")]
     pub result: Option<ReceiptResult>,
+
+    #[prost(message, repeated, tag = "10")]
+    pub logs: Vec<Log>,
+}
+
+#[derive(Clone, Message)]
+pub struct Log {
+    #[prost(message, tag = "1")]
+    pub address: Option<ContractAddress>,
+    #[prost(message, repeated, tag = "2")]
+    pub topics: Vec<Hash>,
+    #[prost(bytes, tag = "3")]
+    pub data: Vec<u8>,
 }
 
 #[derive(Clone, Oneof)]
@@ -48,6 +61,8 @@ pub enum ReceiptResult {
     Call(Call),
     #[prost(message, tag = "9")]
     Fail(Fail),
+    #[prost(message, tag = "11")]
+    Batch(Batch),
 }
 
 #[derive(Clone, Message)]
@@ -98,6 +113,19 @@ pub struct Fail {
     pub user: String,
 }
 
+#[derive(Clone, Message)]
+pub struct Batch {
+    #[prost(message, repeated, tag = "1")]
+    pub results: Vec<BatchResult>,
+}
+
+// A oneof can not be repeated, so the results of a batch are wrapped.
+#[derive(Clone, Message)]
+pub struct BatchResult {
+    #[prost(oneof = "ReceiptResult", tags = "5, 6, 7, 8, 9, 11")]
+    pub result: Option<ReceiptResult>,
+}
+
 // #################
 // Conversion
 // #################
@@ -141,7 +169,7 @@ impl From<receipt::ReceiptResult> for ReceiptResult {
             } => {
                 let deploy = Deploy {
                     contract:      Some(ContractAddress::from(contract)),
-                    contract_type: contract_type as i32,
+                    contract_type: ContractType::from(contract_type) as i32,
                 };
 
                 ReceiptResult::Deploy(deploy)
@@ -164,6 +192,16 @@ impl From<receipt::ReceiptResult> for ReceiptResult {
 
                 ReceiptResult::Fail(fail)
             }
+            receipt::ReceiptResult::Batch { results } => {
+                let results = results
+                    .into_iter()
+                    .map(|result| BatchResult {
+                        result: Some(ReceiptResult::from(result)),
+                    })
+                    .collect::<Vec<_>>();
+
+                ReceiptResult::Batch(Batch { results })
+            }
         }
     }
 }
@@ -245,10 +283,54 @@ impl TryFrom<ReceiptResult> for receipt::ReceiptResult {
 
                 Ok(action)
             }
+            ReceiptResult::Batch(batch) => {
+                let mut results = Vec::with_capacity(batch.results.len());
+                for result in batch.results {
+                    let result = field!(result.result, "ReceiptResult::Batch", "results")?;
+                    results.push(receipt::ReceiptResult::try_from(result)?);
+                }
+
+                Ok(receipt::ReceiptResult::Batch { results })
+            }
+        }
+    }
+}
+
+// Log
+
+impl From<receipt::Log> for Log {
+    fn from(log: receipt::Log) -> Log {
+        let topics = log.topics.into_iter().map(Hash::from).collect::<Vec<_>>();
+
+        Log {
+            address: Some(ContractAddress::from(log.address)),
+            topics,
+            data: log.data.to_vec(),
         }
     }
 }
 
+impl TryFrom<Log> for receipt::Log {
+    type Error = ProtocolError;
+
+    fn try_from(log: Log) -> Result<receipt::Log, Self::Error> {
+        let address = field!(log.address, "Log", "address")?;
+
+        let mut topics = Vec::new();
+        for topic in log.topics {
+            topics.push(protocol_primitive::Hash::try_from(topic)?);
+        }
+
+        let log = receipt::Log {
+            address: protocol_primitive::ContractAddress::try_from(address)?,
+            topics,
+            data: Bytes::from(log.data),
+        };
+
+        Ok(log)
+    }
+}
+
 // Receipt
 
 impl From<receipt::Receipt> for Receipt {
@@ -257,6 +339,7 @@ impl From<receipt::Receipt> for Receipt {
         let tx_hash = Some(Hash::from(receipt.tx_hash));
         let cycles_used = Some(Fee::from(receipt.cycles_used));
         let result = Some(ReceiptResult::from(receipt.result));
+        let logs = receipt.logs.into_iter().map(Log::from).collect::<Vec<_>>();
 
         Receipt {
             state_root,
@@ -264,6 +347,7 @@ impl From<receipt::Receipt> for Receipt {
             tx_hash,
             cycles_used,
             result,
+            logs,
         }
     }
 }
@@ -277,12 +361,18 @@ impl TryFrom<Receipt> for receipt::Receipt {
         let cycles_used = field!(receipt.cycles_used, "Receipt", "cycles_used")?;
         let result = field!(receipt.result, "Receipt", "result")?;
 
+        let mut logs = Vec::new();
+        for log in receipt.logs {
+            logs.push(receipt::Log::try_from(log)?);
+        }
+
         let receipt = receipt::Receipt {
-            state_root:  protocol_primitive::Hash::try_from(state_root)?,
-            epoch_id:    receipt.epoch_id,
-            tx_hash:     protocol_primitive::Hash::try_from(tx_hash)?,
+            state_root: protocol_primitive::Hash::try_from(state_root)?,
+            epoch_id: receipt.epoch_id,
+            tx_hash: protocol_primitive::Hash::try_from(tx_hash)?,
             cycles_used: protocol_primitive::Fee::try_from(cycles_used)?,
-            result:      receipt::ReceiptResult::try_from(result)?,
+            result: receipt::ReceiptResult::try_from(result)?,
+            logs,
         };
 
         Ok(receipt)
@@ -34,12 +34,15 @@ fn test_codec() {
     test!(receipt, ReceiptResult, mock_result, ReceiptType::Deploy);
     test!(receipt, ReceiptResult, mock_result, ReceiptType::Call);
     test!(receipt, ReceiptResult, mock_result, ReceiptType::Fail);
+    test!(receipt, ReceiptResult, mock_result, ReceiptType::Batch);
+    test!(receipt, Log, mock_log);
     test!(receipt, Receipt, mock_receipt, ReceiptType::Transfer);
 
     test!(transaction, TransactionAction, mock_action, AType::Transfer);
     test!(transaction, TransactionAction, mock_action, AType::Approve);
     test!(transaction, TransactionAction, mock_action, AType::Deploy);
     test!(transaction, TransactionAction, mock_action, AType::Call);
+    test!(transaction, TransactionAction, mock_action, AType::Batch);
     test!(transaction, RawTransaction, mock_raw_tx, AType::Approve);
     test!(transaction, SignedTransaction, mock_sign_tx, AType::Deploy);
 
@@ -51,6 +54,67 @@ fn test_codec() {
     test!(epoch, Pill, mock_pill, 100, 200);
 }
 
+#[test]
+fn test_contract_type_codec() {
+    for contract_type in vec![
+        ContractType::Asset,
+        ContractType::App,
+        ContractType::Library,
+    ] {
+        let action = TransactionAction::Deploy {
+            code:          get_random_bytes(100),
+            contract_type: contract_type.clone(),
+            asset:         None,
+        };
+
+        let codec_val: codec::transaction::TransactionAction = action.into();
+        let action: TransactionAction = codec_val.try_into().unwrap();
+        match action {
+            TransactionAction::Deploy {
+                contract_type: decoded,
+                ..
+            } => assert_eq!(decoded, contract_type),
+            _ => panic!("should be a deploy action"),
+        }
+    }
+}
+
+#[test]
+fn test_deploy_asset_codec() {
+    let action = TransactionAction::Deploy {
+        code:          get_random_bytes(100),
+        contract_type: ContractType::Asset,
+        asset:         Some(mock_deploy_asset()),
+    };
+
+    let codec_val: codec::transaction::TransactionAction = action.clone().into();
+    let decoded: TransactionAction = codec_val.try_into().unwrap();
+    assert_eq!(decoded, action);
+}
+
+#[test]
+fn test_receipt_logs_codec() {
+    let receipt = mock_receipt(ReceiptType::Call);
+    let logs = receipt.logs.clone();
+
+    let bytes = receipt.encode_sync().unwrap();
+    let decoded = Receipt::decode_sync(bytes).unwrap();
+    assert_eq!(decoded.logs, logs);
+}
+
+#[test]
+fn test_multisig_threshold_codec() {
+    let signed_tx = mock_sign_tx(AType::Transfer);
+    let mut codec_val: codec::transaction::SignedTransaction = signed_tx.into();
+
+    // The threshold is a single byte, a larger one is not truncated.
+    if let Some(multisig) = codec_val.multisig.as_mut() {
+        multisig.threshold = 258;
+    }
+    let decoded: Result<SignedTransaction, _> = codec_val.try_into();
+    assert!(decoded.is_err());
+}
+
 #[bench]
 fn bench_signed_tx_serialize(b: &mut Bencher) {
     let txs: Vec<SignedTransaction> = (0..50_000).map(|_| mock_sign_tx(AType::Transfer)).collect();
@@ -11,8 +11,11 @@ use crate::types::{
     primitive::{
         Asset, AssetID, Balance, ContractAddress, ContractType, Fee, Hash, MerkleRoot, UserAddress,
     },
-    receipt::{Receipt, ReceiptResult},
-    transaction::{CarryingAsset, RawTransaction, SignedTransaction, TransactionAction},
+    receipt::{Log, Receipt, ReceiptResult},
+    transaction::{
+        CarryingAsset, DeployAsset, MultiSignature, RawTransaction, SignedTransaction,
+        TransactionAction,
+    },
 };
 
 enum ReceiptType {
@@ -21,6 +24,7 @@ enum ReceiptType {
     Deploy,
     Call,
     Fail,
+    Batch,
 }
 
 enum AType {
@@ -28,6 +32,7 @@ enum AType {
     Approve,
     Deploy,
     Call,
+    Batch,
 }
 
 // #####################
@@ -64,11 +69,22 @@ fn mock_asset() -> Asset {
         name:            "test".to_string(),
         symbol:          "MT".to_string(),
         supply:          mock_balance(),
+        decimals:        18,
         manage_contract: mock_contract_address(),
         storage_root:    mock_merkle_root(),
     }
 }
 
+fn mock_deploy_asset() -> DeployAsset {
+    DeployAsset {
+        name:            "test".to_string(),
+        symbol:          "MT".to_string(),
+        supply:          mock_balance(),
+        decimals:        18,
+        manage_contract: Some(mock_contract_address()),
+    }
+}
+
 fn mock_fee() -> Fee {
     Fee {
         asset_id: mock_asset_id(),
@@ -106,6 +122,20 @@ fn mock_result(rtype: ReceiptType) -> ReceiptResult {
             system: "system".to_string(),
             user:   "user".to_string(),
         },
+        ReceiptType::Batch => ReceiptResult::Batch {
+            results: vec![
+                mock_result(ReceiptType::Transfer),
+                mock_result(ReceiptType::Call),
+            ],
+        },
+    }
+}
+
+fn mock_log() -> Log {
+    Log {
+        address: mock_contract_address(),
+        topics:  vec![mock_hash(), mock_hash()],
+        data:    get_random_bytes(100),
     }
 }
 
@@ -116,6 +146,7 @@ fn mock_receipt(rtype: ReceiptType) -> Receipt {
         tx_hash:     mock_hash(),
         cycles_used: mock_fee(),
         result:      mock_result(rtype),
+        logs:        vec![mock_log()],
     }
 }
 
@@ -140,6 +171,7 @@ fn mock_action(atype: AType) -> TransactionAction {
         AType::Deploy => TransactionAction::Deploy {
             code:          get_random_bytes(100),
             contract_type: ContractType::Library,
+            asset:         None,
         },
         AType::Call => TransactionAction::Call {
             contract:       mock_contract_address(),
@@ -150,16 +182,20 @@ fn mock_action(atype: AType) -> TransactionAction {
                 amount:   mock_balance(),
             }),
         },
+        AType::Batch => TransactionAction::Batch {
+            actions: vec![mock_action(AType::Transfer), mock_action(AType::Call)],
+        },
     }
 }
 
 fn mock_raw_tx(atype: AType) -> RawTransaction {
     RawTransaction {
-        chain_id: mock_hash(),
-        nonce:    mock_hash(),
-        timeout:  100,
-        fee:      mock_fee(),
-        action:   mock_action(atype),
+        chain_id:         mock_hash(),
+        nonce:            mock_hash(),
+        timeout:          100,
+        fee:              mock_fee(),
+        max_cycles_price: 10,
+        action:           mock_action(atype),
     }
 }
 
@@ -169,6 +205,15 @@ fn mock_sign_tx(atype: AType) -> SignedTransaction {
         tx_hash:   mock_hash(),
         pubkey:    Default::default(),
         signature: Default::default(),
+        multisig:  Some(MultiSignature {
+            threshold:  2,
+            pubkeys:    vec![
+                get_random_bytes(33),
+                get_random_bytes(33),
+                get_random_bytes(33),
+            ],
+            signatures: vec![get_random_bytes(64), Bytes::new(), get_random_bytes(64)],
+        }),
     }
 }
 
@@ -210,6 +255,7 @@ fn mock_epoch_header() -> EpochHeader {
         state_root:        mock_merkle_root(),
         receipt_root:      vec![mock_hash(), mock_hash()],
         cycles_used:       999_999,
+        cycles_price:      2,
         proposer:          mock_account_address(),
         proof:             mock_proof(),
         validator_version: 1,
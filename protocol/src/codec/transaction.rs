@@ -50,6 +50,27 @@ pub struct Deploy {
 
     #[prost(enumeration = "ContractType", tag = "2")]
     pub contract_type: i32,
+
+    #[prost(message, tag = "3")]
+    pub asset: Option<DeployAsset>,
+}
+
+#[derive(Clone, Message)]
+pub struct DeployAsset {
+    #[prost(string, tag = "1")]
+    pub name: String,
+
+    #[prost(string, tag = "2")]
+    pub symbol: String,
+
+    #[prost(message, tag = "3")]
+    pub supply: Option<Balance>,
+
+    #[prost(uint64, tag = "4")]
+    pub decimals: u64,
+
+    #[prost(message, tag = "5")]
+    pub manage_contract: Option<ContractAddress>,
 }
 
 #[derive(Clone, Message)]
@@ -67,6 +88,19 @@ pub struct Call {
     pub carrying_asset: Option<CarryingAsset>,
 }
 
+#[derive(Clone, Message)]
+pub struct Batch {
+    #[prost(message, repeated, tag = "1")]
+    pub actions: Vec<BatchAction>,
+}
+
+// A oneof can not be repeated, so the actions of a batch are wrapped.
+#[derive(Clone, Message)]
+pub struct BatchAction {
+    #[prost(oneof = "TransactionAction", tags = "5, 6, 7, 8, 10")]
+    pub action: Option<TransactionAction>,
+}
+
 #[derive(Clone, Oneof)]
 pub enum TransactionAction {
     #[prost(message, tag = "5")]
@@ -80,6 +114,9 @@ pub enum TransactionAction {
 
     #[prost(message, tag = "8")]
     Call(Call),
+
+    #[prost(message, tag = "10")]
+    Batch(Batch),
 }
 
 #[derive(Clone, Message)]
@@ -96,8 +133,11 @@ pub struct RawTransaction {
     #[prost(message, tag = "4")]
     pub fee: Option<Fee>,
 
-    #[prost(oneof = "TransactionAction", tags = "5, 6, 7, 8")]
+    #[prost(oneof = "TransactionAction", tags = "5, 6, 7, 8, 10")]
     pub action: Option<TransactionAction>,
+
+    #[prost(uint64, tag = "9")]
+    pub max_cycles_price: u64,
 }
 
 #[derive(Clone, Message)]
@@ -113,6 +153,21 @@ pub struct SignedTransaction {
 
     #[prost(bytes, tag = "4")]
     pub signature: Vec<u8>,
+
+    #[prost(message, tag = "5")]
+    pub multisig: Option<MultiSignature>,
+}
+
+#[derive(Clone, Message)]
+pub struct MultiSignature {
+    #[prost(uint32, tag = "1")]
+    pub threshold: u32,
+
+    #[prost(bytes, repeated, tag = "2")]
+    pub pubkeys: Vec<Vec<u8>>,
+
+    #[prost(bytes, repeated, tag = "3")]
+    pub signatures: Vec<Vec<u8>>,
 }
 
 // #################
@@ -143,6 +198,39 @@ impl TryFrom<CarryingAsset> for transaction::CarryingAsset {
     }
 }
 
+// DeployAsset
+impl From<transaction::DeployAsset> for DeployAsset {
+    fn from(asset: transaction::DeployAsset) -> DeployAsset {
+        DeployAsset {
+            name:            asset.name,
+            symbol:          asset.symbol,
+            supply:          Some(Balance::from(asset.supply)),
+            decimals:        asset.decimals,
+            manage_contract: asset.manage_contract.map(ContractAddress::from),
+        }
+    }
+}
+
+impl TryFrom<DeployAsset> for transaction::DeployAsset {
+    type Error = ProtocolError;
+
+    fn try_from(asset: DeployAsset) -> Result<transaction::DeployAsset, Self::Error> {
+        let supply = field!(asset.supply, "DeployAsset", "supply")?;
+        let manage_contract = match asset.manage_contract {
+            Some(address) => Some(protocol_primitive::ContractAddress::try_from(address)?),
+            None => None,
+        };
+
+        Ok(transaction::DeployAsset {
+            name: asset.name,
+            symbol: asset.symbol,
+            supply: protocol_primitive::Balance::try_from(supply)?,
+            decimals: asset.decimals,
+            manage_contract,
+        })
+    }
+}
+
 // TransactionAction
 
 impl From<transaction::TransactionAction> for TransactionAction {
@@ -178,10 +266,12 @@ impl From<transaction::TransactionAction> for TransactionAction {
             transaction::TransactionAction::Deploy {
                 code,
                 contract_type,
+                asset,
             } => {
                 let deploy = Deploy {
                     code:          code.to_vec(),
-                    contract_type: contract_type as i32,
+                    contract_type: ContractType::from(contract_type) as i32,
+                    asset:         asset.map(DeployAsset::from),
                 };
 
                 TransactionAction::Deploy(deploy)
@@ -212,6 +302,16 @@ impl From<transaction::TransactionAction> for TransactionAction {
 
                 TransactionAction::Call(call)
             }
+            transaction::TransactionAction::Batch { actions } => {
+                let actions = actions
+                    .into_iter()
+                    .map(|action| BatchAction {
+                        action: Some(TransactionAction::from(action)),
+                    })
+                    .collect::<Vec<_>>();
+
+                TransactionAction::Batch(Batch { actions })
+            }
         }
     }
 }
@@ -258,9 +358,15 @@ impl TryFrom<TransactionAction> for transaction::TransactionAction {
                     _ => return Err(CodecError::InvalidContractType(deploy.contract_type).into()),
                 };
 
+                let asset = match deploy.asset {
+                    Some(asset) => Some(transaction::DeployAsset::try_from(asset)?),
+                    None => None,
+                };
+
                 let action = transaction::TransactionAction::Deploy {
                     code: Bytes::from(deploy.code),
                     contract_type,
+                    asset,
                 };
 
                 Ok(action)
@@ -285,6 +391,15 @@ impl TryFrom<TransactionAction> for transaction::TransactionAction {
 
                 Ok(action)
             }
+            TransactionAction::Batch(batch) => {
+                let mut actions = Vec::with_capacity(batch.actions.len());
+                for action in batch.actions {
+                    let action = field!(action.action, "TransactionAction::Batch", "actions")?;
+                    actions.push(transaction::TransactionAction::try_from(action)?);
+                }
+
+                Ok(transaction::TransactionAction::Batch { actions })
+            }
         }
     }
 }
@@ -304,6 +419,7 @@ impl From<transaction::RawTransaction> for RawTransaction {
             timeout: raw.timeout,
             fee,
             action,
+            max_cycles_price: raw.max_cycles_price,
         }
     }
 }
@@ -318,17 +434,47 @@ impl TryFrom<RawTransaction> for transaction::RawTransaction {
         let action = field!(raw.action, "RawTransaction", "action")?;
 
         let raw_tx = transaction::RawTransaction {
-            chain_id: protocol_primitive::Hash::try_from(chain_id)?,
-            nonce:    protocol_primitive::Hash::try_from(nonce)?,
-            timeout:  raw.timeout,
-            fee:      protocol_primitive::Fee::try_from(fee)?,
-            action:   transaction::TransactionAction::try_from(action)?,
+            chain_id:         protocol_primitive::Hash::try_from(chain_id)?,
+            nonce:            protocol_primitive::Hash::try_from(nonce)?,
+            timeout:          raw.timeout,
+            fee:              protocol_primitive::Fee::try_from(fee)?,
+            max_cycles_price: raw.max_cycles_price,
+            action:           transaction::TransactionAction::try_from(action)?,
         };
 
         Ok(raw_tx)
     }
 }
 
+// MultiSignature
+
+impl From<transaction::MultiSignature> for MultiSignature {
+    fn from(multisig: transaction::MultiSignature) -> MultiSignature {
+        MultiSignature {
+            threshold:  u32::from(multisig.threshold),
+            pubkeys:    multisig.pubkeys.iter().map(|k| k.to_vec()).collect(),
+            signatures: multisig.signatures.iter().map(|s| s.to_vec()).collect(),
+        }
+    }
+}
+
+impl TryFrom<MultiSignature> for transaction::MultiSignature {
+    type Error = ProtocolError;
+
+    fn try_from(multisig: MultiSignature) -> Result<transaction::MultiSignature, Self::Error> {
+        let threshold = u8::try_from(multisig.threshold)
+            .map_err(|_| CodecError::InvalidMultiSigThreshold(multisig.threshold))?;
+
+        let multisig = transaction::MultiSignature {
+            threshold,
+            pubkeys: multisig.pubkeys.into_iter().map(Bytes::from).collect(),
+            signatures: multisig.signatures.into_iter().map(Bytes::from).collect(),
+        };
+
+        Ok(multisig)
+    }
+}
+
 // SignedTransaction
 
 impl From<transaction::SignedTransaction> for SignedTransaction {
@@ -341,6 +487,7 @@ impl From<transaction::SignedTransaction> for SignedTransaction {
             tx_hash:   Some(tx_hash),
             pubkey:    stx.pubkey.to_vec(),
             signature: stx.signature.to_vec(),
+            multisig:  stx.multisig.map(MultiSignature::from),
         }
     }
 }
@@ -351,12 +498,17 @@ impl TryFrom<SignedTransaction> for transaction::SignedTransaction {
     fn try_from(stx: SignedTransaction) -> Result<transaction::SignedTransaction, Self::Error> {
         let raw = field!(stx.raw, "SignedTransaction", "raw")?;
         let tx_hash = field!(stx.tx_hash, "SignedTransaction", "tx_hash")?;
+        let multisig = match stx.multisig {
+            Some(multisig) => Some(transaction::MultiSignature::try_from(multisig)?),
+            None => None,
+        };
 
         let stx = transaction::SignedTransaction {
-            raw:       transaction::RawTransaction::try_from(raw)?,
-            tx_hash:   protocol_primitive::Hash::try_from(tx_hash)?,
-            pubkey:    Bytes::from(stx.pubkey),
+            raw: transaction::RawTransaction::try_from(raw)?,
+            tx_hash: protocol_primitive::Hash::try_from(tx_hash)?,
+            pubkey: Bytes::from(stx.pubkey),
             signature: Bytes::from(stx.signature),
+            multisig,
         };
 
         Ok(stx)
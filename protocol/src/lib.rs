@@ -1,5 +1,4 @@
 #![feature(test)]
-#![feature(trait_alias)]
 
 pub mod codec;
 pub mod traits;
@@ -40,6 +39,13 @@ impl From<ProtocolError> for Box<dyn Error + Send> {
     }
 }
 
+impl ProtocolError {
+    /// The underlying error, if it is an `E`.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.downcast_ref::<E>()
+    }
+}
+
 impl Error for ProtocolError {}
 
 pub type ProtocolResult<T> = Result<T, ProtocolError>;
@@ -1,7 +1,12 @@
 use async_trait::async_trait;
+use bytes::Bytes;
 
+use crate::traits::executor::{ExecutorBalanceProof, ExecutorReadResp, ExecutorTrace};
 use crate::traits::Context;
-use crate::types::{Address, Balance, Epoch, Hash, Receipt, SignedTransaction};
+use crate::types::{
+    Address, AssetID, Balance, ContractAddress, Epoch, Hash, Receipt, SignedTransaction,
+    UserAddress,
+};
 use crate::ProtocolResult;
 
 #[async_trait]
@@ -19,4 +24,41 @@ pub trait APIAdapter: Send + Sync {
     async fn get_receipt_by_tx_hash(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<Receipt>;
 
     async fn get_balance(&self, ctx: Context, address: &Address) -> ProtocolResult<Balance>;
+
+    /// Get the balance of an asset with its proof against the state root of
+    /// the epoch, the latest epoch is used if `epoch_id` is `None`.
+    async fn get_balance_proof(
+        &self,
+        ctx: Context,
+        epoch_id: Option<u64>,
+        address: Address,
+        asset_id: AssetID,
+    ) -> ProtocolResult<ExecutorBalanceProof>;
+
+    /// Call a contract on the state of the epoch without changing it, the
+    /// latest epoch is used if `epoch_id` is `None`.
+    async fn query(
+        &self,
+        ctx: Context,
+        epoch_id: Option<u64>,
+        caller: Address,
+        contract: ContractAddress,
+        method: String,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<ExecutorReadResp>;
+
+    /// Execute a transaction sent by `sender` on top of the latest state
+    /// without changing it, as if it is packed into the next epoch. Neither
+    /// the public key nor the signature of the transaction is checked.
+    async fn simulate(
+        &self,
+        ctx: Context,
+        sender: UserAddress,
+        signed_tx: SignedTransaction,
+    ) -> ProtocolResult<Receipt>;
+
+    /// Execute a committed transaction again on the state it was executed on
+    /// and trace it, nothing is changed.
+    async fn trace_transaction(&self, ctx: Context, tx_hash: Hash)
+        -> ProtocolResult<ExecutorTrace>;
 }
@@ -21,19 +21,26 @@ pub struct NodeInfo {
 
 #[derive(Clone, Debug)]
 pub struct CurrentConsensusStatus {
-    pub cycles_price:       u64,
-    pub cycles_limit:       u64,
-    pub epoch_id:           u64,
-    pub prev_hash:          Hash,
-    pub logs_bloom:         Bloom,
-    pub order_root:         MerkleRoot,
-    pub confirm_root:       Vec<MerkleRoot>,
-    pub state_root:         MerkleRoot,
-    pub receipt_root:       Vec<MerkleRoot>,
-    pub cycles_used:        u64,
-    pub proof:              Proof,
-    pub validators:         Vec<Validator>,
+    pub cycles_price: u64,
+    pub cycles_limit: u64,
+    pub epoch_id:     u64,
+    pub prev_hash:    Hash,
+    pub logs_bloom:   Bloom,
+    pub order_root:   MerkleRoot,
+    pub confirm_root: Vec<MerkleRoot>,
+    pub state_root:   MerkleRoot,
+    pub receipt_root: Vec<MerkleRoot>,
+    pub cycles_used:  u64,
+    pub proof:        Proof,
+    pub validators:   Vec<Validator>,
+    // Increased every time the validators change.
+    pub validator_version:  u64,
     pub consensus_interval: u64,
+    // The timeouts of the consensus steps as (numerator, denominator) of the
+    // interval.
+    pub propose_ratio:   (u64, u64),
+    pub prevote_ratio:   (u64, u64),
+    pub precommit_ratio: (u64, u64),
 }
 
 #[async_trait]
@@ -73,6 +80,18 @@ pub trait ConsensusAdapter: Send + Sync {
     /// Check the correctness of the given transactions.
     async fn check_txs(&self, ctx: Context, txs: Vec<Hash>) -> ProtocolResult<()>;
 
+    /// Verify the hashes and the signatures of the signed transactions of a
+    /// proposal, and check them against the state they are executed on. An
+    /// epoch containing a transaction the executor would reject before
+    /// execution is not voted for.
+    async fn verify_txs(
+        &self,
+        ctx: Context,
+        node_info: NodeInfo,
+        status: CurrentConsensusStatus,
+        signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()>;
+
     /// Synchronous signed transactions.
     async fn sync_txs(&self, ctx: Context, txs: Vec<Hash>) -> ProtocolResult<()>;
 
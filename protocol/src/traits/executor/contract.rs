@@ -1,5 +1,10 @@
-use crate::traits::executor::{ContractSchema, RcInvokeContext};
-use crate::types::{Account, Address, Asset, AssetID, Balance, ContractAddress, MerkleRoot};
+use bytes::Bytes;
+
+use crate::traits::executor::{ContractSchema, Dispatcher, RcInvokeContext};
+use crate::types::{
+    Account, Address, ApprovedInfo, Asset, AssetID, Balance, ChainParams, ContractAddress,
+    DeployAsset, Hash, MerkleRoot, UserAddress, Validator,
+};
 use crate::ProtocolResult;
 
 // As the world state access layer, the ContractStateAdapter provides `cache`
@@ -20,23 +25,91 @@ pub trait ContractStateAdapter {
         value: Schema::Value,
     ) -> ProtocolResult<()>;
 
-    // Clear cache, called when executor fails.
+    // Remove a key, the removal is cached like an insertion.
+    fn remove_cache<Schema: ContractSchema>(&mut self, key: &Schema::Key) -> ProtocolResult<()>;
+
+    // Start a nested level of cache, the changes since then can be committed
+    // to the outer level or rolled back alone.
+    fn push_checkpoint(&mut self);
+
+    // Merge the changes of the innermost checkpoint into the outer level.
+    fn commit_checkpoint(&mut self) -> ProtocolResult<()>;
+
+    // Drop the changes of the innermost checkpoint.
+    fn rollback_checkpoint(&mut self) -> ProtocolResult<()>;
+
+    // Clear cache, including all checkpoints, called when executor fails.
     fn revert_cache(&mut self) -> ProtocolResult<()>;
 
     // Put the data in the current cache into the stash space, which means that the
     // data will be finilazy when 'commit' is invoked.
     fn stash(&mut self) -> ProtocolResult<()>;
 
+    // Clear both cache and stash space, all changes since the last `commit`
+    // are dropped.
+    fn revert_stash(&mut self) -> ProtocolResult<()>;
+
     // Persist the data of stash space to `world state` and empty stash space.
     fn commit(&mut self) -> ProtocolResult<MerkleRoot>;
 }
 
+/// NativeDispatcher is what a native contract sees of the executor, it calls
+/// the other contracts and moves the balances kept by the `AccountContract`.
+pub trait NativeDispatcher: Dispatcher {
+    fn get_balance(&self, id: &AssetID, address: &Address) -> ProtocolResult<Balance>;
+
+    /// Move the balance from the contract itself or its caller to `to`.
+    fn transfer(
+        &self,
+        id: &AssetID,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> ProtocolResult<()>;
+
+    /// Credit the newly issued asset, only the bank contract can mint.
+    fn mint(&self, id: &AssetID, to: &Address, amount: Balance) -> ProtocolResult<()>;
+
+    /// Destroy the asset, only the bank contract can burn.
+    fn burn(&self, id: &AssetID, from: &Address, amount: Balance) -> ProtocolResult<()>;
+}
+
+/// NativeContract is implemented by every native contract, the builtin ones
+/// and the ones registered to the executor factory. The `Call` transactions to
+/// the address of a contract are dispatched to it, its state is kept by its
+/// own `Adapter` and committed by the executor.
+pub trait NativeContract<Adapter: ContractStateAdapter> {
+    // The arguments and the return value are encoded as bytes.
+    fn invoke(
+        &mut self,
+        dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes>;
+}
+
+impl<Adapter: ContractStateAdapter, C: NativeContract<Adapter> + ?Sized> NativeContract<Adapter>
+    for Box<C>
+{
+    fn invoke(
+        &mut self,
+        dispatcher: &dyn NativeDispatcher,
+        ictx: RcInvokeContext,
+        method: &str,
+        args: Vec<Bytes>,
+    ) -> ProtocolResult<Bytes> {
+        (**self).invoke(dispatcher, ictx, method, args)
+    }
+}
+
 /// BankContract is the registration and query center for asset.
 ///
-/// It only does two things
+/// It does three things
 /// 1. Responsible for generating a unique ID for the asset and writing the
 /// asset's information to the chain.
 /// 2. Query the basic information of the asset by asset id.
+/// 3. Keep the supply of the asset when it is minted or burned.
 pub trait BankContract<Adapter: ContractStateAdapter> {
     // Register an asset.
     // The asset id is generated by: AssetID = Hash(ChainID + AssetContractAddress).
@@ -47,19 +120,127 @@ pub trait BankContract<Adapter: ContractStateAdapter> {
         &mut self,
         ictx: RcInvokeContext,
         address: &ContractAddress,
-        name: String,
-        symbol: String,
-        supply: Balance,
+        asset: &DeployAsset,
     ) -> ProtocolResult<Asset>;
 
     fn get_asset(&self, ictx: RcInvokeContext, id: &AssetID) -> ProtocolResult<Asset>;
+
+    // Increase the supply of an asset, the caller must be the manage contract
+    // of the asset. The balances are kept by the `AccountContract`.
+    fn mint(
+        &mut self,
+        ictx: RcInvokeContext,
+        id: &AssetID,
+        amount: Balance,
+    ) -> ProtocolResult<Asset>;
+
+    // Decrease the supply of an asset, the caller must be the manage contract
+    // of the asset.
+    fn burn(
+        &mut self,
+        ictx: RcInvokeContext,
+        id: &AssetID,
+        amount: Balance,
+    ) -> ProtocolResult<Asset>;
+}
+
+/// StakingContract keeps the system token bonded by the user accounts, the
+/// accounts with the most stake are the validators of the chain.
+///
+/// The bonded balance is held by the staking contract in the
+/// `AccountContract`, which is moved by the caller of `bond` and `withdraw`.
+pub trait StakingContract<Adapter: ContractStateAdapter> {
+    // Fail if the stake of the address would be below the minimum stake.
+    fn bond(
+        &mut self,
+        ictx: RcInvokeContext,
+        address: &UserAddress,
+        amount: Balance,
+    ) -> ProtocolResult<()>;
+
+    // Fail if `amount` exceeds the stake of the address, or the remaining
+    // stake is neither zero nor at least the minimum stake. The amount stops
+    // counting at once, but it is released only after the unbonding epochs.
+    fn unbond(
+        &mut self,
+        ictx: RcInvokeContext,
+        address: &UserAddress,
+        amount: Balance,
+    ) -> ProtocolResult<()>;
+
+    // Release the unbonded amounts whose release epoch has come, fail if
+    // there is none. Return the released amount.
+    fn withdraw(&mut self, ictx: RcInvokeContext, address: &UserAddress)
+        -> ProtocolResult<Balance>;
+
+    fn get_stake(&self, address: &UserAddress) -> ProtocolResult<Balance>;
+
+    // The validators weighted by their stake, it is empty if no account
+    // bonds the minimum stake.
+    fn get_validators(&self) -> ProtocolResult<Vec<Validator>>;
+}
+
+/// GovernanceContract keeps the parameters of the chain, the validators
+/// propose new parameters and vote on them. The parameters of an approved
+/// proposal take effect from its activate epoch.
+pub trait GovernanceContract<Adapter: ContractStateAdapter> {
+    // The caller must be a validator, it votes for its own proposal. Return
+    // the id of the proposal.
+    fn propose(
+        &mut self,
+        ictx: RcInvokeContext,
+        activate_epoch_id: u64,
+        params: ChainParams,
+    ) -> ProtocolResult<u64>;
+
+    // The proposal is approved once the validators voting for it hold more
+    // than 2/3 of the vote weight.
+    fn vote(&mut self, ictx: RcInvokeContext, proposal_id: u64) -> ProtocolResult<()>;
+
+    fn get_params(&self) -> ProtocolResult<ChainParams>;
 }
 
 pub trait AccountContract<Adapter: ContractStateAdapter> {
     fn transfer(&mut self, ictx: RcInvokeContext, to: &Address) -> ProtocolResult<()>;
 
+    // Allow the `spender` contract to spend at most `max` of the caller's asset.
+    // Approving again replaces the previous allowance and resets its usage.
+    fn approve(
+        &mut self,
+        ictx: RcInvokeContext,
+        spender: &ContractAddress,
+        id: &AssetID,
+        max: Balance,
+    ) -> ProtocolResult<()>;
+
+    // Spend the asset of `from` on its behalf, the caller must be a contract
+    // approved by `from` and the allowance is drawn down by `amount`.
+    fn transfer_from(
+        &mut self,
+        ictx: RcInvokeContext,
+        from: &Address,
+        to: &Address,
+        id: &AssetID,
+        amount: Balance,
+    ) -> ProtocolResult<()>;
+
     fn inc_nonce(&mut self, ictx: RcInvokeContext) -> ProtocolResult<()>;
 
+    // Record the nonce of a transaction sent by `address`, the same nonce can
+    // not be used again by this address.
+    fn record_nonce(&mut self, address: &Address, nonce: &Hash, timeout: u64)
+        -> ProtocolResult<()>;
+
+    fn contains_nonce(&self, address: &Address, nonce: &Hash) -> ProtocolResult<bool>;
+
+    // Index the recorded nonces of `(address, nonce, timeout)` by their
+    // timeouts, so they can be pruned once they expire.
+    fn index_nonces(&mut self, nonces: Vec<(Address, Hash, u64)>) -> ProtocolResult<()>;
+
+    // Remove the nonces whose timeout is `timeout`, the transactions using
+    // them are rejected by the timeout afterwards anyway.
+    fn prune_nonces(&mut self, timeout: u64) -> ProtocolResult<()>;
+
     fn create_account(&mut self, address: &Address) -> ProtocolResult<Account>;
 
     fn add_balance(
@@ -81,4 +262,11 @@ pub trait AccountContract<Adapter: ContractStateAdapter> {
     fn get_account(&self, address: &Address) -> ProtocolResult<Account>;
 
     fn get_nonce(&self, address: &Address) -> ProtocolResult<u64>;
+
+    fn get_allowance(
+        &self,
+        id: &AssetID,
+        owner: &Address,
+        spender: &ContractAddress,
+    ) -> ProtocolResult<ApprovedInfo>;
 }
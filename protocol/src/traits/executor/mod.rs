@@ -7,12 +7,18 @@ use std::sync::Arc;
 use bytes::Bytes;
 
 use crate::types::{
-    Address, Bloom, CarryingAsset, ContractAddress, Fee, Genesis, Hash, MerkleRoot, Receipt,
-    SignedTransaction,
+    Address, AssetID, Balance, Bloom, CarryingAsset, ChainParams, ContractAddress, CyclesSchedule,
+    Fee, Genesis, Hash, Log, MerkleRoot, Receipt, SignedTransaction, UserAddress, Validator,
 };
 use crate::ProtocolResult;
 
-pub trait TrieDB = cita_trie::DB;
+pub trait TrieDB: cita_trie::DB {
+    /// Called with the state root of every committed epoch, the database can
+    /// prune the state which is not reachable from the recent roots.
+    fn record_root(&self, _state_root: &MerkleRoot) {}
+}
+
+impl TrieDB for cita_trie::MemoryDB {}
 
 #[derive(Clone, Debug)]
 pub struct ExecutorExecResp {
@@ -20,16 +26,80 @@ pub struct ExecutorExecResp {
     pub all_cycles_used: Vec<Fee>,
     pub logs_bloom:      Bloom,
     pub state_root:      MerkleRoot,
+    // The validators elected by the staking contract on the new state.
+    pub validators: Vec<Validator>,
+    // The parameters of the governance contract taking effect from the next
+    // epoch, `None` if the chain has no governance contract.
+    pub chain_params: Option<ChainParams>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExecutorReadResp {
+    pub return_value: Bytes,
+    pub cycles_used:  Fee,
+}
+
+/// The balance of an account with its proof against a state root. The root of
+/// the account contract is proved in the world state trie by `state_proof`,
+/// then the account is proved in the trie of the account contract by
+/// `account_proof`, which proves the absence of the account as well.
+#[derive(Clone, Debug)]
+pub struct ExecutorBalanceProof {
+    pub state_root:    MerkleRoot,
+    pub contract_root: MerkleRoot,
+    pub balance:       Balance,
+    pub state_proof:   Vec<Bytes>,
+    pub account_proof: Vec<Bytes>,
+}
+
+/// The receipt of a transaction with everything recorded during its execution.
+#[derive(Clone, Debug)]
+pub struct ExecutorTrace {
+    pub receipt: Receipt,
+    pub events:  Vec<TraceEvent>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent {
+    // A method of a contract is invoked, including the native contracts.
+    Invoke {
+        address: Address,
+        method:  String,
+        args:    Vec<Bytes>,
+    },
+    // The encoded value of a key in the state of a contract.
+    StateRead {
+        address: Address,
+        key:     Bytes,
+        value:   Option<Bytes>,
+    },
+    StateWrite {
+        address:   Address,
+        key:       Bytes,
+        old_value: Option<Bytes>,
+        new_value: Bytes,
+    },
+    // Cycles charged by the cycles schedule, the cycles of wasm instructions
+    // are not recorded one by one.
+    Cycles {
+        action: String,
+        cycles: u64,
+    },
 }
 
+/// The events are recorded in the order they happen.
+pub type RcTracer = Rc<RefCell<Vec<TraceEvent>>>;
+
 pub trait ExecutorFactory<DB: TrieDB>: Send + Sync {
     fn from_root(
+        &self,
         chain_id: Hash,
         state_root: MerkleRoot,
         db: Arc<DB>,
         epoch_id: u64,
         cycles_price: u64,
         coinbase: Address,
+        intermediate_state_root: bool,
     ) -> ProtocolResult<Box<dyn Executor>>;
 }
 
@@ -37,6 +107,56 @@ pub trait Executor {
     fn create_genesis(&mut self, genesis: &Genesis) -> ProtocolResult<MerkleRoot>;
 
     fn exec(&mut self, signed_txs: Vec<SignedTransaction>) -> ProtocolResult<ExecutorExecResp>;
+
+    /// Check a transaction against the current state before it is included
+    /// in an epoch: the cycles price, the timeout and the nonce. The executor
+    /// gives a failed receipt to a transaction failing these checks, so the
+    /// proposals and the pool reject it beforehand. Neither the signature nor
+    /// the balance is checked.
+    fn check_tx(&self, signed_tx: &SignedTransaction) -> ProtocolResult<()>;
+
+    /// Execute a transaction on the current state and drop all its changes
+    /// afterwards, the receipt shows what would happen if the transaction is
+    /// executed. The transaction is sent by `sender` whatever its public
+    /// key and signature are, and its cycles are neither reserved nor paid,
+    /// so the receipt reports the cycles it uses up to its cycles limit.
+    fn simulate(
+        &mut self,
+        sender: UserAddress,
+        signed_tx: SignedTransaction,
+    ) -> ProtocolResult<Receipt>;
+
+    /// Execute the transactions on the current state and trace the last one,
+    /// the ones before it restore the state it was executed on. All changes
+    /// are dropped afterwards.
+    fn trace(&mut self, signed_txs: Vec<SignedTransaction>) -> ProtocolResult<ExecutorTrace>;
+
+    /// Invoke a contract on the current state without changing it, the
+    /// changes made by the contract are discarded and nothing is committed.
+    fn read(
+        &self,
+        caller: &Address,
+        cycles_limit: &Fee,
+        contract: &ContractAddress,
+        method: &str,
+        args: &[Bytes],
+    ) -> ProtocolResult<ExecutorReadResp>;
+
+    /// Get the balance of an asset with the proof against the committed state
+    /// root, the balance of an account that does not exist is zero.
+    fn get_balance_proof(
+        &self,
+        address: &Address,
+        asset_id: &AssetID,
+    ) -> ProtocolResult<ExecutorBalanceProof>;
+
+    /// The validators elected by the staking contract on the current state,
+    /// it is empty if no account bonds the minimum stake.
+    fn get_validators(&self) -> ProtocolResult<Vec<Validator>>;
+
+    /// The parameters of the governance contract on the current state, they
+    /// take effect from the epoch after the one the state is committed by.
+    fn get_chain_params(&self) -> ProtocolResult<Option<ChainParams>>;
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +169,12 @@ pub struct InvokeContext {
     pub caller:         Address,
     pub carrying_asset: Option<CarryingAsset>,
     pub coinbase:       Address,
+    // The schedule of the epoch, all cycles are charged through it.
+    pub cycles_schedule: CyclesSchedule,
+    // The events emitted during the invocation.
+    pub logs: Vec<Log>,
+    // Set when the transaction is traced.
+    pub tracer: Option<RcTracer>,
 }
 
 pub type RcInvokeContext = Rc<RefCell<InvokeContext>>;
@@ -65,7 +191,7 @@ pub trait Dispatcher {
 
 pub trait ContractSchema {
     type Key: ContractSer + Clone + std::hash::Hash + PartialEq + Eq + PartialOrd + Ord;
-    type Value: ContractSer + Clone;
+    type Value: ContractSer + Clone + Send + 'static;
 }
 
 pub trait ContractSer {
@@ -57,5 +57,9 @@ pub trait MemPoolAdapter: Send + Sync {
 
     async fn check_transaction(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
 
+    /// Record the nonce of a transaction accepted by the pool, the other
+    /// transactions using it are rejected by `check_transaction` afterwards.
+    async fn record_nonce(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
+
     async fn check_storage_exist(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<()>;
 }
@@ -10,16 +10,19 @@ pub struct Epoch {
 
 #[derive(Clone, Debug)]
 pub struct EpochHeader {
-    pub chain_id:          Hash,
-    pub epoch_id:          u64,
-    pub pre_hash:          Hash,
-    pub timestamp:         u64,
-    pub logs_bloom:        Bloom,
-    pub order_root:        MerkleRoot,
-    pub confirm_root:      Vec<MerkleRoot>,
-    pub state_root:        MerkleRoot,
-    pub receipt_root:      Vec<MerkleRoot>,
-    pub cycles_used:       u64,
+    pub chain_id:     Hash,
+    pub epoch_id:     u64,
+    pub pre_hash:     Hash,
+    pub timestamp:    u64,
+    pub logs_bloom:   Bloom,
+    pub order_root:   MerkleRoot,
+    pub confirm_root: Vec<MerkleRoot>,
+    pub state_root:   MerkleRoot,
+    pub receipt_root: Vec<MerkleRoot>,
+    pub cycles_used:  u64,
+    // The base fee of the epoch, every cycle used by its transactions is paid
+    // at this price.
+    pub cycles_price:      u64,
     pub proposer:          UserAddress,
     pub proof:             Proof,
     pub validator_version: u64,
@@ -35,7 +38,7 @@ pub struct Proof {
     pub bitmap:     Bytes,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Validator {
     pub address:        UserAddress,
     pub propose_weight: u8,
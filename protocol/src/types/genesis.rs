@@ -1,11 +1,141 @@
+use bytes::Bytes;
 use serde_derive::Deserialize;
 
+use crate::types::epoch::{Epoch, EpochHeader, Proof, Validator};
+use crate::types::primitive::{ChainParams, CyclesSchedule, Hash, MerkleRoot, UserAddress};
+use crate::types::{Bloom, TypesError};
+use crate::ProtocolResult;
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Genesis {
+    pub chain_id:     String,
     pub timestamp:    u64,
     pub prevhash:     String,
+    pub validators:   Vec<GenesisValidator>,
+    pub consensus:    GenesisConsensus,
     pub system_token: GenesisSystemToken,
     pub state_alloc:  Vec<GenesisStateAlloc>,
+    // The default schedule is used before the first one takes effect.
+    #[serde(default)]
+    pub cycles_schedules: Vec<GenesisCyclesSchedule>,
+    #[serde(default)]
+    pub staking: GenesisStaking,
+}
+
+impl Genesis {
+    pub fn chain_id(&self) -> ProtocolResult<Hash> {
+        Hash::from_hex(&self.chain_id)
+    }
+
+    pub fn validators(&self) -> ProtocolResult<Vec<Validator>> {
+        self.validators
+            .iter()
+            .map(|validator| {
+                Ok(Validator {
+                    address:        UserAddress::from_hex(&validator.address)?,
+                    propose_weight: validator.propose_weight,
+                    vote_weight:    validator.vote_weight,
+                })
+            })
+            .collect()
+    }
+
+    pub fn chain_params(&self) -> ChainParams {
+        let consensus = &self.consensus;
+        let duration = &consensus.duration;
+        ChainParams {
+            cycles_limit:          consensus.cycles_limit,
+            cycles_price:          consensus.cycles_price,
+            interval:              consensus.interval,
+            propose_numerator:     duration.propose_numerator,
+            propose_denominator:   duration.propose_denominator,
+            prevote_numerator:     duration.prevote_numerator,
+            prevote_denominator:   duration.prevote_denominator,
+            precommit_numerator:   duration.precommit_numerator,
+            precommit_denominator: duration.precommit_denominator,
+        }
+    }
+
+    /// The genesis epoch only depends on the genesis and its state root, so
+    /// the nodes of the same chain share the same genesis hash.
+    pub fn epoch(&self, state_root: MerkleRoot) -> ProtocolResult<Epoch> {
+        let validators = self.validators()?;
+        // The first validator is the proposer of the genesis epoch.
+        let proposer = validators
+            .first()
+            .map(|validator| validator.address.clone())
+            .ok_or(TypesError::NoValidator)?;
+
+        let header = EpochHeader {
+            chain_id: self.chain_id()?,
+            epoch_id: 0,
+            pre_hash: Hash::from_empty(),
+            timestamp: self.timestamp,
+            logs_bloom: Bloom::default(),
+            order_root: Hash::from_empty(),
+            confirm_root: vec![],
+            state_root,
+            receipt_root: vec![Hash::from_empty()],
+            cycles_used: 0,
+            cycles_price: self.consensus.cycles_price,
+            proposer,
+            proof: Proof {
+                epoch_id:   0,
+                round:      0,
+                epoch_hash: Hash::from_empty(),
+                signature:  Bytes::new(),
+                bitmap:     Bytes::new(),
+            },
+            validator_version: 0,
+            validators,
+        };
+
+        Ok(Epoch {
+            header,
+            ordered_tx_hashes: vec![],
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GenesisValidator {
+    pub address:        String,
+    pub propose_weight: u8,
+    pub vote_weight:    u8,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GenesisConsensus {
+    pub cycles_limit: u64,
+    pub cycles_price: u64,
+    // The interval of epochs in milliseconds.
+    pub interval: u64,
+    #[serde(default)]
+    pub duration: GenesisDuration,
+}
+
+// The timeouts of the consensus steps as fractions of the interval.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GenesisDuration {
+    pub propose_numerator:     u64,
+    pub propose_denominator:   u64,
+    pub prevote_numerator:     u64,
+    pub prevote_denominator:   u64,
+    pub precommit_numerator:   u64,
+    pub precommit_denominator: u64,
+}
+
+impl Default for GenesisDuration {
+    fn default() -> Self {
+        GenesisDuration {
+            propose_numerator:     24,
+            propose_denominator:   30,
+            prevote_numerator:     6,
+            prevote_denominator:   30,
+            precommit_numerator:   6,
+            precommit_denominator: 30,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -20,23 +150,73 @@ pub struct GenesisStateAsset {
     pub balance:  String,
 }
 
+// The validators of the genesis bond `validator_stake` of the system token
+// from their allocations, at most `max_validators` accounts with the most
+// stake are the validators. The unbonded stake is withdrawable
+// `unbonding_epochs` epochs after the account leaves the validators.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GenesisStaking {
+    pub min_stake:        u64,
+    pub max_validators:   u64,
+    pub validator_stake:  u64,
+    pub unbonding_epochs: u64,
+}
+
+impl Default for GenesisStaking {
+    fn default() -> Self {
+        GenesisStaking {
+            min_stake:        1,
+            max_validators:   21,
+            validator_stake:  1,
+            unbonding_epochs: 100,
+        }
+    }
+}
+
+// The schedule takes effect from the epoch `epoch_id`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GenesisCyclesSchedule {
+    pub epoch_id: u64,
+    pub schedule: CyclesSchedule,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct GenesisSystemToken {
-    pub code:   String,
-    pub name:   String,
+    pub code: String,
+    pub name: String,
     pub symbol: String,
     pub supply: u64,
+    #[serde(default)]
+    pub decimals: u64,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Genesis;
+    use bytes::Bytes;
+
+    use super::{Genesis, GenesisDuration};
+    use crate::codec::ProtocolCodecSync;
+    use crate::types::epoch::Epoch;
+    use crate::types::primitive::{CyclesSchedule, Hash, MerkleRoot};
 
     #[test]
     fn test_name() {
         let genesis_string = r#"{
+            "chain_id": "b6a4d7da21443f5e816e8700eea87610e6d769657d6b8ec73028457bf2ca4036",
             "timestamp": 100000,
             "prevhash": "0x0000000000",
+            "validators": [
+                {
+                    "address": "10f8389d774afdad8755ef8e629e5a154fddc6325a",
+                    "propose_weight": 1,
+                    "vote_weight": 1
+                }
+            ],
+            "consensus": {
+                "cycles_limit": 99999999,
+                "cycles_price": 1,
+                "interval": 3000
+            },
             "system_token": {
                 "code": "",
                 "name": "Muta system token",
@@ -56,4 +236,149 @@ mod tests {
 
         let _: Genesis = serde_json::from_str(genesis_string).unwrap();
     }
+
+    #[test]
+    fn test_chain_params() {
+        let genesis_string = r#"{
+            "chain_id": "b6a4d7da21443f5e816e8700eea87610e6d769657d6b8ec73028457bf2ca4036",
+            "timestamp": 100000,
+            "prevhash": "0x0000000000",
+            "validators": [],
+            "consensus": {
+                "cycles_limit": 99999999,
+                "cycles_price": 2,
+                "interval": 3000
+            },
+            "system_token": {
+                "code": "",
+                "name": "Muta system token",
+                "symbol": "MST",
+                "supply": 21000000000
+            },
+            "state_alloc": []
+        }"#;
+
+        let mut genesis: Genesis = serde_json::from_str(genesis_string).unwrap();
+        let params = genesis.chain_params();
+        assert_eq!(params.cycles_limit, 99_999_999);
+        assert_eq!(params.cycles_price, 2);
+        assert_eq!(params.interval, 3000);
+        // The timeouts have the default values.
+        assert_eq!(params.propose_numerator, 24);
+        assert_eq!(params.propose_denominator, 30);
+
+        genesis.consensus.duration = GenesisDuration {
+            propose_numerator:     15,
+            propose_denominator:   20,
+            prevote_numerator:     3,
+            prevote_denominator:   20,
+            precommit_numerator:   2,
+            precommit_denominator: 20,
+        };
+        let params = genesis.chain_params();
+        assert_eq!(params.propose_numerator, 15);
+        assert_eq!(params.precommit_numerator, 2);
+        assert_eq!(params.precommit_denominator, 20);
+    }
+
+    #[test]
+    fn test_cycles_schedules() {
+        let genesis_string = r#"{
+            "chain_id": "b6a4d7da21443f5e816e8700eea87610e6d769657d6b8ec73028457bf2ca4036",
+            "timestamp": 100000,
+            "prevhash": "0x0000000000",
+            "validators": [
+                {
+                    "address": "10f8389d774afdad8755ef8e629e5a154fddc6325a",
+                    "propose_weight": 1,
+                    "vote_weight": 1
+                }
+            ],
+            "consensus": {
+                "cycles_limit": 99999999,
+                "cycles_price": 1,
+                "interval": 3000
+            },
+            "system_token": {
+                "code": "",
+                "name": "Muta system token",
+                "symbol": "MST",
+                "supply": 21000000000
+            },
+            "state_alloc": [],
+            "cycles_schedules": [
+                {
+                    "epoch_id": 100,
+                    "schedule": {
+                        "base": 21,
+                        "transfer": 42
+                    }
+                }
+            ]
+        }"#;
+
+        let genesis: Genesis = serde_json::from_str(genesis_string).unwrap();
+        let schedule = &genesis.cycles_schedules[0];
+        assert_eq!(schedule.epoch_id, 100);
+        assert_eq!(schedule.schedule.base, 21);
+        assert_eq!(schedule.schedule.transfer, 42);
+        // The missing fields have the default values.
+        assert_eq!(schedule.schedule.deploy, CyclesSchedule::default().deploy);
+    }
+
+    #[test]
+    fn test_genesis_epoch() {
+        let genesis_string = r#"{
+            "chain_id": "b6a4d7da21443f5e816e8700eea87610e6d769657d6b8ec73028457bf2ca4036",
+            "timestamp": 100000,
+            "prevhash": "0x0000000000",
+            "validators": [
+                {
+                    "address": "10f8389d774afdad8755ef8e629e5a154fddc6325a",
+                    "propose_weight": 1,
+                    "vote_weight": 2
+                },
+                {
+                    "address": "103e9b982b443592ffc3d4c2a484c220fb3e29e2e4",
+                    "propose_weight": 3,
+                    "vote_weight": 4
+                }
+            ],
+            "consensus": {
+                "cycles_limit": 99999999,
+                "cycles_price": 1,
+                "interval": 3000
+            },
+            "system_token": {
+                "code": "",
+                "name": "Muta system token",
+                "symbol": "MST",
+                "supply": 21000000000
+            },
+            "state_alloc": []
+        }"#;
+
+        let genesis: Genesis = serde_json::from_str(genesis_string).unwrap();
+        let epoch = genesis.epoch(MerkleRoot::from_empty()).unwrap();
+        assert_eq!(epoch.header.chain_id, genesis.chain_id().unwrap());
+        assert_eq!(epoch.header.timestamp, 100_000);
+        assert_eq!(epoch.header.validators.len(), 2);
+        assert_eq!(epoch.header.validators[1].propose_weight, 3);
+        assert_eq!(epoch.header.validators[1].vote_weight, 4);
+        assert_eq!(
+            epoch.header.proposer,
+            epoch.header.validators[0].address.clone()
+        );
+
+        // The genesis hash is derived from the genesis and the state root.
+        let hash = |epoch: Epoch| Hash::digest(epoch.encode_sync().unwrap());
+        let other = genesis.epoch(MerkleRoot::from_empty()).unwrap();
+        assert_eq!(hash(epoch.clone()), hash(other));
+        let other = genesis.epoch(Hash::digest(Bytes::from("state"))).unwrap();
+        assert_ne!(hash(epoch), hash(other));
+
+        let mut genesis = genesis;
+        genesis.validators.clear();
+        assert!(genesis.epoch(MerkleRoot::from_empty()).is_err());
+    }
 }
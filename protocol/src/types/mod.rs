@@ -12,14 +12,20 @@ use crate::{ProtocolError, ProtocolErrorKind};
 
 pub use epoch::{Epoch, EpochHeader, EpochId, Pill, Proof, Validator};
 pub use ethbloom::{Bloom, BloomRef, Input as BloomInput};
-pub use genesis::{Genesis, GenesisStateAlloc, GenesisStateAsset};
+pub use genesis::{
+    Genesis, GenesisConsensus, GenesisCyclesSchedule, GenesisDuration, GenesisStaking,
+    GenesisStateAlloc, GenesisStateAsset, GenesisSystemToken, GenesisValidator,
+};
 pub use primitive::{
-    Account, Address, ApprovedInfo, Asset, AssetID, AssetInfo, Balance, ContractAccount,
-    ContractAddress, ContractType, Fee, Hash, MerkleRoot, UserAccount, UserAddress,
-    GENESIS_EPOCH_ID,
+    next_cycles_price, Account, Address, ApprovedInfo, Asset, AssetID, AssetInfo, Balance,
+    ChainParams, ContractAccount, ContractAddress, ContractType, CyclesSchedule, Fee, Hash,
+    MerkleRoot, UserAccount, UserAddress, GENESIS_EPOCH_ID,
+};
+pub use receipt::{logs_bloom, Log, Receipt, ReceiptResult};
+pub use transaction::{
+    CarryingAsset, DeployAsset, MultiSignature, RawTransaction, SignedTransaction,
+    TransactionAction,
 };
-pub use receipt::{Receipt, ReceiptResult};
-pub use transaction::{CarryingAsset, RawTransaction, SignedTransaction, TransactionAction};
 
 #[derive(Debug, Display, From)]
 pub enum TypesError {
@@ -31,6 +37,22 @@ pub enum TypesError {
 
     #[display(fmt = "{:?} is an invalid address", address)]
     InvalidAddress { address: String },
+
+    #[display(fmt = "There is no validator in the genesis")]
+    NoValidator,
+
+    #[display(
+        fmt = "Invalid multi-signature account, threshold {} of {} public keys",
+        threshold,
+        pubkeys
+    )]
+    InvalidMultiSig { threshold: u8, pubkeys: usize },
+
+    #[display(
+        fmt = "Public key {} appears twice in the multi-signature account",
+        pubkey
+    )]
+    DuplicatePubkey { pubkey: String },
 }
 
 impl Error for TypesError {}
@@ -1,11 +1,12 @@
 #![allow(clippy::all)]
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 
 use bytes::Bytes;
 use hasher::{Hasher, HasherKeccak};
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
+use serde_derive::Deserialize;
 
 use crate::types::TypesError;
 use crate::ProtocolResult;
@@ -99,6 +100,8 @@ const APP_CONTRACT_ADDRESS_MAGIC: u8 = 0x21;
 const LIBRARY_CONTRACT_ADDRESS_MAGIC: u8 = 0x22;
 /// Magic number of native contract address.
 const NATIVE_CONTRACT_ADDRESS_MAGIC: u8 = 0x23;
+/// The max number of public keys of a multi-signature account.
+const MAX_MULTISIG_PUBKEYS: usize = 16;
 
 /// Contract type
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -228,6 +231,39 @@ impl UserAddress {
         Self::from_bytes(decoded.into())
     }
 
+    /// The address of a multi-signature account is derived from the threshold
+    /// and the public keys in order. Every key is prefixed by its length, so
+    /// two different lists of keys never derive the same bytes. A key can't be
+    /// listed twice, or its signature would count twice for the threshold.
+    pub fn from_multisig(threshold: u8, pubkeys: &[Bytes]) -> ProtocolResult<Self> {
+        if threshold == 0
+            || usize::from(threshold) > pubkeys.len()
+            || pubkeys.len() > MAX_MULTISIG_PUBKEYS
+        {
+            return Err(TypesError::InvalidMultiSig {
+                threshold,
+                pubkeys: pubkeys.len(),
+            }
+            .into());
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(pubkey) = pubkeys.iter().find(|pubkey| !seen.insert(*pubkey)) {
+            return Err(TypesError::DuplicatePubkey {
+                pubkey: hex::encode(pubkey),
+            }
+            .into());
+        }
+
+        let mut bytes = vec![threshold];
+        for pubkey in pubkeys {
+            bytes.extend_from_slice(&(pubkey.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(pubkey);
+        }
+
+        Self::from_pubkey_bytes(Bytes::from(bytes))
+    }
+
     pub fn from_hex(s: &str) -> ProtocolResult<Self> {
         let s = clean_0x(s);
         let bytes = hex::decode(s).map_err(TypesError::from)?;
@@ -342,6 +378,7 @@ pub struct Asset {
     pub name:            String,
     pub symbol:          String,
     pub supply:          Balance,
+    pub decimals:        u64,
     pub manage_contract: ContractAddress,
     pub storage_root:    MerkleRoot,
 }
@@ -352,6 +389,91 @@ pub struct Fee {
     pub cycle:    u64,
 }
 
+/// The cycles charged by the executor. `base` is charged for every
+/// transaction, `payload_byte` for every byte of the encoded transaction and
+/// `state_byte` for every byte written to the state. `host_call` is charged
+/// for every call of a contract to the state, events or other contracts, and
+/// `host_byte` for every byte passed by such a call. The others are charged
+/// for the actions.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CyclesSchedule {
+    pub base:         u64,
+    pub payload_byte: u64,
+    pub state_byte:   u64,
+    pub transfer:     u64,
+    pub approve:      u64,
+    pub register:     u64,
+    pub mint:         u64,
+    pub burn:         u64,
+    pub deploy:       u64,
+    pub call:         u64,
+    pub host_call:    u64,
+    pub host_byte:    u64,
+}
+
+impl Default for CyclesSchedule {
+    fn default() -> Self {
+        CyclesSchedule {
+            base:         0,
+            payload_byte: 0,
+            state_byte:   1,
+            transfer:     210,
+            approve:      210,
+            register:     2100,
+            mint:         210,
+            burn:         210,
+            deploy:       21000,
+            call:         210,
+            host_call:    21,
+            host_byte:    1,
+        }
+    }
+}
+
+/// The parameters every node of the chain must agree on. They are initialized
+/// by the genesis and changed by the governance contract afterwards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainParams {
+    pub cycles_limit: u64,
+    // The base fee never drops below it.
+    pub cycles_price: u64,
+    // The interval of epochs in milliseconds.
+    pub interval: u64,
+    // The timeouts of the consensus steps as fractions of the interval.
+    pub propose_numerator:     u64,
+    pub propose_denominator:   u64,
+    pub prevote_numerator:     u64,
+    pub prevote_denominator:   u64,
+    pub precommit_numerator:   u64,
+    pub precommit_denominator: u64,
+}
+
+/// Calculate the base fee of the next epoch. The price moves towards the
+/// point where an epoch uses half of its cycles limit, changing by at most
+/// one eighth each epoch, and never drops below 1.
+pub fn next_cycles_price(cycles_price: u64, cycles_used: u64, cycles_limit: u64) -> u64 {
+    let target = u128::from(cycles_limit / 2);
+    if target == 0 {
+        return cycles_price.max(1);
+    }
+
+    let price = u128::from(cycles_price);
+    let used = u128::from(cycles_used);
+    let next = if used > target {
+        let delta = (price * (used - target) / target / 8).max(1);
+        price.saturating_add(delta)
+    } else {
+        price.saturating_sub(price * (target - used) / target / 8)
+    };
+
+    if next > u128::from(u64::max_value()) {
+        u64::max_value()
+    } else {
+        (next as u64).max(1)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Account {
     User(UserAccount),
@@ -424,6 +546,46 @@ mod tests {
         assert_eq!(addr.as_hex(), expect_addr);
     }
 
+    #[test]
+    fn test_from_multisig() {
+        let pubkeys = vec![
+            "031313016e9670deb49779c1b0c646d6a25a545712658f9781995f623bcd0d0b3d",
+            "02a0a3f6d3b0d2d6c5a0f7c59d32ce4a7b7d2a5f6f8bcb47c7a4fd5b6e9b1b3e8f",
+            "03f2a6c4b1e8d7a9c3b5e1f0d2c4a6b8e0f1d3c5a7b9e1f3d5c7a9b1e3f5d7c9a1",
+        ]
+        .into_iter()
+        .map(|pubkey| Bytes::from(hex::decode(pubkey).unwrap()))
+        .collect::<Vec<_>>();
+
+        let addr = UserAddress::from_multisig(2, &pubkeys).unwrap();
+        assert_ne!(addr, UserAddress::from_multisig(3, &pubkeys).unwrap());
+        assert_ne!(
+            addr,
+            UserAddress::from_pubkey_bytes(pubkeys[0].clone()).unwrap()
+        );
+
+        let mut reordered = pubkeys.clone();
+        reordered.swap(0, 1);
+        assert_ne!(addr, UserAddress::from_multisig(2, &reordered).unwrap());
+
+        // The same bytes split into different keys derive another address.
+        let mut resplit = pubkeys.clone();
+        let mut first = resplit[0].to_vec();
+        first.extend_from_slice(&resplit[1][..1]);
+        resplit[0] = Bytes::from(first);
+        resplit[1] = resplit[1].slice_from(1);
+        assert_ne!(addr, UserAddress::from_multisig(2, &resplit).unwrap());
+
+        assert!(UserAddress::from_multisig(0, &pubkeys).is_err());
+        assert!(UserAddress::from_multisig(4, &pubkeys).is_err());
+
+        // A key listed twice would sign twice.
+        let mut duplicated = pubkeys.clone();
+        duplicated[2] = pubkeys[0].clone();
+        let err = UserAddress::from_multisig(2, &duplicated).unwrap_err();
+        assert!(err.to_string().contains("DuplicatePubkey"));
+    }
+
     #[test]
     fn test_address() {
         // account address
@@ -1,7 +1,8 @@
 use bytes::Bytes;
 
 use crate::types::{
-    AssetID, Balance, Bloom, ContractAddress, ContractType, Fee, Hash, MerkleRoot, UserAddress,
+    AssetID, Balance, Bloom, BloomInput, ContractAddress, ContractType, Fee, Hash, MerkleRoot,
+    UserAddress,
 };
 
 #[derive(Clone, Debug)]
@@ -11,6 +12,34 @@ pub struct Receipt {
     pub tx_hash:     Hash,
     pub cycles_used: Fee,
     pub result:      ReceiptResult,
+    pub logs:        Vec<Log>,
+}
+
+/// An event emitted by a contract, `topics` are indexed by the bloom so that
+/// the logs can be filtered without decoding `data`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Log {
+    pub address: ContractAddress,
+    pub topics:  Vec<Hash>,
+    pub data:    Bytes,
+}
+
+impl Log {
+    pub fn accrue_bloom(&self, bloom: &mut Bloom) {
+        bloom.accrue(BloomInput::Raw(self.address.as_bytes().as_ref()));
+        for topic in self.topics.iter() {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes().as_ref()));
+        }
+    }
+}
+
+/// Compute the bloom of the logs.
+pub fn logs_bloom<'a, I: IntoIterator<Item = &'a Log>>(logs: I) -> Bloom {
+    let mut bloom = Bloom::default();
+    for log in logs {
+        log.accrue_bloom(&mut bloom);
+    }
+    bloom
 }
 
 #[derive(Clone, Debug)]
@@ -39,4 +68,8 @@ pub enum ReceiptResult {
         system: String,
         user:   String,
     },
+    // The results of the actions in a batch, in order.
+    Batch {
+        results: Vec<ReceiptResult>,
+    },
 }
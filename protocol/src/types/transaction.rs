@@ -3,6 +3,7 @@ use bytes::Bytes;
 use crate::types::primitive::{
     AssetID, Balance, ContractAddress, ContractType, Fee, Hash, UserAddress,
 };
+use crate::ProtocolResult;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RawTransaction {
@@ -10,7 +11,10 @@ pub struct RawTransaction {
     pub nonce:    Hash,
     pub timeout:  u64,
     pub fee:      Fee,
-    pub action:   TransactionAction,
+    // The highest cycles price the sender pays, the transaction is rejected
+    // if the base fee of the epoch is higher.
+    pub max_cycles_price: u64,
+    pub action:           TransactionAction,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -27,6 +31,8 @@ pub enum TransactionAction {
     Deploy {
         code:          Bytes,
         contract_type: ContractType,
+        // Required by the asset contract.
+        asset: Option<DeployAsset>,
     },
     Call {
         contract:       ContractAddress,
@@ -34,6 +40,12 @@ pub enum TransactionAction {
         args:           Vec<Bytes>,
         carrying_asset: Option<CarryingAsset>,
     },
+    // The actions are executed in order under the signature and the fee of the
+    // transaction, all of them are reverted if any fails. A batch can not be
+    // nested.
+    Batch {
+        actions: Vec<TransactionAction>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -42,10 +54,46 @@ pub struct CarryingAsset {
     pub amount:   Balance,
 }
 
+/// The metadata of a deployed asset, the initial supply is credited to the
+/// deployer. Only the `manage_contract` can mint and burn the asset, it is the
+/// asset contract itself if not given, so the supply is fixed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeployAsset {
+    pub name:            String,
+    pub symbol:          String,
+    pub supply:          Balance,
+    pub decimals:        u64,
+    pub manage_contract: Option<ContractAddress>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SignedTransaction {
     pub raw:       RawTransaction,
     pub tx_hash:   Hash,
     pub pubkey:    Bytes,
     pub signature: Bytes,
+    // Set if the sender is a multi-signature account, `pubkey` and `signature`
+    // are left empty then.
+    pub multisig: Option<MultiSignature>,
+}
+
+impl SignedTransaction {
+    /// The sender is derived from the public key, or from the public keys and
+    /// the threshold of a multi-signature account.
+    pub fn sender(&self) -> ProtocolResult<UserAddress> {
+        match &self.multisig {
+            Some(multisig) => UserAddress::from_multisig(multisig.threshold, &multisig.pubkeys),
+            None => UserAddress::from_pubkey_bytes(self.pubkey.clone()),
+        }
+    }
+}
+
+/// The signatures of an M-of-N account. `signatures` is aligned with
+/// `pubkeys`, an empty signature means the key does not sign, and at least
+/// `threshold` keys must sign the transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiSignature {
+    pub threshold:  u8,
+    pub pubkeys:    Vec<Bytes>,
+    pub signatures: Vec<Bytes>,
 }
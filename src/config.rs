@@ -3,13 +3,14 @@ use std::path::PathBuf;
 
 use serde_derive::Deserialize;
 
-use core_consensus::DurationConfig;
-
 #[derive(Debug, Deserialize)]
 pub struct ConfigGraphQL {
     pub listening_address: SocketAddr,
     pub graphql_uri:       String,
     pub graphiql_uri:      String,
+    // The max cycles a query can consume, the cycles limit of an epoch is
+    // used if it is not set.
+    pub query_cycles_limit: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,34 +31,30 @@ pub struct ConfigMempool {
     pub pool_size:   u64,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ConfigConsensus {
-    pub cycles_limit:  u64,
-    pub cycles_price:  u64,
-    pub interval:      u64,
-    pub duration:      DurationConfig,
-    pub verifier_list: Vec<String>,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct ConfigExecutor {
     pub light: bool,
+    // Record the state root after each transaction in its receipt.
+    #[serde(default)]
+    pub intermediate_state_root: bool,
+    // Keep the state of the last N epochs and prune the older state, nothing
+    // is pruned if it is not set.
+    pub prune_keep_epochs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
-    // chain id
-    pub chain_id: String,
     // crypto
     pub privkey: String,
     // db config
     pub data_path: PathBuf,
+    // The genesis defines the chain id, validators and consensus parameters.
+    pub genesis: PathBuf,
 
-    pub graphql:   ConfigGraphQL,
-    pub network:   ConfigNetwork,
-    pub mempool:   ConfigMempool,
-    pub consensus: ConfigConsensus,
-    pub executor:  ConfigExecutor,
+    pub graphql:  ConfigGraphQL,
+    pub network:  ConfigNetwork,
+    pub mempool:  ConfigMempool,
+    pub executor: ConfigExecutor,
 }
 
 impl Config {
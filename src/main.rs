@@ -4,7 +4,8 @@ mod config;
 
 use std::convert::TryFrom;
 use std::fs::File;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -19,17 +20,21 @@ use core_consensus::message::{
     ProposalMessageHandler, QCMessageHandler, VoteMessageHandler, END_GOSSIP_AGGREGATED_VOTE,
     END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE,
 };
+use core_consensus::{next_validators, DurationConfig};
+use core_executor::snapshot::{
+    export_snapshot, import_snapshot, verify_snapshot_epoch, SnapshotError, StateSnapshot,
+};
 use core_executor::trie::RocksTrieDB;
 use core_executor::TransactionExecutorFactory;
 use core_mempool::{DefaultMemPoolAdapter, HashMemPool};
 use core_network::{NetworkConfig, NetworkService};
-use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
+use core_storage::{adapter::rocks::RocksAdapter, ImplStorage, StorageError};
 
-use protocol::traits::executor::ExecutorFactory;
+use protocol::codec::ProtocolCodecSync;
+use protocol::traits::executor::{ExecutorFactory, TrieDB};
 use protocol::traits::{CurrentConsensusStatus, NodeInfo, Storage};
 use protocol::types::{
-    Address, Bloom, Epoch, EpochHeader, Genesis, Hash, MerkleRoot, Pill, Proof, UserAddress,
-    Validator,
+    next_cycles_price, Address, Epoch, Genesis, Hash, MerkleRoot, Pill, Receipt, UserAddress,
 };
 use protocol::ProtocolResult;
 
@@ -49,38 +54,63 @@ async fn main() {
         .subcommand(
             clap::SubCommand::with_name("init")
                 .about("Initializes a new genesis block and definition for the network")
-                .arg(
-                    clap::Arg::from_usage("<genesis.json> 'expects a genesis file'")
-                        .default_value("./devtools/chain/genesis.json"),
-                ),
+                .arg(clap::Arg::from_usage(
+                    "[genesis.json] 'expects a genesis file, the one in the config by default'",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("export-snapshot")
+                .about("Exports the state of an epoch to a snapshot file")
+                .arg(clap::Arg::from_usage(
+                    "<snapshot> 'the snapshot file to write'",
+                ))
+                .arg(clap::Arg::from_usage(
+                    "-e --epoch=[EPOCH_ID] 'the epoch to export, the latest one by default'",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("import-snapshot")
+                .about("Initializes the node from a snapshot file instead of the genesis")
+                .arg(clap::Arg::from_usage(
+                    "<snapshot> 'the snapshot file to read'",
+                )),
         )
         .get_matches();
     let args_config = matches.value_of("config").unwrap();
-    let cfg: Config = common_config_parser::parse(args_config).unwrap();
-    log::info!("Go with config: {:?}", cfg);
+    let mut cfg: Config = common_config_parser::parse(args_config).unwrap();
 
     // init genesis
     if let Some(matches) = matches.subcommand_matches("init") {
-        let genesis_path = matches.value_of("genesis.json").unwrap();
-        log::info!("Genesis path: {}", genesis_path);
-        handle_init(&cfg, genesis_path).await.unwrap();
+        if let Some(genesis_path) = matches.value_of("genesis.json") {
+            cfg.genesis = PathBuf::from(genesis_path);
+        }
+        log::info!("Genesis path: {:?}", cfg.genesis);
+        handle_init(&cfg).await.unwrap();
     }
+    log::info!("Go with config: {:?}", cfg);
 
-    start(&cfg).await.unwrap();
-}
+    if let Some(matches) = matches.subcommand_matches("export-snapshot") {
+        let snapshot_path = matches.value_of("snapshot").unwrap();
+        let epoch_id = matches
+            .value_of("epoch")
+            .map(|epoch_id| epoch_id.parse::<u64>().unwrap());
+        handle_export_snapshot(&cfg, snapshot_path, epoch_id)
+            .await
+            .unwrap();
+        return;
+    }
 
-async fn handle_init(cfg: &Config, genesis_path: impl AsRef<Path>) -> ProtocolResult<()> {
-    let chain_id = Hash::from_hex(&cfg.chain_id).unwrap();
+    if let Some(matches) = matches.subcommand_matches("import-snapshot") {
+        let snapshot_path = matches.value_of("snapshot").unwrap();
+        log::info!("Snapshot path: {}", snapshot_path);
+        handle_import_snapshot(&cfg, snapshot_path).await.unwrap();
+    }
 
-    // self private key
-    let my_privkey =
-        Secp256k1PrivateKey::try_from(hex::decode(cfg.privkey.clone()).unwrap().as_ref()).unwrap();
-    let my_pubkey = my_privkey.pub_key();
-    let my_address = UserAddress::from_pubkey_bytes(my_pubkey.to_bytes()).unwrap();
+    start(&cfg).await.unwrap();
+}
 
-    // Read genesis.
-    let mut r = File::open(genesis_path).unwrap();
-    let genesis: Genesis = serde_json::from_reader(&mut r).unwrap();
+async fn handle_init(cfg: &Config) -> ProtocolResult<()> {
+    let genesis = read_genesis(&cfg.genesis);
     log::info!("Genesis data: {:?}", genesis);
 
     // Init Block db
@@ -94,69 +124,159 @@ async fn handle_init(cfg: &Config, genesis_path: impl AsRef<Path>) -> ProtocolRe
             log::info!("The Genesis block has been initialized.");
             return Ok(());
         }
-        Err(e) => {
-            if !e.to_string().contains("GetNone") {
-                return Err(e);
-            }
-        }
+        // Nothing is stored yet.
+        Err(e) => match e.downcast_ref::<StorageError>() {
+            Some(StorageError::GetNone) => (),
+            _ => return Err(e),
+        },
     };
 
     // Init trie db
     let path_state = cfg.data_path_for_state();
-    let trie_db = Arc::new(RocksTrieDB::new(path_state, cfg.executor.light).unwrap());
+    let trie_db = Arc::new(open_trie_db(cfg, path_state)?);
 
     // Init genesis
-    let genesis_state_root = {
-        let mut executor = TransactionExecutorFactory::from_root(
-            chain_id.clone(),
-            MerkleRoot::from_empty(),
-            Arc::clone(&trie_db),
-            0,
-            cfg.consensus.cycles_price,
-            Address::User(my_address),
-        )?;
-
-        executor.create_genesis(&genesis)?
+    let genesis_epoch = build_genesis(&genesis, trie_db)?;
+    log::info!(
+        "Genesis hash: {}",
+        Hash::digest(genesis_epoch.encode_sync()?).as_hex()
+    );
+
+    let latest_proof = genesis_epoch.header.proof.clone();
+    storage.insert_epoch(genesis_epoch).await.unwrap();
+    storage.update_latest_proof(latest_proof).await.unwrap();
+    Ok(())
+}
+
+// The genesis epoch is built from the genesis and its state, which is written
+// to `trie_db`.
+fn build_genesis<DB: 'static + TrieDB>(
+    genesis: &Genesis,
+    trie_db: Arc<DB>,
+) -> ProtocolResult<Epoch> {
+    let mut epoch = genesis.epoch(MerkleRoot::from_empty())?;
+
+    let mut executor = new_executor_factory::<DB>().from_root(
+        epoch.header.chain_id.clone(),
+        MerkleRoot::from_empty(),
+        trie_db,
+        0,
+        genesis.consensus.cycles_price,
+        Address::User(epoch.header.proposer.clone()),
+        false,
+    )?;
+    epoch.header.state_root = executor.create_genesis(genesis)?;
+
+    Ok(epoch)
+}
+
+// The native contracts of the chain besides the builtin ones are registered to
+// the factory, e.g. `register_native_contract(address, |state| ...)`. Every
+// node of the chain must register the same contracts.
+fn new_executor_factory<DB: 'static + TrieDB>() -> TransactionExecutorFactory<DB> {
+    TransactionExecutorFactory::new()
+}
+
+fn read_genesis(genesis_path: impl AsRef<Path>) -> Genesis {
+    let mut r = File::open(genesis_path).unwrap();
+    serde_json::from_reader(&mut r).unwrap()
+}
+
+// The snapshot file consists of the epoch, the snapshot of its state and the
+// receipts of its transactions, the node continues from the epoch after
+// importing it.
+async fn handle_export_snapshot(
+    cfg: &Config,
+    snapshot_path: impl AsRef<Path>,
+    epoch_id: Option<u64>,
+) -> ProtocolResult<()> {
+    let rocks_adapter = Arc::new(RocksAdapter::new(cfg.data_path_for_block()).unwrap());
+    let storage = Arc::new(ImplStorage::new(Arc::clone(&rocks_adapter)));
+    let epoch = match epoch_id {
+        Some(epoch_id) => storage.get_epoch_by_epoch_id(epoch_id).await?,
+        None => storage.get_latest_epoch().await?,
     };
 
-    // Build genesis block.
-    let genesis_epoch_header = EpochHeader {
-        chain_id:          chain_id.clone(),
-        epoch_id:          0,
-        pre_hash:          Hash::from_empty(),
-        timestamp:         genesis.timestamp,
-        logs_bloom:        Bloom::default(),
-        order_root:        Hash::from_empty(),
-        confirm_root:      vec![],
-        state_root:        genesis_state_root,
-        receipt_root:      vec![Hash::from_empty()],
-        cycles_used:       0,
-        proposer:          UserAddress::from_hex("100000000000000000000000000000000000000000")
-            .unwrap(),
-        proof:             Proof {
-            epoch_id:   0,
-            round:      0,
-            epoch_hash: Hash::from_empty(),
-            signature:  Bytes::new(),
-            bitmap:     Bytes::new(),
+    let trie_db = Arc::new(RocksTrieDB::new(cfg.data_path_for_state(), false)?);
+    let snapshot = export_snapshot(trie_db, &epoch.header.state_root)?;
+    log::info!(
+        "Export the state of epoch {}, {} contracts",
+        epoch.header.epoch_id,
+        snapshot.contracts.len()
+    );
+
+    // The receipts are kept for the cycles used by the epoch.
+    let receipts = storage
+        .get_receipts(epoch.ordered_tx_hashes.clone())
+        .await?;
+
+    let mut stream = rlp::RlpStream::new_list(3);
+    stream.append(&epoch.encode_sync()?.to_vec());
+    stream.append(&snapshot.encode().to_vec());
+    stream.begin_list(receipts.len());
+    for receipt in receipts.iter() {
+        stream.append(&receipt.encode_sync()?.to_vec());
+    }
+
+    let mut w = File::create(snapshot_path).unwrap();
+    w.write_all(&stream.out()).unwrap();
+    Ok(())
+}
+
+async fn handle_import_snapshot(
+    cfg: &Config,
+    snapshot_path: impl AsRef<Path>,
+) -> ProtocolResult<()> {
+    let rocks_adapter = Arc::new(RocksAdapter::new(cfg.data_path_for_block()).unwrap());
+    let storage = Arc::new(ImplStorage::new(Arc::clone(&rocks_adapter)));
+
+    match storage.get_latest_epoch().await {
+        Ok(_) => {
+            log::info!("The node has been initialized.");
+            return Ok(());
+        }
+        // Nothing is stored yet.
+        Err(e) => match e.downcast_ref::<StorageError>() {
+            Some(StorageError::GetNone) => (),
+            _ => return Err(e),
         },
-        validator_version: 0,
-        validators:        vec![],
     };
-    let latest_proof = genesis_epoch_header.proof.clone();
-    storage
-        .insert_epoch(Epoch {
-            header:            genesis_epoch_header,
-            ordered_tx_hashes: vec![],
-        })
-        .await
-        .unwrap();
-    storage.update_latest_proof(latest_proof).await.unwrap();
+
+    let mut data = vec![];
+    File::open(snapshot_path)
+        .and_then(|mut file| file.read_to_end(&mut data))
+        .map_err(SnapshotError::from)?;
+    let r = rlp::Rlp::new(&data);
+    let epoch_bytes: Vec<u8> = r.val_at(0).map_err(SnapshotError::from)?;
+    let snapshot_bytes: Vec<u8> = r.val_at(1).map_err(SnapshotError::from)?;
+    let receipts_bytes: Vec<Vec<u8>> = r.list_at(2).map_err(SnapshotError::from)?;
+
+    let epoch = Epoch::decode_sync(Bytes::from(epoch_bytes))?;
+    let snapshot = StateSnapshot::decode(Bytes::from(snapshot_bytes))?;
+    let receipts = receipts_bytes
+        .into_iter()
+        .map(|bytes| Receipt::decode_sync(Bytes::from(bytes)))
+        .collect::<ProtocolResult<Vec<_>>>()?;
+    verify_snapshot_epoch(&epoch, &snapshot, &receipts)?;
+
+    let trie_db = Arc::new(open_trie_db(cfg, cfg.data_path_for_state())?);
+    import_snapshot(trie_db, &snapshot)?;
+    log::info!(
+        "Import the state of epoch {}, {} contracts",
+        epoch.header.epoch_id,
+        snapshot.contracts.len()
+    );
+
+    let latest_proof = epoch.header.proof.clone();
+    storage.insert_receipts(receipts).await?;
+    storage.insert_epoch(epoch).await?;
+    storage.update_latest_proof(latest_proof).await?;
     Ok(())
 }
 
 async fn start(cfg: &Config) -> ProtocolResult<()> {
-    let chain_id = Hash::from_hex(&cfg.chain_id).unwrap();
+    let genesis = read_genesis(&cfg.genesis);
+    let chain_id = genesis.chain_id()?;
 
     // self private key
     let my_privkey =
@@ -191,11 +311,47 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
         .listen(cfg.network.listening_address)
         .unwrap();
 
-    // Init mempool
+    // The node must be initialized with the same genesis, check it by the
+    // genesis hash. Its state is rebuilt in memory.
     let current_epoch = storage.get_latest_epoch().await.unwrap();
-    let mempool_adapter = DefaultMemPoolAdapter::<Secp256k1, _, _>::new(
+    assert_eq!(
+        current_epoch.header.chain_id, chain_id,
+        "the chain id is different from the genesis"
+    );
+    // The epochs before an imported snapshot are not stored.
+    if let Ok(genesis_epoch) = storage.get_epoch_by_epoch_id(0).await {
+        let expect = build_genesis(&genesis, Arc::new(cita_trie::MemoryDB::new(false)))?;
+        assert_eq!(
+            Hash::digest(genesis_epoch.encode_sync()?),
+            Hash::digest(expect.encode_sync()?),
+            "the genesis hash is different from the genesis"
+        );
+    }
+
+    // Init trie db
+    let path_state = cfg.data_path_for_state();
+    let trie_db = Arc::new(open_trie_db(cfg, path_state)?);
+
+    // The recorded state roots are lost on restart, record the roots of the
+    // recent epochs again so their state is kept by the pruner.
+    if let Some(keep_epochs) = cfg.executor.prune_keep_epochs {
+        let latest_epoch_id = current_epoch.header.epoch_id;
+        for epoch_id in latest_epoch_id.saturating_sub(keep_epochs)..=latest_epoch_id {
+            // The epochs before an imported snapshot are not stored.
+            if let Ok(epoch) = storage.get_epoch_by_epoch_id(epoch_id).await {
+                trie_db.record_root(&epoch.header.state_root);
+            }
+        }
+    }
+
+    let executor_factory = Arc::new(new_executor_factory::<RocksTrieDB>());
+
+    // Init mempool
+    let mempool_adapter = DefaultMemPoolAdapter::<Secp256k1, _, _, _, _>::new(
         network_service.handle(),
         Arc::clone(&storage),
+        Arc::clone(&trie_db),
+        Arc::clone(&executor_factory),
         cfg.mempool.timeout_gap,
     );
     let mempool = Arc::new(HashMemPool::new(
@@ -205,22 +361,14 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
         mempool_adapter,
     ));
 
-    // Init trie db
-    let path_state = cfg.data_path_for_state();
-    let trie_db = Arc::new(RocksTrieDB::new(path_state, cfg.executor.light).unwrap());
-
     // Init Consensus
-    let consensus_adapter = Arc::new(OverlordConsensusAdapter::<
-        TransactionExecutorFactory,
-        _,
-        _,
-        _,
-        _,
-    >::new(
+    let consensus_adapter = Arc::new(OverlordConsensusAdapter::new(
         Arc::new(network_service.handle()),
         Arc::clone(&mempool),
         Arc::clone(&storage),
         Arc::clone(&trie_db),
+        Arc::clone(&executor_factory),
+        cfg.executor.intermediate_state_root,
     ));
     let node_info = NodeInfo {
         chain_id:     chain_id.clone(),
@@ -234,9 +382,51 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
             propose_hashes: vec![],
         },
     })));
+
+    // The base fee of the next epoch follows the cycles used by the latest one.
+    let latest_cycles_used = storage
+        .get_receipts(current_epoch.ordered_tx_hashes.clone())
+        .await?
+        .iter()
+        .fold(0u64, |acc, receipt| {
+            acc.saturating_add(receipt.cycles_used.cycle)
+        });
+    let latest_executor = executor_factory.from_root(
+        chain_id.clone(),
+        current_header.state_root.clone(),
+        Arc::clone(&trie_db),
+        current_header.epoch_id,
+        current_header.cycles_price,
+        Address::User(current_header.proposer.clone()),
+        false,
+    )?;
+
+    // The parameters of the governance contract on the latest state take
+    // effect from the next epoch, the same as the consensus does after
+    // committing an epoch. The chain without the governance contract keeps
+    // the parameters of the genesis.
+    let state_params = latest_executor.get_chain_params()?;
+    let chain_params = state_params
+        .clone()
+        .unwrap_or_else(|| genesis.chain_params());
+    let min_cycles_price = state_params.map_or(1, |params| params.cycles_price);
+    let next_price = next_cycles_price(
+        current_header.cycles_price,
+        latest_cycles_used,
+        chain_params.cycles_limit,
+    )
+    .max(min_cycles_price);
+
+    // So do the validators elected by the latest state.
+    let elected = latest_executor.get_validators()?;
+    let (validator_set, validator_set_version) = next_validators(
+        &current_header.validators,
+        current_header.validator_version,
+        elected,
+    );
     let current_consensus_status = CurrentConsensusStatus {
-        cycles_price:       cfg.consensus.cycles_price,
-        cycles_limit:       cfg.consensus.cycles_limit,
+        cycles_price:       next_price,
+        cycles_limit:       chain_params.cycles_limit,
         epoch_id:           current_epoch.header.epoch_id + 1,
         prev_hash:          prevhash,
         logs_bloom:         current_header.logs_bloom,
@@ -244,19 +434,23 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
         confirm_root:       current_header.confirm_root.clone(),
         state_root:         current_header.state_root.clone(),
         receipt_root:       current_header.receipt_root.clone(),
-        cycles_used:        current_header.cycles_used,
+        cycles_used:        latest_cycles_used,
         proof:              current_header.proof.clone(),
-        validators:         cfg
-            .consensus
-            .verifier_list
-            .iter()
-            .map(|v| Validator {
-                address:        UserAddress::from_hex(v).unwrap(),
-                propose_weight: 1,
-                vote_weight:    1,
-            })
-            .collect(),
-        consensus_interval: cfg.consensus.interval,
+        validators:         validator_set,
+        validator_version:  validator_set_version,
+        consensus_interval: chain_params.interval,
+        propose_ratio:      (
+            chain_params.propose_numerator,
+            chain_params.propose_denominator,
+        ),
+        prevote_ratio:      (
+            chain_params.prevote_numerator,
+            chain_params.prevote_denominator,
+        ),
+        precommit_ratio:    (
+            chain_params.precommit_numerator,
+            chain_params.precommit_denominator,
+        ),
     };
 
     let overlord_consensus = Arc::new(OverlordConsensus::new(
@@ -289,7 +483,13 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
     runtime::spawn(network_service);
 
     // Init graphql
-    let api_adapter = DefaultAPIAdapter::new(Arc::clone(&mempool), Arc::clone(&storage));
+    let api_adapter = DefaultAPIAdapter::new(
+        Arc::clone(&mempool),
+        Arc::clone(&storage),
+        Arc::clone(&trie_db),
+        Arc::clone(&executor_factory),
+        cfg.graphql.query_cycles_limit,
+    );
     let mut graphql_config = GraphQLConfig::default();
     graphql_config.listening_address = cfg.graphql.listening_address;
     graphql_config.graphql_uri = cfg.graphql.graphql_uri.clone();
@@ -299,10 +499,25 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
     runtime::spawn(core_api::start_graphql(graphql_config, api_adapter));
 
     // Run consensus
+    let duration = DurationConfig::new(
+        chain_params.propose_numerator,
+        chain_params.propose_denominator,
+        chain_params.prevote_numerator,
+        chain_params.prevote_denominator,
+        chain_params.precommit_numerator,
+        chain_params.precommit_denominator,
+    );
     overlord_consensus
-        .run(cfg.consensus.interval, Some(cfg.consensus.duration.clone()))
+        .run(chain_params.interval, Some(duration))
         .await
         .unwrap();
 
     Ok(())
 }
+
+fn open_trie_db(cfg: &Config, path_state: PathBuf) -> ProtocolResult<RocksTrieDB> {
+    match cfg.executor.prune_keep_epochs {
+        Some(keep_epochs) => RocksTrieDB::with_pruning(path_state, keep_epochs as usize),
+        None => RocksTrieDB::new(path_state, cfg.executor.light),
+    }
+}